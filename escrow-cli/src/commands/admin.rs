@@ -0,0 +1,151 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        create_initialize_config_instruction, create_update_config_instruction, derive_config_pda,
+        get_config,
+    },
+};
+use colored::*;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transaction::Transaction,
+};
+
+/// Parse an optional pubkey argument
+fn parse_pubkey(value: Option<&str>, name: &str) -> Result<Option<Pubkey>, EscrowCliError> {
+    value
+        .map(|value| {
+            Pubkey::from_str(value)
+                .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid {}: {}", name, e)))
+        })
+        .transpose()
+}
+
+/// Sign and send a single admin instruction
+async fn send_admin_instruction(
+    network: &str,
+    admin: &Keypair,
+    instruction: Instruction,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&admin.pubkey()),
+        &[admin],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+    Ok(())
+}
+
+pub async fn initialize_config(
+    network: &str,
+    admin: &Keypair,
+    program_id_str: &str,
+    fee_bps: u16,
+    fee_recipient_str: &str,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!(
+        "\n{}",
+        "═══════════════════════════════════".bold().magenta()
+    );
+    println!("{}", "        INITIALIZE CONFIG".bold().magenta());
+    println!("{}", "═══════════════════════════════════".bold().magenta());
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let fee_recipient = Pubkey::from_str(fee_recipient_str)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid fee recipient: {}", e)))?;
+
+    println!("  Config PDA: {}", derive_config_pda(&program_id));
+    println!("  Admin: {}", admin.pubkey());
+    println!("  Fee: {} bps to {}", fee_bps, fee_recipient);
+    let instruction =
+        create_initialize_config_instruction(&program_id, &admin.pubkey(), fee_bps, &fee_recipient);
+    send_admin_instruction(network, admin, instruction, client).await?;
+    println!("\n{}", "✓ Config initialized".green().bold());
+    Ok(())
+}
+
+/// The config fields to change; unset fields are left as they are
+#[derive(Default)]
+pub struct ConfigUpdate<'a> {
+    pub new_admin: Option<&'a str>,
+    pub fee_bps: Option<u16>,
+    pub fee_recipient: Option<&'a str>,
+    pub paused: Option<bool>,
+}
+
+pub async fn update_config(
+    network: &str,
+    admin: &Keypair,
+    program_id_str: &str,
+    update: ConfigUpdate<'_>,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    let ConfigUpdate {
+        new_admin: new_admin_str,
+        fee_bps,
+        fee_recipient: fee_recipient_str,
+        paused,
+    } = update;
+    println!(
+        "\n{}",
+        "═══════════════════════════════════".bold().magenta()
+    );
+    println!("{}", "        UPDATE CONFIG".bold().magenta());
+    println!("{}", "═══════════════════════════════════".bold().magenta());
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let new_admin = parse_pubkey(new_admin_str, "admin")?;
+    let fee_recipient = parse_pubkey(fee_recipient_str, "fee recipient")?;
+
+    let config = get_config(client, &program_id).await?.ok_or_else(|| {
+        EscrowCliError::CustomError("Config has not been initialized".to_string())
+    })?;
+    if config.admin != admin.pubkey() {
+        return Err(EscrowCliError::CustomError(format!(
+            "Wallet is not the config admin ({})",
+            config.admin
+        )));
+    }
+    println!(
+        "  Current: admin {}, {} bps fee to {}, paused: {}",
+        config.admin, config.fee_bps, config.fee_recipient, config.paused
+    );
+    let instruction = create_update_config_instruction(
+        &program_id,
+        &admin.pubkey(),
+        new_admin,
+        fee_bps,
+        fee_recipient,
+        paused,
+    );
+    send_admin_instruction(network, admin, instruction, client).await?;
+    match paused {
+        Some(true) => println!("\n{}", "✓ Program paused".yellow().bold()),
+        Some(false) => println!("\n{}", "✓ Program unpaused".green().bold()),
+        None => println!("\n{}", "✓ Config updated".green().bold()),
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        create_approve_instruction, derive_pdas, ensure_token_account, get_token_program,
+        VaultAccounts,
+    },
+};
+use colored::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+/// Approve an approval escrow as one of its approvers; the approval that reaches the threshold
+/// pays its Token A to the recipient
+pub async fn approve_escrow(
+    network: &str,
+    approver: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().green());
+    println!("{}", "        APPROVE ESCROW".bold().green());
+    println!("{}", "═══════════════════════════════════".bold().green());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let maker = Pubkey::from_str(maker_str)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid maker address: {}", e)))?;
+
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
+    println!("Escrow PDA: {}", escrow_pda);
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
+    let (EscrowStatus::InProgress, Some(recipient)) = (escrow_data.status, escrow_data.beneficiary)
+    else {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and is not waiting for approvals",
+            escrow_data.status
+        )));
+    };
+    if !escrow_data.requires_approval() {
+        return Err(EscrowCliError::CustomError(
+            "Escrow does not need approvals".to_string(),
+        ));
+    }
+    match escrow_data
+        .approvers
+        .iter()
+        .find(|entry| entry.key == approver.pubkey())
+    {
+        Some(entry) if entry.approved => {
+            return Err(EscrowCliError::CustomError(format!(
+                "{} has already approved",
+                approver.pubkey()
+            )))
+        }
+        Some(_) => {}
+        None => {
+            return Err(EscrowCliError::CustomError(format!(
+                "{} is not an approver of this escrow",
+                approver.pubkey()
+            )))
+        }
+    }
+    let approvals = escrow_data.approvals() + 1;
+    let threshold = escrow_data.approval_threshold as usize;
+    println!("  Recipient: {}", recipient.to_string().cyan());
+    println!(
+        "  Approvals: {} of {} with yours",
+        approvals.to_string().yellow(),
+        threshold
+    );
+
+    // The deposit is paid to the recipient's associated token account, created if missing.
+    let recipient_token_a_acc = ensure_token_account(
+        client,
+        approver,
+        &recipient,
+        &escrow_data.token_mint_a,
+        "Recipient's Token A",
+    )
+    .await?;
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    let vault = VaultAccounts {
+        maker,
+        mint_a: escrow_data.token_mint_a,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        escrow_id,
+    };
+    let instruction = create_approve_instruction(
+        &program_id,
+        &approver.pubkey(),
+        &vault,
+        &recipient_token_a_acc,
+    );
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&approver.pubkey()),
+        &[approver],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    if approvals >= threshold {
+        println!(
+            "\n{}",
+            "✓ Approved: the escrow is released to its recipient"
+                .green()
+                .bold()
+        );
+    } else {
+        println!(
+            "\n{} {}",
+            "✓ Approved; approvals still needed:".green().bold(),
+            (threshold - approvals).to_string().yellow()
+        );
+    }
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
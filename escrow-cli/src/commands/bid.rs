@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        check_token_account, create_accept_bid_instruction, create_place_bid_instruction,
+        create_withdraw_bid_instruction, derive_bid_pdas, derive_pdas, ensure_token_account,
+        get_config, get_token_program, AcceptBidAccounts, BidAccounts,
+    },
+};
+use colored::*;
+use escrow_native::state::{Bid, Escrow, ReceiveAsset};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transaction::Transaction,
+};
+
+/// Parse a pubkey argument
+fn parse_pubkey(value: &str, name: &str) -> Result<Pubkey, EscrowCliError> {
+    Pubkey::from_str(value)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid {}: {}", name, e)))
+}
+
+/// Fetch and unpack an escrow state account
+async fn get_escrow(client: &RpcClient, escrow_pda: &Pubkey) -> Result<Escrow, EscrowCliError> {
+    let escrow_account = client
+        .get_account(escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })
+}
+
+/// Sign and send a single bid instruction
+async fn send_bid_instruction(
+    network: &str,
+    signer: &Keypair,
+    instruction: Instruction,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&signer.pubkey()),
+        &[signer],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+    Ok(())
+}
+
+/// Lock a counter-offer of Token B for everything an escrow has on offer
+pub async fn place_bid(
+    network: &str,
+    bidder: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    bid_amount: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().cyan());
+    println!("{}", "          PLACE BID".bold().cyan());
+    println!("{}", "═══════════════════════════════════".bold().cyan());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let maker = parse_pubkey(maker_str, "maker address")?;
+    let (_vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
+    let escrow_data = get_escrow(client, &escrow_pda).await?;
+    if escrow_data.receive_asset != ReceiveAsset::Token || escrow_data.is_basket() {
+        return Err(EscrowCliError::CustomError(
+            "Bids are only supported on single-pair Token B escrows".to_string(),
+        ));
+    }
+    println!(
+        "  Escrow offers {} Token A for {} Token B",
+        escrow_data.offered, escrow_data.receive
+    );
+    println!(
+        "  Your bid: {} Token B",
+        bid_amount.to_string().yellow().bold()
+    );
+
+    let token_program_b = get_token_program(client, &escrow_data.token_mint_b).await?;
+    let bidder_token_b_acc = ensure_token_account(
+        client,
+        bidder,
+        &bidder.pubkey(),
+        &escrow_data.token_mint_b,
+        "Token B",
+    )
+    .await?;
+    check_token_account(client, &bidder_token_b_acc, bid_amount).await?;
+    // The maker pays you in Token A if they accept.
+    ensure_token_account(
+        client,
+        bidder,
+        &bidder.pubkey(),
+        &escrow_data.token_mint_a,
+        "Token A",
+    )
+    .await?;
+
+    let bid = BidAccounts {
+        bidder: bidder.pubkey(),
+        mint_b: escrow_data.token_mint_b,
+        bidder_token_b_acc,
+        escrow_pda,
+        token_program_b,
+    };
+    let instruction = create_place_bid_instruction(
+        &program_id,
+        &bid,
+        escrow_id,
+        bid_amount,
+        escrow_data.offered,
+    );
+    send_bid_instruction(network, bidder, instruction, client).await?;
+    println!("\n{}", "✓ Bid placed".green().bold());
+    Ok(())
+}
+
+/// Accept a bid, settling the whole escrow at the bid's price
+pub async fn accept_bid(
+    network: &str,
+    maker: &Keypair,
+    program_id_str: &str,
+    bidder_str: &str,
+    escrow_id: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().green());
+    println!("{}", "          ACCEPT BID".bold().green());
+    println!("{}", "═══════════════════════════════════".bold().green());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let bidder = parse_pubkey(bidder_str, "bidder address")?;
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
+    let escrow_data = get_escrow(client, &escrow_pda).await?;
+    let (bid_pda, _bid_vault_pda) = derive_bid_pdas(&program_id, &escrow_pda, &bidder);
+    let bid_account = client
+        .get_account(&bid_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Bid not found: {}", e)))?;
+    let bid = Bid::unpack_the_slice_data(&bid_account.data)
+        .map_err(|e| EscrowCliError::CustomError(format!("Failed to parse bid: {:?}", e)))?;
+    if bid.offered != escrow_data.offered {
+        return Err(EscrowCliError::CustomError(format!(
+            "Bid is for {} Token A but the escrow now offers {}",
+            bid.offered, escrow_data.offered
+        )));
+    }
+    println!(
+        "  Bid: {} Token B for {} Token A (asked {})",
+        bid.amount.to_string().yellow().bold(),
+        bid.offered,
+        escrow_data.receive
+    );
+
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    let token_program_b = get_token_program(client, &escrow_data.token_mint_b).await?;
+    let maker_token_b_acc = ensure_token_account(
+        client,
+        maker,
+        &maker.pubkey(),
+        &escrow_data.token_mint_b,
+        "Token B",
+    )
+    .await?;
+    let bidder_token_a_acc = ensure_token_account(
+        client,
+        maker,
+        &bidder,
+        &escrow_data.token_mint_a,
+        "Bidder Token A",
+    )
+    .await?;
+    // The protocol fee comes out of the bid, so the fee recipient needs a Token B account.
+    let config = get_config(client, &program_id).await?;
+    let fee_amount = match &config {
+        Some(_) => escrow_data.fee_for(bid.amount).map_err(|e| {
+            EscrowCliError::CustomError(format!("Failed to compute protocol fee: {:?}", e))
+        })?,
+        None => 0,
+    };
+    let fee_account = match (&config, fee_amount) {
+        (Some(config), 1..) => {
+            println!(
+                "  Protocol fee: {} Token B to {}",
+                fee_amount, config.fee_recipient
+            );
+            Some(
+                ensure_token_account(
+                    client,
+                    maker,
+                    &config.fee_recipient,
+                    &escrow_data.token_mint_b,
+                    "Fee recipient Token B",
+                )
+                .await?,
+            )
+        }
+        _ => None,
+    };
+
+    let accept_accounts = AcceptBidAccounts {
+        maker: maker.pubkey(),
+        bidder,
+        mint_a: escrow_data.token_mint_a,
+        mint_b: escrow_data.token_mint_b,
+        maker_token_b_acc,
+        bidder_token_a_acc,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        token_program_b,
+        fee_account,
+    };
+    let instruction = create_accept_bid_instruction(&program_id, &accept_accounts, escrow_id);
+    send_bid_instruction(network, maker, instruction, client).await?;
+    println!("\n{}", "✓ Bid accepted, escrow closed".green().bold());
+    Ok(())
+}
+
+/// Withdraw a bid and get its Token B back
+pub async fn withdraw_bid(
+    network: &str,
+    bidder: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().red());
+    println!("{}", "         WITHDRAW BID".bold().red());
+    println!("{}", "═══════════════════════════════════".bold().red());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let maker = parse_pubkey(maker_str, "maker address")?;
+    // The escrow may already be closed, so everything comes from the bid itself.
+    let (_vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
+    let (bid_pda, _bid_vault_pda) = derive_bid_pdas(&program_id, &escrow_pda, &bidder.pubkey());
+    let bid_account = client
+        .get_account(&bid_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Bid not found: {}", e)))?;
+    let bid = Bid::unpack_the_slice_data(&bid_account.data)
+        .map_err(|e| EscrowCliError::CustomError(format!("Failed to parse bid: {:?}", e)))?;
+    println!(
+        "  Withdrawing {} Token B",
+        bid.amount.to_string().yellow().bold()
+    );
+
+    let token_program_b = get_token_program(client, &bid.token_mint_b).await?;
+    let bidder_token_b_acc = ensure_token_account(
+        client,
+        bidder,
+        &bidder.pubkey(),
+        &bid.token_mint_b,
+        "Token B",
+    )
+    .await?;
+    let instruction = create_withdraw_bid_instruction(
+        &program_id,
+        &BidAccounts {
+            bidder: bidder.pubkey(),
+            mint_b: bid.token_mint_b,
+            bidder_token_b_acc,
+            escrow_pda,
+            token_program_b,
+        },
+    );
+    send_bid_instruction(network, bidder, instruction, client).await?;
+    println!("\n{}", "✓ Bid withdrawn".green().bold());
+    Ok(())
+}
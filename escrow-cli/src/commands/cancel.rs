@@ -2,22 +2,24 @@ use std::str::FromStr;
 
 use crate::{
     error::EscrowCliError,
-    helper::{create_cancel_instruction, derive_pdas},
+    helper::{
+        basket_refund_accounts, create_cancel_instruction, derive_pdas, ensure_token_account,
+        get_token_program, unpack_token_account, VaultAccounts,
+    },
 };
 use colored::*;
+use escrow_native::state::Escrow;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer,
-    transaction::Transaction,
-};
-use spl_token::state::Account;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
 
+/// Cancel an escrow as its maker, or as its operator when `maker_str` names the maker
 pub async fn cancel_escrow(
     network: &str,
-    maker: &Keypair,
+    authority: &Keypair,
     escrow_id: u64,
     program_id_str: &str,
     mint_a_str: &str,
+    maker_str: Option<&str>,
     client: &RpcClient,
 ) -> Result<(), EscrowCliError> {
     println!("\n{}", "═══════════════════════════════════".bold().red());
@@ -29,10 +31,17 @@ pub async fn cancel_escrow(
 
     let mint_a =
         Pubkey::from_str(mint_a_str).map_err(|e| EscrowCliError::InvalidPubkey(e.to_string()))?;
+    // Cancelling someone else's escrow means acting as its operator.
+    let maker = match maker_str {
+        Some(maker_str) => Pubkey::from_str(maker_str)
+            .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid maker address: {}", e)))?,
+        None => authority.pubkey(),
+    };
+    let operator = (maker != authority.pubkey()).then(|| authority.pubkey());
 
     println!("\n{}", "Step 1: Derive PDAs".bold().cyan());
 
-    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
     println!("Vault PDA: {}", vault_pda);
     println!("Escrow PDA: {}", escrow_pda);
     println!("\n{}", "Step 2: Verify Escrow Exists".bold().cyan());
@@ -43,13 +52,16 @@ pub async fn cancel_escrow(
     println!("Escrow account found");
     println!("    Owner: {}", escrow_account.owner);
     println!("    Lamports: {}", escrow_account.lamports);
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
     println!("\n{}", "Step 3: Check Vault Balance".bold().cyan());
     let vault_account = client
         .get_account(&vault_pda)
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("Vault not found: {}", e)))?;
 
-    let vault_data = Account::unpack(&vault_account.data).map_err(|e| {
+    let vault_data = unpack_token_account(&vault_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to parse vault: {}", e))
     })?;
     if vault_data.mint != mint_a {
@@ -63,10 +75,14 @@ pub async fn cancel_escrow(
     println!("    Balance: {} tokens", vault_data.amount);
     println!("    Owner: {}", vault_data.owner);
     println!("\n{}", "Step 4: Find Maker's Token Account".bold().cyan());
-    let maker_token_account = spl_associated_token_account::get_associated_token_address(
-        &maker.pubkey(),
-        &vault_data.mint,
-    );
+    let token_program_a = get_token_program(client, &mint_a).await?;
+    // Token A is refunded to the maker's multisig, if the maker trades for one.
+    let maker_token_account =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &escrow_data.token_owner(),
+            &vault_data.mint,
+            &token_program_a,
+        );
 
     println!("Maker's Token Account: {}", maker_token_account);
     client
@@ -75,24 +91,41 @@ pub async fn cancel_escrow(
         .map_err(|_e| {
             EscrowCliError::TokenAccountNotFound("Maker's token account does not exist".to_string())
         })?;
+    // Each offered basket leg is refunded to the maker's account for its mint.
+    for leg in &escrow_data.basket_offered {
+        ensure_token_account(
+            client,
+            authority,
+            &escrow_data.token_owner(),
+            &leg.mint,
+            "Basket refund",
+        )
+        .await?;
+    }
     println!("\n{}", "Step 5: Build Cancel Instruction".bold().cyan());
-    let cancel_instruction = create_cancel_instruction(
-        &program_id,
-        &maker.pubkey(),
-        &mint_a,
-        &vault_pda,
-        &escrow_pda,
-        &maker_token_account,
+    let vault = VaultAccounts {
+        maker,
+        mint_a,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
         escrow_id,
-    );
+    };
+    let mut cancel_instruction =
+        create_cancel_instruction(&program_id, &vault, &maker_token_account, operator.as_ref());
+    cancel_instruction.accounts.extend(basket_refund_accounts(
+        &program_id,
+        &escrow_data,
+        &token_program_a,
+    ));
     println!("\n{}", "Step 6: Send Transaction".bold().cyan());
     let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
         EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
     })?;
     let cancel_tx = Transaction::new_signed_with_payer(
         &[cancel_instruction],
-        Some(&maker.pubkey()),
-        &[maker],
+        Some(&authority.pubkey()),
+        &[authority],
         recent_blockhash,
     );
     println!("  Sending transaction...");
@@ -132,7 +165,7 @@ pub async fn cancel_escrow(
     );
 
     println!("\n{}", "Result".bold().white());
-    println!("  Your tokens have been returned to your Token account");
+    println!("  The tokens have been returned to the maker's Token account");
     println!("  Escrow account closed and rent reclaimed");
     println!("  Vault account closed");
 
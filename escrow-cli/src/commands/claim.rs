@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{create_claim_vested_instruction, derive_pdas, get_token_program, VaultAccounts},
+};
+use colored::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    sysvar::{self, clock::Clock},
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+/// Claim the Token A a taken vesting escrow has unlocked so far; anyone may pay for the claim
+pub async fn claim_vested(
+    network: &str,
+    payer: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().green());
+    println!("{}", "        CLAIM VESTED".bold().green());
+    println!("{}", "═══════════════════════════════════".bold().green());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let maker = Pubkey::from_str(maker_str)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid maker address: {}", e)))?;
+
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
+    println!("Escrow PDA: {}", escrow_pda);
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
+    let (Some(vesting), Some(beneficiary)) = (escrow_data.vesting, escrow_data.beneficiary) else {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and has nothing vesting",
+            escrow_data.status
+        )));
+    };
+    if escrow_data.status != EscrowStatus::Vesting {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and has nothing vesting",
+            escrow_data.status
+        )));
+    }
+    // Show what a claim would release now, as the program will work it out.
+    let clock_account = client
+        .get_account(&sysvar::clock::id())
+        .await
+        .map_err(|e| EscrowCliError::RpcError(format!("failed to get the clock: {}", e)))?;
+    let clock: Clock = from_account(&clock_account)
+        .ok_or_else(|| EscrowCliError::RpcError("failed to parse the clock".to_string()))?;
+    let unlocked = vesting.unlocked(escrow_data.offered, clock.unix_timestamp);
+    println!("  Beneficiary: {}", beneficiary.to_string().cyan());
+    println!(
+        "  Unlocked: {} of {} Token A ({} already claimed)",
+        unlocked, escrow_data.offered, escrow_data.claimed
+    );
+
+    // Token A is paid to the beneficiary's associated token account.
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    let beneficiary_token_a_acc = get_associated_token_address_with_program_id(
+        &beneficiary,
+        &escrow_data.token_mint_a,
+        &token_program_a,
+    );
+    let vault = VaultAccounts {
+        maker,
+        mint_a: escrow_data.token_mint_a,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        escrow_id,
+    };
+    let instruction =
+        create_claim_vested_instruction(&program_id, &vault, &beneficiary_token_a_acc);
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    println!(
+        "\n{} {} Token A",
+        "✓ Claimed up to".green().bold(),
+        unlocked.to_string().yellow()
+    );
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+use crate::{error::EscrowCliError, helper::decode_event_log};
+use colored::*;
+use escrow_native::events::EscrowEvent;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::signature::Signature;
+
+/// Print the escrow events logged by a transaction
+pub async fn view_events(signature_str: &str, client: &RpcClient) -> Result<(), EscrowCliError> {
+    let signature = Signature::from_str(signature_str)
+        .map_err(|e| EscrowCliError::CustomError(format!("Invalid signature: {}", e)))?;
+    let transaction = client
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                max_supported_transaction_version: Some(0),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| EscrowCliError::RpcError(format!("Transaction not found: {}", e)))?;
+    let logs: Option<Vec<String>> = transaction
+        .transaction
+        .meta
+        .map(|meta| meta.log_messages.into())
+        .unwrap_or_default();
+    let events: Vec<EscrowEvent> = logs
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|line| decode_event_log(line))
+        .collect();
+
+    println!("\n{}", "═══════════════════════════════════".bold().cyan());
+    println!("{}", "         ESCROW EVENTS".bold().cyan());
+    println!("{}", "═══════════════════════════════════".bold().cyan());
+    if events.is_empty() {
+        println!("  No escrow events in this transaction.");
+    }
+    for event in events {
+        match event {
+            EscrowEvent::Created(created) => {
+                println!("\n{}", "Escrow Created".bold().green());
+                println!("  Escrow:   {} (id {})", created.escrow, created.escrow_id);
+                println!("  Maker:    {}", created.maker);
+                println!(
+                    "  Offers:   {} of {}",
+                    created.offered, created.token_mint_a
+                );
+                println!(
+                    "  Asks:     {} of {}",
+                    created.receive, created.token_mint_b
+                );
+            }
+            EscrowEvent::Filled(filled) => {
+                println!("\n{}", "Escrow Filled".bold().green());
+                println!("  Escrow:   {} (id {})", filled.escrow, filled.escrow_id);
+                println!("  Taker:    {}", filled.taker);
+                println!("  Released: {} Token A", filled.token_a_released);
+                println!(
+                    "  Paid:     {} Token B ({} protocol fee)",
+                    filled.token_b_paid, filled.fee_paid
+                );
+                println!(
+                    "  Remaining: {} Token A for {} Token B",
+                    filled.remaining_offered, filled.remaining_receive
+                );
+            }
+            EscrowEvent::Cancelled(cancelled) => {
+                println!("\n{}", "Escrow Cancelled".bold().red());
+                println!(
+                    "  Escrow:   {} (id {})",
+                    cancelled.escrow, cancelled.escrow_id
+                );
+                println!("  Refunded: {} Token A", cancelled.refunded);
+            }
+            EscrowEvent::Expired(expired) => {
+                println!("\n{}", "Escrow Expired".bold().yellow());
+                println!("  Escrow:   {} (id {})", expired.escrow, expired.escrow_id);
+                println!("  Refunded: {} Token A", expired.refunded);
+            }
+            EscrowEvent::Claimed(claimed) => {
+                println!("\n{}", "Vested Tokens Claimed".bold().green());
+                println!("  Escrow:   {} (id {})", claimed.escrow, claimed.escrow_id);
+                println!("  Beneficiary: {}", claimed.beneficiary);
+                println!("  Claimed:  {} Token A", claimed.claimed);
+                println!("  Remaining: {} Token A", claimed.remaining);
+            }
+            EscrowEvent::Settled(settled) => {
+                println!("\n{}", "Escrow Settled".bold().green());
+                println!("  Escrow:   {} (id {})", settled.escrow, settled.escrow_id);
+                match settled.resolved_by {
+                    Some(arbiter) => println!("  Resolved by arbiter: {}", arbiter),
+                    None => println!("  Confirmed by both parties"),
+                }
+                println!(
+                    "  Taker:    {} Token A, {} Token B back",
+                    settled.token_a_to_taker, settled.token_b_to_taker
+                );
+                println!(
+                    "  Maker:    {} Token B, {} Token A back",
+                    settled.token_b_to_maker, settled.token_a_to_maker
+                );
+            }
+            EscrowEvent::MilestoneApproved(approved) => {
+                println!("\n{}", "Milestone Approved".bold().green());
+                println!(
+                    "  Escrow:   {} (id {})",
+                    approved.escrow, approved.escrow_id
+                );
+                println!("  Recipient: {}", approved.recipient);
+                println!(
+                    "  Released: {} Token A for milestone {}",
+                    approved.released, approved.milestone
+                );
+                println!("  Remaining: {} Token A", approved.remaining);
+            }
+            EscrowEvent::Approved(approved) => {
+                println!("\n{}", "Escrow Approved".bold().green());
+                println!(
+                    "  Escrow:   {} (id {})",
+                    approved.escrow, approved.escrow_id
+                );
+                println!("  Approver: {}", approved.approver);
+                println!(
+                    "  Approvals: {} of {}",
+                    approved.approvals, approved.threshold
+                );
+                if approved.approvals >= approved.threshold {
+                    println!("  Released: {} Token A", approved.released);
+                }
+            }
+        }
+    }
+    Ok(())
+}
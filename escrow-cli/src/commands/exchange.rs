@@ -1,26 +1,47 @@
 use std::str::FromStr;
 
-use escrow_native::state::Escrow;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer,
-    transaction::Transaction,
+use escrow_native::{
+    arbiter::find_payment_vault,
+    instructions::instruction::ReleaseFundsArgs,
+    state::{Escrow, EscrowStatus, ReceiveAsset},
 };
-use spl_token::{amount_to_ui_amount, state::Account};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, system_program};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::amount_to_ui_amount;
 
 use crate::{
     error::EscrowCliError,
-    helper::{check_token_account, create_exchange_instruction, derive_pdas, ensure_token_account},
+    helper::{
+        basket_take_accounts, check_sol_balance, check_token_account, create_exchange_instruction,
+        derive_pdas, ensure_token_account, get_config, get_token_program, gross_amount_for_net,
+        net_amount_after_fee, sign_transaction, unpack_mint, unpack_token_account,
+        ExchangeAccounts, MultisigSigners,
+    },
 };
 use colored::*;
+
+/// The escrow to take, and how much of it
+pub struct TakeOrder<'a> {
+    pub maker: &'a Keypair,
+    pub escrow_id: u64,
+    /// Token B to pay, or all that the escrow still asks for when unset
+    pub fill_amount: Option<u64>,
+}
+
 pub async fn exchange_funds(
     network: &str,
     program_id_str: &str,
     taker: &Keypair,
-    maker: &Keypair,
-    escrow_id: u64,
+    order: TakeOrder<'_>,
+    multisig: Option<&MultisigSigners>,
     client: &RpcClient,
 ) -> Result<(), EscrowCliError> {
+    let TakeOrder {
+        maker,
+        escrow_id,
+        fill_amount,
+    } = order;
     println!("\n{}", "═══════════════════════════════════".bold().green());
     println!("{}", "        EXCHANGE ESCROW (TAKE)".bold().green());
     println!("{}", "═══════════════════════════════════".bold().green());
@@ -40,6 +61,12 @@ pub async fn exchange_funds(
         EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
     })?;
 
+    if escrow_data.status != EscrowStatus::Open {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and can no longer be taken",
+            escrow_data.status
+        )));
+    }
     println!("  Escrow found!");
     println!("    Maker: {}", escrow_data.maker);
     println!("    Token A Mint: {}", escrow_data.token_mint_a);
@@ -50,7 +77,7 @@ pub async fn exchange_funds(
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("Mint A not found: {}", e)))?;
 
-    let mint_a_data = spl_token::state::Mint::unpack(&mint_a_account.data).map_err(|e| {
+    let mint_a_data = unpack_mint(&mint_a_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to parse Mint A: {}", e))
     })?;
     let mint_b_account = client
@@ -58,7 +85,7 @@ pub async fn exchange_funds(
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("Mint B not found: {}", e)))?;
 
-    let mint_b_data = spl_token::state::Mint::unpack(&mint_b_account.data).map_err(|e| {
+    let mint_b_data = unpack_mint(&mint_b_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to parse Mint B: {}", e))
     })?;
     println!("\n{}", "Step 4: Fetch Vault Details".bold().cyan());
@@ -66,77 +93,252 @@ pub async fn exchange_funds(
         .get_account(&vault_pda)
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("Vault not found: {}", e)))?;
-    let vault_data = Account::unpack(&vault_account.data).map_err(|e| {
+    let vault_data = unpack_token_account(&vault_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to parse vault: {}", e))
     })?;
 
-    let offered_amount = amount_to_ui_amount(vault_data.amount, mint_a_data.decimals);
-    let requested_amount = amount_to_ui_amount(escrow_data.receive, mint_b_data.decimals);
+    // Fill the whole escrow unless a partial fill amount was requested.
+    let fill_amount = fill_amount.unwrap_or(escrow_data.receive);
+    if fill_amount == 0 || fill_amount > escrow_data.receive {
+        return Err(EscrowCliError::CustomError(format!(
+            "Fill amount must be between 1 and the remaining {} Token B",
+            escrow_data.receive
+        )));
+    }
+    if escrow_data.is_basket() && fill_amount != escrow_data.receive {
+        return Err(EscrowCliError::CustomError(
+            "A basket escrow can only be filled in full".to_string(),
+        ));
+    }
+    if escrow_data.is_nft() && fill_amount != escrow_data.receive {
+        return Err(EscrowCliError::CustomError(
+            "An NFT can only be bought for the full price".to_string(),
+        ));
+    }
+    if escrow_data.is_vesting() && fill_amount != escrow_data.receive {
+        return Err(EscrowCliError::CustomError(
+            "A vesting escrow can only be filled in full".to_string(),
+        ));
+    }
+    if escrow_data.is_arbitrated() && fill_amount != escrow_data.receive {
+        return Err(EscrowCliError::CustomError(
+            "An arbiter escrow can only be filled in full".to_string(),
+        ));
+    }
+    // Token A is pro-rated at the maker's price; the final fill empties the vault.
+    let release_amount = if fill_amount == escrow_data.receive {
+        vault_data.amount
+    } else {
+        (escrow_data.offered as u128 * fill_amount as u128 / escrow_data.receive as u128) as u64
+    };
+    // The protocol fee, if one is configured, is taken out of the maker's share of the fill.
+    let config = get_config(client, &program_id).await?;
+    let fee_amount = match &config {
+        Some(_) => escrow_data.fee_for(fill_amount).map_err(|e| {
+            EscrowCliError::CustomError(format!("Failed to compute protocol fee: {:?}", e))
+        })?,
+        None => 0,
+    };
+    // A Token-2022 transfer fee on Token B is paid by the taker on top of each transfer.
+    let payment_amount =
+        gross_amount_for_net(client, &escrow_data.token_mint_b, fill_amount - fee_amount).await?
+            + gross_amount_for_net(client, &escrow_data.token_mint_b, fee_amount).await?;
+    // The quoted amounts become the taker's slippage limits, so the take fails rather than
+    // settle on worse terms if a transfer fee changes before it lands.
+    let token_a_out =
+        net_amount_after_fee(client, &escrow_data.token_mint_a, release_amount).await?;
+    let offered_amount = amount_to_ui_amount(release_amount, mint_a_data.decimals);
+    let requested_amount = amount_to_ui_amount(payment_amount, mint_b_data.decimals);
     println!("  Token A decimals: {}", mint_a_data.decimals);
     println!("  Token B decimals: {}", mint_b_data.decimals);
 
     // Display exchange terms
-    println!("\n{}", "Step 5: Exchange Terms".bold().cyan());
-    println!(
-        "  You will RECEIVE: {} Token A",
-        offered_amount.to_string().yellow().bold()
-    );
+    // An NFT sale is shown as the NFT itself rather than an amount of Token A.
+    let offered_asset = if escrow_data.is_nft() {
+        format!("NFT {}", escrow_data.token_mint_a)
+    } else {
+        format!("{} Token A", offered_amount)
+    };
+    if escrow_data.is_nft() {
+        println!("\n{}", "Step 5: NFT Sale Terms".bold().cyan());
+    } else {
+        println!("\n{}", "Step 5: Exchange Terms".bold().cyan());
+    }
+    println!("  You will RECEIVE: {}", offered_asset.yellow().bold());
+    if let Some(vesting) = escrow_data.vesting {
+        println!(
+            "    unlocking linearly from {} to {} (unix time); claim it with `claim`",
+            vesting.start, vesting.end
+        );
+    }
+    if let Some(arbiter) = escrow_data.arbiter {
+        println!(
+            "    held with your payment until you both `confirm`, or arbiter {} resolves it",
+            arbiter
+        );
+    }
+    let payment_asset = match escrow_data.receive_asset {
+        ReceiveAsset::Token => "Token B",
+        ReceiveAsset::NativeSol => "SOL",
+    };
     println!(
-        "  You will SEND: {} Token B",
-        requested_amount.to_string().yellow().bold()
+        "  You will SEND: {} {}",
+        requested_amount.to_string().yellow().bold(),
+        payment_asset
     );
+    for leg in &escrow_data.basket_offered {
+        println!("  You will RECEIVE: {} of {}", leg.amount, leg.mint);
+    }
+    for leg in &escrow_data.basket_requested {
+        println!("  You will SEND: {} of {}", leg.amount, leg.mint);
+    }
     // Check/create taker's token accounts
     println!("\n{}", "Step 6: Setup Taker Token Accounts".bold().cyan());
-    // Taker needs Token A account (to receive)
-    let taker_token_a_acc = ensure_token_account(
-        &client,
-        &taker,
-        &taker.pubkey(),
+    // The tokens are traded from the multisig's accounts when a multisig owns them
+    let token_owner = multisig.map_or(taker.pubkey(), |multisig| multisig.multisig);
+    if let Some(multisig) = multisig {
+        println!("  Trading for multisig: {}", multisig.multisig);
+    }
+    // The program creates the taker's Token A account in the exchange itself if it is missing.
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    let taker_token_a_acc = get_associated_token_address_with_program_id(
+        &token_owner,
         &escrow_data.token_mint_a,
-        "Token A",
-    )
-    .await
-    .map_err(|e| {
-        EscrowCliError::TokenAccountNotFound(format!("Taker A Account Not Found:{}", e))
-    })?;
+        &token_program_a,
+    );
     println!("  Token A account: {}", taker_token_a_acc);
 
-    // Taker needs Token B account (to send)
-    let taker_token_b_acc = ensure_token_account(
-        client,
-        taker,
-        &taker.pubkey(),
-        &escrow_data.token_mint_b,
-        "Token B (send)",
-    )
-    .await
-    .map_err(|e| {
-        EscrowCliError::TokenAccountCreation(format!("Failed to setup Token B account: {}", e))
-    })?;
-    println!("  Token B account: {}", taker_token_b_acc);
-    // Verify taker has enough Token B
-    let taker_token_b_data = check_token_account(&client, &taker_token_b_acc, escrow_data.receive)
-        .await
-        .map_err(|e| {
-            EscrowCliError::CustomError(format!("Failed to get taker token b balance:{}", e))
-        })?;
-    println!(
-        "  Balance: {} Token B (needed: {})",
-        amount_to_ui_amount(taker_token_b_data, mint_b_data.decimals),
-        requested_amount
-    );
-    println!("  Sufficient balance confirmed!");
-    let maker_token_b_acc = ensure_token_account(
-        &client,
-        maker,
-        &maker.pubkey(),
-        &escrow_data.token_mint_b,
-        "Token B",
-    )
-    .await
-    .map_err(|e| {
-        EscrowCliError::TokenAccountCreation(format!("Failed to setup Token A account: {}", e))
-    })?;
+    let (taker_token_b_acc, maker_token_b_acc, token_program_b) = match escrow_data.receive_asset {
+        // SOL is paid straight from the taker's wallet to the maker's.
+        ReceiveAsset::NativeSol => {
+            if multisig.is_some() {
+                return Err(EscrowCliError::CustomError(
+                    "A multisig can only take escrows paid in Token B".to_string(),
+                ));
+            }
+            let taker_lamports = check_sol_balance(client, &taker.pubkey()).await?;
+            if taker_lamports < payment_amount {
+                return Err(EscrowCliError::InsufficientSol {
+                    needed: payment_amount,
+                    actual: taker_lamports,
+                });
+            }
+            println!(
+                "  Balance: {} lamports (needed: {})",
+                taker_lamports, payment_amount
+            );
+            println!("  Sufficient balance confirmed!");
+            (taker.pubkey(), maker.pubkey(), system_program::id())
+        }
+        ReceiveAsset::Token => {
+            // Taker needs Token B account (to send)
+            let taker_token_b_acc = ensure_token_account(
+                client,
+                taker,
+                &token_owner,
+                &escrow_data.token_mint_b,
+                "Token B (send)",
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::TokenAccountCreation(format!(
+                    "Failed to setup Token B account: {}",
+                    e
+                ))
+            })?;
+            println!("  Token B account: {}", taker_token_b_acc);
+            // Verify taker has enough Token B
+            let taker_token_b_data =
+                check_token_account(client, &taker_token_b_acc, payment_amount)
+                    .await
+                    .map_err(|e| {
+                        EscrowCliError::CustomError(format!(
+                            "Failed to get taker token b balance:{}",
+                            e
+                        ))
+                    })?;
+            println!(
+                "  Balance: {} Token B (needed: {})",
+                amount_to_ui_amount(taker_token_b_data, mint_b_data.decimals),
+                requested_amount
+            );
+            println!("  Sufficient balance confirmed!");
+            // Like the taker's Token A account, the program creates this one if it is missing.
+            // It belongs to the maker's multisig if the maker trades for one. An arbiter
+            // escrow is paid into its payment vault instead, also created by the program.
+            let token_program_b = get_token_program(client, &escrow_data.token_mint_b).await?;
+            let maker_token_b_acc = if escrow_data.is_arbitrated() {
+                find_payment_vault(&program_id, &maker.pubkey(), escrow_id).0
+            } else {
+                get_associated_token_address_with_program_id(
+                    &escrow_data.token_owner(),
+                    &escrow_data.token_mint_b,
+                    &token_program_b,
+                )
+            };
+            (taker_token_b_acc, maker_token_b_acc, token_program_b)
+        }
+    };
+
+    // The fee recipient is paid in the same asset as the maker.
+    let fee_account = match (&config, fee_amount) {
+        (Some(config), 1..) => {
+            println!(
+                "  Protocol fee: {} of the payment goes to {}",
+                fee_amount, config.fee_recipient
+            );
+            Some(match escrow_data.receive_asset {
+                ReceiveAsset::NativeSol => config.fee_recipient,
+                ReceiveAsset::Token => {
+                    ensure_token_account(
+                        client,
+                        taker,
+                        &config.fee_recipient,
+                        &escrow_data.token_mint_b,
+                        "Fee recipient Token B",
+                    )
+                    .await?
+                }
+            })
+        }
+        _ => None,
+    };
+
+    // The taker receives every offered basket leg and pays every requested one.
+    for leg in &escrow_data.basket_offered {
+        ensure_token_account(client, taker, &token_owner, &leg.mint, "Basket receive").await?;
+    }
+    for leg in &escrow_data.basket_requested {
+        let leg_fee = match &config {
+            Some(_) => escrow_data.fee_for(leg.amount).map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to compute protocol fee: {:?}", e))
+            })?,
+            None => 0,
+        };
+        let leg_payment = gross_amount_for_net(client, &leg.mint, leg.amount - leg_fee).await?
+            + gross_amount_for_net(client, &leg.mint, leg_fee).await?;
+        let taker_leg_acc =
+            ensure_token_account(client, taker, &token_owner, &leg.mint, "Basket send").await?;
+        check_token_account(client, &taker_leg_acc, leg_payment).await?;
+        ensure_token_account(
+            client,
+            taker,
+            &escrow_data.token_owner(),
+            &leg.mint,
+            "Maker basket",
+        )
+        .await?;
+        if let (Some(config), 1..) = (&config, leg_fee) {
+            ensure_token_account(
+                client,
+                taker,
+                &config.fee_recipient,
+                &leg.mint,
+                "Fee recipient basket",
+            )
+            .await?;
+        }
+    }
 
     println!("\n{}", "Step 7: Verify Vault Has Tokens".bold().cyan());
     if vault_data.amount == 0 {
@@ -147,30 +349,52 @@ pub async fn exchange_funds(
     println!("  Vault contains {} tokens", vault_data.amount);
     // Build exchange instruction
     println!("\n{}", "Step 8: Build Exchange Instruction".bold().cyan());
-    let exchange_instruction = create_exchange_instruction(
+    let exchange_accounts = ExchangeAccounts {
+        maker: maker.pubkey(),
+        taker: taker.pubkey(),
+        vault_pda,
+        escrow_pda,
+        taker_token_a_acc,
+        taker_token_b_acc,
+        maker_token_b_acc,
+        mint_a: escrow_data.token_mint_a,
+        mint_b: escrow_data.token_mint_b,
+        token_program_a,
+        token_program_b,
+        fee_account,
+    };
+    let mut exchange_instruction = create_exchange_instruction(
         &program_id,
-        &maker.pubkey(),
-        &taker,
-        &vault_pda,
-        &escrow_pda,
-        &taker_token_a_acc,
-        &taker_token_b_acc,
-        &maker_token_b_acc,
-        &escrow_data.token_mint_a,
-        &escrow_data.token_mint_b,
-        escrow_id,
+        &exchange_accounts,
+        ReleaseFundsArgs {
+            escrow_id,
+            fill_amount,
+            expected_offered: escrow_data.offered,
+            expected_receive: escrow_data.receive,
+            min_token_a_out: token_a_out,
+            max_token_b_in: payment_amount,
+        },
     );
+    exchange_instruction.accounts.extend(basket_take_accounts(
+        &program_id,
+        &escrow_data,
+        &token_owner,
+        &token_program_a,
+        &token_program_b,
+        config.as_ref(),
+    )?);
+    // A multisig owning the taker's tokens comes last, with the members signing for it
+    if let Some(multisig) = multisig {
+        exchange_instruction
+            .accounts
+            .extend(multisig.account_metas(&taker.pubkey()));
+    }
     println!("\n{}", "Step 9: Send Transaction".bold().cyan());
     let recent_blockhash = client
         .get_latest_blockhash()
         .await
         .map_err(|e| EscrowCliError::RpcError(format!("Failed to get blockhash: {}", e)))?;
-    let exchange_tx = Transaction::new_signed_with_payer(
-        &[exchange_instruction],
-        Some(&taker.pubkey()),
-        &[taker],
-        recent_blockhash,
-    );
+    let exchange_tx = sign_transaction(&[exchange_instruction], taker, multisig, recent_blockhash)?;
     println!("  Sending transaction...");
     let signature = client
         .send_and_confirm_transaction(&exchange_tx)
@@ -208,13 +432,11 @@ pub async fn exchange_funds(
     );
 
     println!("\n{}", "Exchange Summary".bold().white());
+    println!("  You received: {}", offered_asset.green());
     println!(
-        "  You received: {} Token A",
-        offered_amount.to_string().green()
-    );
-    println!(
-        "  You sent: {} Token B",
-        requested_amount.to_string().yellow()
+        "  You sent: {} {}",
+        requested_amount.to_string().yellow(),
+        payment_asset
     );
 
     println!("\n{}", "Account Details".bold().white());
@@ -4,28 +4,67 @@ use crate::{
     error::EscrowCliError,
     helper::{
         check_token_account, create_initialize_escrow_instruction, derive_pdas,
-        ensure_token_account,
+        ensure_token_account, get_token_program, sign_transaction, unpack_mint,
+        unpack_token_account, InitializeEscrowAccounts, MultisigSigners,
     },
 };
 use colored::*;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer,
-    transaction::Transaction,
+use escrow_native::{
+    instructions::instruction::InitializeEscrowArgs,
+    state::{OfferAsset, ReceiveAsset, VestingSchedule},
 };
-use spl_token::amount_to_ui_amount;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, system_program};
+use spl_token::{amount_to_ui_amount, native_mint};
+
+/// The terms of a new escrow, as given on the command line
+pub struct EscrowTerms<'a> {
+    pub mint_a: &'a str,
+    pub mint_b: Option<&'a str>,
+    pub deposit_amount: u64,
+    pub receive_amount: u64,
+    pub escrow_id: u64,
+    pub expires_at: Option<i64>,
+    pub allowed_taker: Option<&'a str>,
+    pub receive_asset: ReceiveAsset,
+    pub offer_asset: OfferAsset,
+    pub vesting: Option<VestingSchedule>,
+    pub operator: Option<&'a str>,
+    pub arbiter: Option<&'a str>,
+    pub milestones: &'a [u64],
+    pub approvers: &'a [String],
+    pub approval_threshold: u8,
+    pub basket_offered: &'a [(Pubkey, u64)],
+    pub basket_requested: &'a [(Pubkey, u64)],
+}
 
 pub async fn initialize_escrow(
     network: &str,
     program_id_str: &str,
     maker: &Keypair,
-    mint_a_str: &str,
-    mint_b_str: &str,
-    deposit_amount: u64,
-    receive_amount: u64,
-    escrow_id: u64,
+    terms: EscrowTerms<'_>,
+    multisig: Option<&MultisigSigners>,
     client: &RpcClient,
 ) -> Result<(), EscrowCliError> {
+    let EscrowTerms {
+        mint_a: mint_a_str,
+        mint_b: mint_b_str,
+        deposit_amount,
+        receive_amount,
+        escrow_id,
+        expires_at,
+        allowed_taker: allowed_taker_str,
+        receive_asset,
+        offer_asset,
+        vesting,
+        operator: operator_str,
+        arbiter: arbiter_str,
+        milestones,
+        approvers: approver_strs,
+        approval_threshold,
+        basket_offered,
+        basket_requested,
+    } = terms;
     println!("\n{}", "═══════════════════════════════════".bold().blue());
     println!("{}", "      INITIALIZE ESCROW".bold().blue());
     println!("{}", "═══════════════════════════════════".bold().blue());
@@ -35,30 +74,76 @@ pub async fn initialize_escrow(
     let mint_a =
         Pubkey::from_str(mint_a_str).map_err(|e| EscrowCliError::InvalidPubkey(e.to_string()))?;
 
-    let mint_b =
-        Pubkey::from_str(mint_b_str).map_err(|e| EscrowCliError::InvalidPubkey(e.to_string()))?;
+    // A native SOL escrow is marked by the native mint. Milestone and approval escrows receive
+    // nothing, so without a Token B they name Token A on both sides.
+    let mint_b = match receive_asset {
+        ReceiveAsset::NativeSol => native_mint::ID,
+        ReceiveAsset::Token => mint_b_str
+            .map_or(Ok(mint_a), Pubkey::from_str)
+            .map_err(|e| EscrowCliError::InvalidPubkey(e.to_string()))?,
+    };
 
+    let allowed_taker = allowed_taker_str
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid taker address: {}", e)))?;
+    let operator = operator_str
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid operator address: {}", e)))?;
+    let arbiter = arbiter_str
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid arbiter address: {}", e)))?;
+    let approvers = approver_strs
+        .iter()
+        .map(|approver| Pubkey::from_str(approver))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid approver address: {}", e)))?;
+    let pays_recipient = !milestones.is_empty() || !approvers.is_empty();
+
+    // The tokens are traded from the multisig's accounts when a multisig owns them
+    let token_owner = multisig.map_or(maker.pubkey(), |multisig| multisig.multisig);
+    if let Some(multisig) = multisig {
+        println!("  Trading for multisig: {}", multisig.multisig);
+    }
     // Get or create token accounts
-    let maker_token_a_acc =
-        ensure_token_account(&client, maker, &maker.pubkey(), &mint_a, "Token A")
-            .await
-            .map_err(|e| {
-                EscrowCliError::TokenAccountCreation(format!(
-                    "Failed to setup Token A account: {}",
-                    e
-                ))
-            })?;
-    let maker_token_b_acc =
-        ensure_token_account(&client, maker, &maker.pubkey(), &mint_b, "Token B")
-            .await
-            .map_err(|e| {
-                EscrowCliError::TokenAccountCreation(format!(
-                    "Failed to setup Token A account: {}",
-                    e
-                ))
-            })?;
+    let maker_token_a_acc = ensure_token_account(client, maker, &token_owner, &mint_a, "Token A")
+        .await
+        .map_err(|e| {
+            EscrowCliError::TokenAccountCreation(format!("Failed to setup Token A account: {}", e))
+        })?;
+    // SOL is received straight into the maker's wallet.
+    let maker_token_b_acc = match receive_asset {
+        ReceiveAsset::NativeSol => maker.pubkey(),
+        ReceiveAsset::Token => {
+            ensure_token_account(client, maker, &token_owner, &mint_b, "Token B")
+                .await
+                .map_err(|e| {
+                    EscrowCliError::TokenAccountCreation(format!(
+                        "Failed to setup Token A account: {}",
+                        e
+                    ))
+                })?
+        }
+    };
     // Check Token A balance
     check_token_account(client, &maker_token_a_acc, deposit_amount).await?;
+    // Token A may be owned by the SPL Token or the Token-2022 program
+    let token_program_a = get_token_program(client, &mint_a).await?;
+    let token_program_b = match receive_asset {
+        ReceiveAsset::NativeSol => system_program::id(),
+        ReceiveAsset::Token => get_token_program(client, &mint_b).await?,
+    };
+    // The maker deposits every offered basket leg and is paid every requested one
+    for (mint, amount) in basket_offered {
+        let maker_leg_acc =
+            ensure_token_account(client, maker, &token_owner, mint, "Basket deposit").await?;
+        check_token_account(client, &maker_leg_acc, *amount).await?;
+    }
+    for (mint, _) in basket_requested {
+        ensure_token_account(client, maker, &token_owner, mint, "Basket request").await?;
+    }
     // Derive PDAs
     let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
     println!("\n{}", "Derive PDAs".bold().cyan());
@@ -68,30 +153,45 @@ pub async fn initialize_escrow(
     println!("\n{}", "Send Transaction".bold().cyan());
 
     // Build initialize instruction
-    let init_instruction = create_initialize_escrow_instruction(
-        &maker,
+    let init_accounts = InitializeEscrowAccounts {
+        maker: maker.pubkey(),
+        token_mint_a: mint_a,
+        token_mint_b: mint_b,
+        vault_pda,
+        escrow_pda,
+        maker_token_acc_a: maker_token_a_acc,
+        maker_token_acc_b: maker_token_b_acc,
+        token_program_a,
+        token_program_b,
+        basket_offered_mints: basket_offered.iter().map(|(mint, _)| *mint).collect(),
+        basket_requested_mints: basket_requested.iter().map(|(mint, _)| *mint).collect(),
+        maker_multisig: multisig,
+    };
+    let init_args = InitializeEscrowArgs {
         escrow_id,
         deposit_amount,
         receive_amount,
-        &program_id,
-        &mint_a,
-        &mint_b,
-        &vault_pda,
-        &escrow_pda,
-        &maker_token_a_acc,
-        &maker_token_b_acc,
-    );
+        expires_at,
+        allowed_taker,
+        receive_asset,
+        basket_offered: basket_offered.iter().map(|(_, amount)| *amount).collect(),
+        basket_requested: basket_requested.iter().map(|(_, amount)| *amount).collect(),
+        operator,
+        offer_asset,
+        vesting,
+        arbiter,
+        milestones: milestones.to_vec(),
+        approvers: approvers.clone(),
+        approval_threshold,
+    };
+    let init_instruction =
+        create_initialize_escrow_instruction(&program_id, &init_accounts, init_args);
 
     let recent_blockhash = client
         .get_latest_blockhash()
         .await
         .map_err(|e| EscrowCliError::RpcError(format!("failed to get latest blockhash:{}", e)))?;
-    let init_tx = Transaction::new_signed_with_payer(
-        &[init_instruction],
-        Some(&maker.pubkey()),
-        &[&maker],
-        recent_blockhash,
-    );
+    let init_tx = sign_transaction(&[init_instruction], maker, multisig, recent_blockhash)?;
 
     let signature = client
         .send_and_confirm_transaction(&init_tx)
@@ -137,13 +237,76 @@ pub async fn initialize_escrow(
     );
     println!("  Vault PDA: {}", vault_pda.to_string().magenta());
     println!("  Escrow PDA: {}", escrow_pda.to_string().bright_magenta());
+    if let Some(expires_at) = expires_at {
+        println!("  Expires At: {}", expires_at.to_string().yellow());
+    }
+    if let Some(allowed_taker) = allowed_taker {
+        if pays_recipient {
+            println!("  Pays To: {}", allowed_taker.to_string().cyan());
+        } else {
+            println!("  Private To: {}", allowed_taker.to_string().cyan());
+        }
+    }
+    if let Some(operator) = operator {
+        println!("  Operator: {}", operator.to_string().cyan());
+    }
+    if offer_asset == OfferAsset::Nft {
+        println!("  Selling NFT: {}", mint_a.to_string().cyan());
+    }
+    if let Some(vesting) = vesting {
+        println!(
+            "  Vests To Taker: from {} to {}",
+            vesting.start.to_string().yellow(),
+            vesting.end.to_string().yellow()
+        );
+    }
+    if let Some(arbiter) = arbiter {
+        println!("  Arbiter: {}", arbiter.to_string().cyan());
+    }
+    for (index, amount) in milestones.iter().enumerate() {
+        println!(
+            "  Milestone {}: {} Token A",
+            index,
+            amount.to_string().yellow()
+        );
+    }
+    if !approvers.is_empty() {
+        println!(
+            "  Released On: {} of {} approvals",
+            approval_threshold.to_string().yellow(),
+            approvers.len()
+        );
+    }
+    for approver in &approvers {
+        println!("  Approver: {}", approver.to_string().cyan());
+    }
+    if receive_asset == ReceiveAsset::NativeSol {
+        println!(
+            "  Receives: {} lamports",
+            receive_amount.to_string().yellow()
+        );
+    }
+    for (mint, amount) in basket_offered {
+        println!(
+            "  Basket Deposit: {} of {}",
+            amount.to_string().yellow(),
+            mint
+        );
+    }
+    for (mint, amount) in basket_requested {
+        println!(
+            "  Basket Request: {} of {}",
+            amount.to_string().yellow(),
+            mint
+        );
+    }
 
     println!("\n{}", "Vault Details".bold().white());
     let vault_account = client
         .get_account(&vault_pda)
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("vault not found:{}", e)))?;
-    let vault_data = spl_token::state::Account::unpack(&vault_account.data).map_err(|e| {
+    let vault_data = unpack_token_account(&vault_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("vault account not found:{}", e))
     })?;
     let mint_a_account = client
@@ -151,7 +314,7 @@ pub async fn initialize_escrow(
         .await
         .map_err(|e| EscrowCliError::TokenAccountNotFound(format!("Mint A not found: {}", e)))?;
 
-    let mint_a_data = spl_token::state::Mint::unpack(&mint_a_account.data).map_err(|e| {
+    let mint_a_data = unpack_mint(&mint_a_account.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to parse Mint A: {}", e))
     })?;
     let offered_amount = amount_to_ui_amount(vault_data.amount, mint_a_data.decimals);
@@ -165,7 +328,13 @@ pub async fn initialize_escrow(
     println!("  State: {}", format!("{:?}", vault_data.state).blue());
 
     println!("\n{}", "Summary".bold().white());
-    println!("  Your tokens are locked in the escrow vault and waiting for a taker!");
+    if !milestones.is_empty() {
+        println!("  Your tokens are locked in the escrow vault until you approve each milestone!");
+    } else if !approvers.is_empty() {
+        println!("  Your tokens are locked in the escrow vault until enough approvers approve!");
+    } else {
+        println!("  Your tokens are locked in the escrow vault and waiting for a taker!");
+    }
     println!("  Maker address:");
     println!(
         "    {}",
@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{create_migrate_instruction, derive_pdas},
+};
+use colored::*;
+use escrow_native::state::{Escrow, VersionedEscrow};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+pub async fn migrate_escrow(
+    network: &str,
+    maker: &Keypair,
+    escrow_id: u64,
+    program_id_str: &str,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!(
+        "\n{}",
+        "═══════════════════════════════════".bold().yellow()
+    );
+    println!("{}", "        MIGRATE ESCROW".bold().yellow());
+    println!("{}", "═══════════════════════════════════".bold().yellow());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+
+    println!("\n{}", "Step 1: Derive PDAs".bold().cyan());
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
+    println!("Vault PDA: {}", vault_pda);
+    println!("Escrow PDA: {}", escrow_pda);
+
+    println!("\n{}", "Step 2: Check Escrow Version".bold().cyan());
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let version = VersionedEscrow::unpack(&escrow_account.data)
+        .map_err(|e| {
+            EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+        })?
+        .version();
+    println!("    Current version: {}", version);
+    if version == Escrow::VERSION {
+        println!("  Escrow is already at the latest version, nothing to do.");
+        return Ok(());
+    }
+
+    println!("\n{}", "Step 3: Send Transaction".bold().cyan());
+    let migrate_instruction = create_migrate_instruction(
+        &program_id,
+        &maker.pubkey(),
+        &vault_pda,
+        &escrow_pda,
+        escrow_id,
+    );
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let migrate_tx = Transaction::new_signed_with_payer(
+        &[migrate_instruction],
+        Some(&maker.pubkey()),
+        &[maker],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&migrate_tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    println!(
+        "\n{} {} -> {}",
+        "✓ Escrow migrated:".green().bold(),
+        version,
+        Escrow::VERSION
+    );
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
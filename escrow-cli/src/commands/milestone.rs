@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        create_approve_milestone_instruction, derive_pdas, ensure_token_account, get_token_program,
+        VaultAccounts,
+    },
+};
+use colored::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+/// Approve a milestone of your milestone escrow, paying its tranche of Token A to the recipient
+pub async fn approve_milestone(
+    network: &str,
+    maker: &Keypair,
+    program_id_str: &str,
+    escrow_id: u64,
+    milestone: u8,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().green());
+    println!("{}", "      APPROVE MILESTONE".bold().green());
+    println!("{}", "═══════════════════════════════════".bold().green());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
+    println!("Escrow PDA: {}", escrow_pda);
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
+    let (EscrowStatus::InProgress, Some(recipient)) = (escrow_data.status, escrow_data.beneficiary)
+    else {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and has no milestones to approve",
+            escrow_data.status
+        )));
+    };
+    let amount = match escrow_data.milestones.get(milestone as usize) {
+        Some(pending) if !pending.approved => pending.amount,
+        Some(_) => {
+            return Err(EscrowCliError::CustomError(format!(
+                "Milestone {} is already approved",
+                milestone
+            )))
+        }
+        None => {
+            return Err(EscrowCliError::CustomError(format!(
+                "Escrow has {} milestones, numbered from 0",
+                escrow_data.milestones.len()
+            )))
+        }
+    };
+    println!("  Recipient: {}", recipient.to_string().cyan());
+    println!(
+        "  Milestone {}: {} Token A",
+        milestone,
+        amount.to_string().yellow()
+    );
+
+    // The tranche is paid to the recipient's associated token account, created if missing.
+    let recipient_token_a_acc = ensure_token_account(
+        client,
+        maker,
+        &recipient,
+        &escrow_data.token_mint_a,
+        "Recipient's Token A",
+    )
+    .await?;
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    let vault = VaultAccounts {
+        maker: maker.pubkey(),
+        mint_a: escrow_data.token_mint_a,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        escrow_id,
+    };
+    let instruction = create_approve_milestone_instruction(
+        &program_id,
+        &vault,
+        &recipient_token_a_acc,
+        milestone,
+    );
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&maker.pubkey()),
+        &[maker],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    println!(
+        "\n{} {}",
+        "✓ Approved milestone".green().bold(),
+        milestone.to_string().yellow()
+    );
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
@@ -1,4 +1,14 @@
+pub mod admin;
+pub mod approve;
+pub mod bid;
 pub mod cancel;
+pub mod claim;
+pub mod events;
 pub mod exchange;
 pub mod initialize;
+pub mod migrate;
+pub mod milestone;
+pub mod operator;
+pub mod settle;
+pub mod update;
 pub mod view;
@@ -0,0 +1,76 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{create_set_operator_instruction, derive_pdas},
+};
+use colored::*;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+/// Set or clear the operator allowed to cancel and reprice an escrow
+pub async fn set_operator(
+    network: &str,
+    maker: &Keypair,
+    escrow_id: u64,
+    program_id_str: &str,
+    operator_str: Option<&str>,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().cyan());
+    println!("{}", "         SET OPERATOR".bold().cyan());
+    println!("{}", "═══════════════════════════════════".bold().cyan());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let operator = operator_str
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid operator address: {}", e)))?;
+
+    let (_vault_pda, escrow_pda) = derive_pdas(&program_id, &maker.pubkey(), escrow_id);
+    println!("Escrow PDA: {}", escrow_pda);
+
+    let instruction = create_set_operator_instruction(
+        &program_id,
+        &maker.pubkey(),
+        &escrow_pda,
+        escrow_id,
+        operator,
+    );
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&maker.pubkey()),
+        &[maker],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    match operator {
+        Some(operator) => println!(
+            "\n{} {}",
+            "✓ Operator set:".green().bold(),
+            operator.to_string().cyan()
+        ),
+        None => println!("\n{}", "✓ Operator cleared".green().bold()),
+    }
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        create_confirm_escrow_instruction, create_resolve_instruction, derive_pdas,
+        ensure_token_account, get_token_program, SettleAccounts,
+    },
+};
+use colored::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transaction::Transaction,
+};
+
+/// Confirm a held arbiter escrow as its maker or taker; the second confirmation settles it
+pub async fn confirm_escrow(
+    network: &str,
+    wallet: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!("\n{}", "═══════════════════════════════════".bold().green());
+    println!("{}", "        CONFIRM ESCROW".bold().green());
+    println!("{}", "═══════════════════════════════════".bold().green());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let (escrow_data, taker, beneficiary) =
+        get_held_escrow(client, &program_id, maker_str, escrow_id).await?;
+    if wallet.pubkey() != escrow_data.maker && wallet.pubkey() != taker {
+        return Err(EscrowCliError::CustomError(
+            "Only the maker or the taker can confirm this escrow".to_string(),
+        ));
+    }
+    let (token_program_a, token_program_b) =
+        ensure_settle_accounts(client, wallet, &escrow_data, &beneficiary).await?;
+    let settles = if wallet.pubkey() == escrow_data.maker {
+        escrow_data.taker_confirmed
+    } else {
+        escrow_data.maker_confirmed
+    };
+    let settle = SettleAccounts {
+        escrow: &escrow_data,
+        taker,
+        beneficiary,
+        token_program_a,
+        token_program_b,
+    };
+    let instruction = create_confirm_escrow_instruction(&program_id, &settle, &wallet.pubkey());
+    send_settle_transaction(network, wallet, instruction, client).await?;
+    if settles {
+        println!(
+            "\n{}",
+            "✓ Both parties confirmed; the trade is complete"
+                .green()
+                .bold()
+        );
+    } else {
+        println!(
+            "\n{}",
+            "✓ Confirmed; waiting for the other party".green().bold()
+        );
+    }
+    Ok(())
+}
+
+/// Settle a held arbiter escrow as its arbiter, releasing `release_bps` of the trade
+pub async fn resolve_escrow(
+    network: &str,
+    arbiter: &Keypair,
+    program_id_str: &str,
+    maker_str: &str,
+    escrow_id: u64,
+    release_bps: u16,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    println!(
+        "\n{}",
+        "═══════════════════════════════════".bold().yellow()
+    );
+    println!("{}", "        RESOLVE ESCROW".bold().yellow());
+    println!("{}", "═══════════════════════════════════".bold().yellow());
+
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    let (escrow_data, taker, beneficiary) =
+        get_held_escrow(client, &program_id, maker_str, escrow_id).await?;
+    if escrow_data.arbiter != Some(arbiter.pubkey()) {
+        return Err(EscrowCliError::CustomError(
+            "Only the escrow's arbiter can resolve it".to_string(),
+        ));
+    }
+    println!(
+        "  Releasing {} bps: the taker gets that share of Token A, the maker that share of the payment",
+        release_bps.to_string().yellow()
+    );
+    let (token_program_a, token_program_b) =
+        ensure_settle_accounts(client, arbiter, &escrow_data, &beneficiary).await?;
+    let settle = SettleAccounts {
+        escrow: &escrow_data,
+        taker,
+        beneficiary,
+        token_program_a,
+        token_program_b,
+    };
+    let instruction =
+        create_resolve_instruction(&program_id, &settle, &arbiter.pubkey(), release_bps);
+    send_settle_transaction(network, arbiter, instruction, client).await?;
+    println!("\n{}", "✓ Escrow resolved".green().bold());
+    Ok(())
+}
+
+/// Fetch an arbiter escrow that has been taken, with its taker and beneficiary
+async fn get_held_escrow(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    maker_str: &str,
+    escrow_id: u64,
+) -> Result<(Escrow, Pubkey, Pubkey), EscrowCliError> {
+    let maker = Pubkey::from_str(maker_str)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid maker address: {}", e)))?;
+    let (_, escrow_pda) = derive_pdas(program_id, &maker, escrow_id);
+    println!("Escrow PDA: {}", escrow_pda);
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
+    match (
+        escrow_data.status,
+        escrow_data.taker,
+        escrow_data.beneficiary,
+    ) {
+        (EscrowStatus::Held, Some(taker), Some(beneficiary)) => {
+            println!("  Taker: {}", taker.to_string().cyan());
+            println!(
+                "  Confirmed by maker: {}, by taker: {}",
+                escrow_data.maker_confirmed, escrow_data.taker_confirmed
+            );
+            Ok((escrow_data, taker, beneficiary))
+        }
+        _ => Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and is not held for settlement",
+            escrow_data.status
+        ))),
+    }
+}
+
+/// Make sure both sides have token accounts for both mints, returning the mints' token programs
+async fn ensure_settle_accounts(
+    client: &RpcClient,
+    payer: &Keypair,
+    escrow_data: &Escrow,
+    beneficiary: &Pubkey,
+) -> Result<(Pubkey, Pubkey), EscrowCliError> {
+    for owner in [escrow_data.token_owner(), *beneficiary] {
+        ensure_token_account(client, payer, &owner, &escrow_data.token_mint_a, "Token A").await?;
+        ensure_token_account(client, payer, &owner, &escrow_data.token_mint_b, "Token B").await?;
+    }
+    Ok((
+        get_token_program(client, &escrow_data.token_mint_a).await?,
+        get_token_program(client, &escrow_data.token_mint_b).await?,
+    ))
+}
+
+async fn send_settle_transaction(
+    network: &str,
+    payer: &Keypair,
+    instruction: Instruction,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use crate::{
+    error::EscrowCliError,
+    helper::{
+        create_update_instruction, derive_pdas, get_token_program, sign_transaction,
+        MultisigSigners, VaultAccounts,
+    },
+};
+use colored::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// An escrow to update, and the changes to make to it
+pub struct EscrowUpdate<'a> {
+    /// The escrow's maker, when updating it as its operator
+    pub maker: Option<&'a str>,
+    pub escrow_id: u64,
+    pub receive_amount: Option<u64>,
+    pub deposit_amount: u64,
+    pub withdraw_amount: u64,
+}
+
+/// Update an escrow as its maker, or reprice it as its operator when `update.maker` names the maker
+pub async fn update_escrow(
+    network: &str,
+    authority: &Keypair,
+    program_id_str: &str,
+    update: EscrowUpdate<'_>,
+    multisig: Option<&MultisigSigners>,
+    client: &RpcClient,
+) -> Result<(), EscrowCliError> {
+    let EscrowUpdate {
+        maker: maker_str,
+        escrow_id,
+        receive_amount,
+        deposit_amount,
+        withdraw_amount,
+    } = update;
+    println!("\n{}", "═══════════════════════════════════".bold().cyan());
+    println!("{}", "        UPDATE ESCROW".bold().cyan());
+    println!("{}", "═══════════════════════════════════".bold().cyan());
+
+    if receive_amount.is_none() && deposit_amount == 0 && withdraw_amount == 0 {
+        return Err(EscrowCliError::CustomError(
+            "Nothing to update: pass --receive, --deposit or --withdraw".to_string(),
+        ));
+    }
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|e| EscrowCliError::InvalidProgramId(e.to_string()))?;
+    // Updating someone else's escrow means acting as its operator, who can only reprice.
+    let maker = match maker_str {
+        Some(maker_str) => Pubkey::from_str(maker_str)
+            .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid maker address: {}", e)))?,
+        None => authority.pubkey(),
+    };
+    let operator = (maker != authority.pubkey()).then(|| authority.pubkey());
+    if operator.is_some() && (deposit_amount > 0 || withdraw_amount > 0) {
+        return Err(EscrowCliError::CustomError(
+            "An operator can only reprice: --deposit and --withdraw need the maker".to_string(),
+        ));
+    }
+
+    println!("\n{}", "Step 1: Derive PDAs".bold().cyan());
+    let (vault_pda, escrow_pda) = derive_pdas(&program_id, &maker, escrow_id);
+    println!("Vault PDA: {}", vault_pda);
+    println!("Escrow PDA: {}", escrow_pda);
+
+    println!("\n{}", "Step 2: Fetch Escrow Details".bold().cyan());
+    let escrow_account = client
+        .get_account(&escrow_pda)
+        .await
+        .map_err(|e| EscrowCliError::CustomError(format!("Escrow not found: {}", e)))?;
+    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
+    })?;
+    if escrow_data.status != EscrowStatus::Open {
+        return Err(EscrowCliError::CustomError(format!(
+            "Escrow is {:?} and can no longer be updated",
+            escrow_data.status
+        )));
+    }
+    println!(
+        "  Current terms: {} Token A for {} Token B",
+        escrow_data.offered, escrow_data.receive
+    );
+
+    println!("\n{}", "Step 3: Find Maker's Token Account".bold().cyan());
+    let token_program_a = get_token_program(client, &escrow_data.token_mint_a).await?;
+    // Token A always moves to and from the maker's multisig, if the maker trades for one.
+    let maker_token_account =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &escrow_data.token_owner(),
+            &escrow_data.token_mint_a,
+            &token_program_a,
+        );
+    println!("Maker's Token Account: {}", maker_token_account);
+
+    println!("\n{}", "Step 4: Send Transaction".bold().cyan());
+    let vault = VaultAccounts {
+        maker,
+        mint_a: escrow_data.token_mint_a,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        escrow_id,
+    };
+    let mut update_instruction = create_update_instruction(
+        &program_id,
+        &vault,
+        &maker_token_account,
+        receive_amount,
+        deposit_amount,
+        withdraw_amount,
+        operator.as_ref(),
+    );
+    // The multisig's members sign for a top up from its account.
+    if let (Some(multisig), 1..) = (multisig, deposit_amount) {
+        update_instruction
+            .accounts
+            .extend(multisig.account_metas(&authority.pubkey()));
+    }
+    let recent_blockhash = client.get_latest_blockhash().await.map_err(|e| {
+        EscrowCliError::NetworkConnection(format!("failed to get Recent blockhash:{}", e))
+    })?;
+    let update_tx = sign_transaction(&[update_instruction], authority, multisig, recent_blockhash)?;
+    println!("  Sending transaction...");
+    let signature = client
+        .send_and_confirm_transaction(&update_tx)
+        .await
+        .map_err(|e| EscrowCliError::TransactionFailed(format!("Transaction failed: {}", e)))?;
+
+    println!("\n{}", "✓ Escrow updated".green().bold());
+    if let Some(receive_amount) = receive_amount {
+        println!("  New price: {} Token B", receive_amount);
+    }
+    if deposit_amount > 0 {
+        println!("  Deposited: {} Token A", deposit_amount);
+    }
+    if withdraw_amount > 0 {
+        println!("  Withdrawn: {} Token A", withdraw_amount);
+    }
+    println!("  Transaction: {}", signature.to_string().yellow().bold());
+    println!(
+        "  Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster={}",
+            signature, network
+        )
+        .underline()
+        .cyan()
+    );
+
+    Ok(())
+}
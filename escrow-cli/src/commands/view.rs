@@ -1,14 +1,16 @@
 use std::str::FromStr;
 
-use crate::{error::EscrowCliError, helper::derive_pdas};
+use crate::{
+    error::EscrowCliError,
+    helper::{derive_pdas, get_config, unpack_mint, unpack_token_account},
+};
 use colored::*;
-use escrow_native::state::Escrow;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
-use spl_token::{
-    amount_to_ui_amount,
-    state::{Account, Mint},
+use escrow_native::state::{
+    Config, Escrow, EscrowStatus, MigrationContext, ReceiveAsset, VersionedEscrow,
 };
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::amount_to_ui_amount;
 pub async fn view_escrow(
     network: &str,
     program_id: &str,
@@ -27,9 +29,35 @@ pub async fn view_escrow(
     let escrow_account = client.get_account(&escrow_pda).await.map_err(|e| {
         EscrowCliError::CustomError(format!("failed to get the escrow account:{}", e))
     })?;
-    let escrow_data = Escrow::unpack_the_slice_data(&escrow_account.data).map_err(|e| {
+    let versioned_escrow = VersionedEscrow::unpack(&escrow_account.data).map_err(|e| {
         EscrowCliError::CustomError(format!("Failed to deserialize escrow data: {:?}", e))
     })?;
+    let version = versioned_escrow.version();
+
+    // Fetch vault account to see deposited amount
+    let vault_account = client.get_account(&vault_pda).await.map_err(|e| {
+        EscrowCliError::CustomError(format!("Failed to get the vault account:{}", e))
+    })?;
+    let vault_data = unpack_token_account(&vault_account.data).map_err(|e| {
+        EscrowCliError::TokenAccountNotFound(format!("Failed to parse vault: {}", e))
+    })?;
+    // Older layouts are shown as they would look once migrated, which stores the vault and
+    // config bumps and the current protocol fee.
+    let vault_bump = Pubkey::find_program_address(
+        &[Escrow::VAULT_SEED, maker.as_ref(), &escrow_id.to_le_bytes()],
+        &program_id,
+    )
+    .1;
+    let config_bump = Config::find_address(&program_id).1;
+    let fee_bps = get_config(client, &program_id)
+        .await?
+        .map_or(0, |config| config.fee_bps);
+    let escrow_data = versioned_escrow.into_current(&MigrationContext {
+        vault_amount: vault_data.amount,
+        vault_bump,
+        config_bump,
+        fee_bps,
+    });
 
     let mint_a_acc = client
         .get_account(&escrow_data.token_mint_a)
@@ -37,7 +65,7 @@ pub async fn view_escrow(
         .map_err(|e| {
             EscrowCliError::TokenAccountNotFound(format!("Failed to get Mint A Account:{}", e))
         })?;
-    let mint_a_data = Mint::unpack(&mint_a_acc.data).map_err(|e| {
+    let mint_a_data = unpack_mint(&mint_a_acc.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to get Mint A Account Data:{}", e))
     })?;
     // Token B info (what maker wants)
@@ -47,17 +75,9 @@ pub async fn view_escrow(
         .map_err(|e| {
             EscrowCliError::TokenAccountNotFound(format!("Failed to get Mint B Account:{}", e))
         })?;
-    let mint_b_data = Mint::unpack(&mint_b_acc.data).map_err(|e| {
+    let mint_b_data = unpack_mint(&mint_b_acc.data).map_err(|e| {
         EscrowCliError::TokenAccountNotFound(format!("Failed to get Mint B Account Data:{}", e))
     })?;
-
-    // Fetch vault account to see deposited amount
-    let vault_account = client.get_account(&vault_pda).await.map_err(|e| {
-        EscrowCliError::CustomError(format!("Failed to get the vault account:{}", e))
-    })?;
-    let vault_data = Account::unpack(&vault_account.data).map_err(|e| {
-        EscrowCliError::TokenAccountNotFound(format!("Failed to parse vault: {}", e))
-    })?;
     // let token_a_amount = amount_to_ui_amount(vault_data.amount, mint_a_data.decimals);
 
     // println!("{}", "\nEscrow State:".bold().green());
@@ -111,7 +131,8 @@ pub async fn view_escrow(
     //     .underline()
     //     .cyan()
     // );
-    let offered_amount = amount_to_ui_amount(vault_data.amount, mint_a_data.decimals);
+    let offered_amount = amount_to_ui_amount(escrow_data.offered, mint_a_data.decimals);
+    let vault_balance = amount_to_ui_amount(vault_data.amount, mint_a_data.decimals);
     let requested_amount = amount_to_ui_amount(escrow_data.receive, mint_b_data.decimals);
     let separator =
         "────────────────────────────────────────────────────────────".truecolor(90, 90, 90);
@@ -153,36 +174,209 @@ pub async fn view_escrow(
         "Address:".dimmed(),
         escrow_pda.to_string().bright_blue()
     );
+    if version < Escrow::VERSION {
+        println!(
+            "  {:<14} {} {}",
+            "Version:".dimmed(),
+            version.to_string().red().bold(),
+            "(outdated, run `migrate` before using this escrow)".red()
+        );
+    } else {
+        println!("  {:<14} {}", "Version:".dimmed(), version);
+    }
+    let status = format!("{:?}", escrow_data.status).to_uppercase();
+    println!(
+        "  {:<14} {}",
+        "Status:".dimmed(),
+        match escrow_data.status {
+            EscrowStatus::Open => status.green().bold(),
+            EscrowStatus::Vesting | EscrowStatus::Held | EscrowStatus::InProgress => {
+                status.yellow().bold()
+            }
+        }
+    );
+    match escrow_data.expires_at {
+        Some(expires_at) => println!(
+            "  {:<14} {} (unix time)",
+            "Expires At:".dimmed(),
+            expires_at.to_string().yellow()
+        ),
+        None => println!("  {:<14} {}", "Expires At:".dimmed(), "Never".dimmed()),
+    }
+    match escrow_data.allowed_taker {
+        Some(allowed_taker) if escrow_data.has_milestones() || escrow_data.requires_approval() => {
+            println!(
+                "  {:<14} {}",
+                "Recipient:".dimmed(),
+                allowed_taker.to_string().cyan()
+            )
+        }
+        Some(allowed_taker) => println!(
+            "  {:<14} {}",
+            "Taker:".dimmed(),
+            allowed_taker.to_string().cyan()
+        ),
+        None => println!("  {:<14} {}", "Taker:".dimmed(), "Anyone".dimmed()),
+    }
+    if let Some(operator) = escrow_data.operator {
+        println!(
+            "  {:<14} {}",
+            "Operator:".dimmed(),
+            operator.to_string().cyan()
+        );
+    }
+    if let Some(vesting) = escrow_data.vesting {
+        println!(
+            "  {:<14} from {} to {} (unix time)",
+            "Vests:".dimmed(),
+            vesting.start.to_string().yellow(),
+            vesting.end.to_string().yellow()
+        );
+        if let Some(beneficiary) = escrow_data.beneficiary {
+            println!(
+                "  {:<14} {}",
+                "Beneficiary:".dimmed(),
+                beneficiary.to_string().cyan()
+            );
+            println!(
+                "  {:<14} {} of {} Tokens",
+                "Claimed:".dimmed(),
+                amount_to_ui_amount(escrow_data.claimed, mint_a_data.decimals),
+                offered_amount
+            );
+        }
+    }
+    if let Some(arbiter) = escrow_data.arbiter {
+        println!(
+            "  {:<14} {}",
+            "Arbiter:".dimmed(),
+            arbiter.to_string().cyan()
+        );
+        if let Some(taker) = escrow_data.taker {
+            println!(
+                "  {:<14} {}",
+                "Taken By:".dimmed(),
+                taker.to_string().cyan()
+            );
+            println!(
+                "  {:<14} maker {}, taker {}",
+                "Confirmed:".dimmed(),
+                escrow_data.maker_confirmed,
+                escrow_data.taker_confirmed
+            );
+        }
+    }
+    for (index, milestone) in escrow_data.milestones.iter().enumerate() {
+        println!(
+            "  {:<14} {} Tokens, {}",
+            format!("Milestone {}:", index).dimmed(),
+            amount_to_ui_amount(milestone.amount, mint_a_data.decimals),
+            if milestone.approved {
+                "approved".green()
+            } else {
+                "pending".yellow()
+            }
+        );
+    }
+    if escrow_data.requires_approval() {
+        println!(
+            "  {:<14} {} of {}",
+            "Approvals:".dimmed(),
+            escrow_data.approvals().to_string().yellow(),
+            escrow_data.approval_threshold
+        );
+    }
+    for approver in &escrow_data.approvers {
+        println!(
+            "  {:<14} {}, {}",
+            "Approver:".dimmed(),
+            approver.key.to_string().cyan(),
+            if approver.approved {
+                "approved".green()
+            } else {
+                "pending".yellow()
+            }
+        );
+    }
+    if escrow_data.fee_bps > 0 {
+        println!(
+            "  {:<14} {} bps",
+            "Protocol Fee:".dimmed(),
+            escrow_data.fee_bps.to_string().yellow()
+        );
+    }
     println!("{}", separator);
 
     // EXCHANGE TERMS
-    println!("\n{}", "Exchange Terms".white().bold());
+    if escrow_data.is_nft() {
+        println!("\n{}", "NFT Sale".white().bold());
+    } else {
+        println!("\n{}", "Exchange Terms".white().bold());
+    }
 
     // OFFERING SIDE
-    println!("\n{} {}", "OFFERING".green().bold(), "(Locked in Vault)");
-    println!(
-        "    {:<12} {} Tokens",
-        "Amount:".dimmed(),
-        offered_amount.to_string().bold()
-    );
-    println!(
-        "    {:<12} {}",
-        "Mint A:".dimmed(),
-        escrow_data.token_mint_a
-    );
+    println!("\n{} (Locked in Vault)", "OFFERING".green().bold());
+    if escrow_data.is_nft() {
+        // A single token with no decimals, so the mint is the whole offer.
+        println!(
+            "    {:<12} {}",
+            "NFT:".dimmed(),
+            escrow_data.token_mint_a.to_string().bold()
+        );
+    } else {
+        println!(
+            "    {:<12} {} Tokens",
+            "Amount:".dimmed(),
+            offered_amount.to_string().bold()
+        );
+        println!(
+            "    {:<12} {}",
+            "Mint A:".dimmed(),
+            escrow_data.token_mint_a
+        );
+    }
+    for leg in &escrow_data.basket_offered {
+        println!(
+            "    {:<12} {} of {}",
+            "Basket:".dimmed(),
+            leg.amount,
+            leg.mint
+        );
+    }
 
     // REQUESTING SIDE
-    println!("\n{} {}", "REQUESTING".magenta().bold(), "(To Withdraw)");
-    println!(
-        "    {:<12} {} Tokens",
-        "Amount:".dimmed(),
-        requested_amount.to_string().bold()
-    );
-    println!(
-        "    {:<12} {}",
-        "Mint B:".dimmed(),
-        escrow_data.token_mint_b
-    );
+    println!("\n{} (To Withdraw)", "REQUESTING".magenta().bold());
+    match escrow_data.receive_asset {
+        ReceiveAsset::Token => {
+            println!(
+                "    {:<12} {} Tokens",
+                "Amount:".dimmed(),
+                requested_amount.to_string().bold()
+            );
+            println!(
+                "    {:<12} {}",
+                "Mint B:".dimmed(),
+                escrow_data.token_mint_b
+            );
+            for leg in &escrow_data.basket_requested {
+                println!(
+                    "    {:<12} {} of {}",
+                    "Basket:".dimmed(),
+                    leg.amount,
+                    leg.mint
+                );
+            }
+        }
+        ReceiveAsset::NativeSol => {
+            println!(
+                "    {:<12} {} SOL ({} lamports)",
+                "Amount:".dimmed(),
+                requested_amount.to_string().bold(),
+                escrow_data.receive
+            );
+            println!("    {:<12} Native SOL", "Asset:".dimmed());
+        }
+    }
     println!("{}", separator);
 
     // VAULT STATUS
@@ -200,7 +394,7 @@ pub async fn view_escrow(
         println!(
             "  {:<14} {} (Raw units: {})",
             "Balance:".dimmed(),
-            offered_amount,
+            vault_balance,
             vault_data.amount
         );
     } else {
@@ -1,21 +1,36 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use escrow_native::{
+    arbiter::find_payment_vault,
+    basket::find_basket_vault,
+    events::EscrowEvent,
+    instructions::instruction::{EscrowInstruction, InitializeEscrowArgs, ReleaseFundsArgs},
+    state::{Bid, Config, Escrow},
+};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
+    bpf_loader_upgradeable,
     commitment_config::CommitmentConfig,
+    hash::Hash,
     instruction::{AccountMeta, Instruction},
-    program_pack::Pack,
+    program_error::ProgramError,
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair},
     signer::Signer,
     system_program, sysvar,
     transaction::Transaction,
 };
-use spl_token::{state::Account, ID as TOKEN_PROGRAM_ID};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account, Mint},
+};
 
 use crate::error::EscrowCliError;
 
 //Connect to Solana Network
 /// Create RPC client for the specified network
-
 pub fn connect_to_network(network: &str) -> Result<RpcClient, EscrowCliError> {
     let url = match network {
         "devnet" => "https://api.devnet.solana.com",
@@ -37,14 +52,143 @@ pub fn get_wallet(path: &str) -> Result<Keypair, EscrowCliError> {
         .map_err(|e| EscrowCliError::WalletLoad(format!("Failed to read keypair: {}", e)))
 }
 
+/// An SPL multisig that owns the wallet's token accounts, with the members co-signing for it
+pub struct MultisigSigners {
+    pub multisig: Pubkey,
+    pub co_signers: Vec<Keypair>,
+}
+impl MultisigSigners {
+    /// Trailing accounts handing the multisig and its signing members to the program.
+    /// The wallet always signs as one of the members.
+    pub fn account_metas(&self, wallet: &Pubkey) -> Vec<AccountMeta> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.multisig, false),
+            AccountMeta::new_readonly(*wallet, true),
+        ];
+        accounts.extend(
+            self.co_signers
+                .iter()
+                .filter(|co_signer| co_signer.pubkey() != *wallet)
+                .map(|co_signer| AccountMeta::new_readonly(co_signer.pubkey(), true)),
+        );
+        accounts
+    }
+}
+/// Load the multisig owning the wallet's tokens and the keypairs of the members co-signing
+pub fn load_multisig(
+    multisig: Option<&str>,
+    signer_paths: &[String],
+) -> Result<Option<MultisigSigners>, EscrowCliError> {
+    let Some(multisig) = multisig else {
+        if !signer_paths.is_empty() {
+            return Err(EscrowCliError::CustomError(
+                "--multisig-signer needs --multisig".to_string(),
+            ));
+        }
+        return Ok(None);
+    };
+    let multisig = Pubkey::from_str(multisig)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid multisig address: {}", e)))?;
+    let co_signers = signer_paths
+        .iter()
+        .map(|path| get_wallet(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(MultisigSigners {
+        multisig,
+        co_signers,
+    }))
+}
+/// Sign a transaction with the wallet, then collect each multisig member's partial signature
+pub fn sign_transaction(
+    instructions: &[Instruction],
+    payer: &Keypair,
+    multisig: Option<&MultisigSigners>,
+    recent_blockhash: Hash,
+) -> Result<Transaction, EscrowCliError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.try_partial_sign(&[payer], recent_blockhash)?;
+    for co_signer in multisig.map_or(&[][..], |multisig| &multisig.co_signers) {
+        transaction.try_partial_sign(&[co_signer], recent_blockhash)?;
+    }
+    if !transaction.is_signed() {
+        return Err(EscrowCliError::TransactionFailed(
+            "Transaction is missing multisig signatures".to_string(),
+        ));
+    }
+    Ok(transaction)
+}
 /// Check SOL balance of a wallet
 pub async fn check_sol_balance(client: &RpcClient, wallet: &Pubkey) -> Result<u64, EscrowCliError> {
     client
-        .get_balance(&wallet)
+        .get_balance(wallet)
         .await
         .map_err(|e| EscrowCliError::RpcError(format!("Failed to get balance: {}", e)))
 }
 
+/// Unpack a token account of either token program, ignoring any extensions
+pub fn unpack_token_account(data: &[u8]) -> Result<Account, ProgramError> {
+    Ok(StateWithExtensions::<Account>::unpack(data)?.base)
+}
+/// Unpack a mint of either token program, ignoring any extensions
+pub fn unpack_mint(data: &[u8]) -> Result<Mint, ProgramError> {
+    Ok(StateWithExtensions::<Mint>::unpack(data)?.base)
+}
+/// Get the token program (SPL Token or Token-2022) that owns a mint
+pub async fn get_token_program(
+    client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Pubkey, EscrowCliError> {
+    let mint_account = client.get_account(mint).await.map_err(|e| {
+        EscrowCliError::TokenAccountNotFound(format!("Mint {} not found: {}", mint, e))
+    })?;
+    if mint_account.owner != spl_token::ID && mint_account.owner != spl_token_2022::ID {
+        return Err(EscrowCliError::CustomError(format!(
+            "Mint {} is not owned by the SPL Token or Token-2022 program",
+            mint
+        )));
+    }
+    Ok(mint_account.owner)
+}
+/// Amount to send so the recipient receives `net_amount` after the mint's transfer fee
+pub async fn gross_amount_for_net(
+    client: &RpcClient,
+    mint: &Pubkey,
+    net_amount: u64,
+) -> Result<u64, EscrowCliError> {
+    let mint_account = client.get_account(mint).await.map_err(|e| {
+        EscrowCliError::TokenAccountNotFound(format!("Mint {} not found: {}", mint, e))
+    })?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|e| EscrowCliError::CustomError(format!("Failed to parse mint: {}", e)))?;
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(net_amount);
+    };
+    let epoch = client.get_epoch_info().await?.epoch;
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or_else(|| EscrowCliError::CustomError("Transfer fee overflow".to_string()))?;
+    Ok(net_amount.saturating_add(fee))
+}
+/// Amount received when `gross_amount` of a mint is sent, after its current transfer fee
+pub async fn net_amount_after_fee(
+    client: &RpcClient,
+    mint: &Pubkey,
+    gross_amount: u64,
+) -> Result<u64, EscrowCliError> {
+    let mint_account = client.get_account(mint).await.map_err(|e| {
+        EscrowCliError::TokenAccountNotFound(format!("Mint {} not found: {}", mint, e))
+    })?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|e| EscrowCliError::CustomError(format!("Failed to parse mint: {}", e)))?;
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(gross_amount);
+    };
+    let epoch = client.get_epoch_info().await?.epoch;
+    let fee = fee_config
+        .calculate_epoch_fee(epoch, gross_amount)
+        .ok_or_else(|| EscrowCliError::CustomError("Transfer fee overflow".to_string()))?;
+    Ok(gross_amount.saturating_sub(fee))
+}
 /// Check if token account exists and has sufficient balance
 pub async fn check_token_account(
     client: &RpcClient,
@@ -55,7 +199,7 @@ pub async fn check_token_account(
         .get_account(account)
         .await
         .map_err(|_| EscrowCliError::TokenAccountNotFound(account.to_string()))?;
-    let token_account = Account::unpack(&result.data).map_err(|e| {
+    let token_account = unpack_token_account(&result.data).map_err(|e| {
         EscrowCliError::CustomError(format!("Failed to unpack token account: {}", e))
     })?;
     if token_account.amount < required_amount {
@@ -75,10 +219,12 @@ pub async fn ensure_token_account(
     token_name: &str,
 ) -> Result<Pubkey, EscrowCliError> {
     println!("Checking {} token account...", token_name);
+    // The account must be created by the token program that owns the mint
+    let token_program = get_token_program(client, mint).await?;
     let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
-        &payer.pubkey(),
-        &mint,
-        &TOKEN_PROGRAM_ID,
+        owner,
+        mint,
+        &token_program,
     );
     // Check if account exists
     if client.get_account(&ata).await.is_ok() {
@@ -91,9 +237,9 @@ pub async fn ensure_token_account(
     let create_ata_instruction =
         spl_associated_token_account::instruction::create_associated_token_account_idempotent(
             &payer.pubkey(),
-            &owner,
-            &mint,
-            &TOKEN_PROGRAM_ID,
+            owner,
+            mint,
+            &token_program,
         );
     let recent_blockhash = client
         .get_latest_blockhash()
@@ -121,102 +267,725 @@ pub fn derive_pdas(program_id: &Pubkey, maker: &Pubkey, escrow_id: u64) -> (Pubk
     let vault_pda = Pubkey::find_program_address(vault_seeds, program_id).0;
     let escrow_pda = Pubkey::find_program_address(
         &[b"escrow_vault", maker.as_ref(), &escrow_id.to_le_bytes()],
-        &program_id,
+        program_id,
     )
     .0;
     (vault_pda, escrow_pda)
 }
-pub fn create_initialize_escrow_instruction(
-    maker: &Keypair,
+/// Derive the bid and bid vault PDAs of a bidder on an escrow
+pub fn derive_bid_pdas(program_id: &Pubkey, escrow: &Pubkey, bidder: &Pubkey) -> (Pubkey, Pubkey) {
+    let bid_pda = Bid::find_address(program_id, escrow, bidder).0;
+    let bid_vault_pda = Bid::find_vault_address(program_id, escrow, bidder).0;
+    (bid_pda, bid_vault_pda)
+}
+/// Derive the program-wide config PDA
+pub fn derive_config_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[Config::SEED], program_id).0
+}
+/// Derive the program's ProgramData account under the upgradeable BPF loader
+pub fn derive_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+/// Fetch the program-wide config, if it has been created
+pub async fn get_config(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<Config>, EscrowCliError> {
+    let config_pda = derive_config_pda(program_id);
+    let Ok(config_account) = client.get_account(&config_pda).await else {
+        return Ok(None);
+    };
+    Config::unpack_the_slice_data(&config_account.data)
+        .map(Some)
+        .map_err(|e| EscrowCliError::CustomError(format!("Failed to parse config: {:?}", e)))
+}
+/// Decode an escrow event from a `Program data: <base64>` transaction log line
+pub fn decode_event_log(line: &str) -> Option<EscrowEvent> {
+    let data = line.strip_prefix("Program data: ")?;
+    let bytes = STANDARD.decode(data).ok()?;
+    EscrowEvent::unpack(&bytes).ok()
+}
+/// Parse a basket leg given as `MINT:AMOUNT`
+pub fn parse_basket_leg(value: &str) -> Result<(Pubkey, u64), EscrowCliError> {
+    let (mint, amount) = value.split_once(':').ok_or_else(|| {
+        EscrowCliError::CustomError(format!("Basket leg {} must be MINT:AMOUNT", value))
+    })?;
+    let mint = Pubkey::from_str(mint)
+        .map_err(|e| EscrowCliError::InvalidPubkey(format!("Invalid basket mint: {}", e)))?;
+    let amount = amount.parse::<u64>().map_err(|e| {
+        EscrowCliError::CustomError(format!("Invalid basket amount {}: {}", amount, e))
+    })?;
+    Ok((mint, amount))
+}
+/// Trailing accounts of `InitializeEscrow` for the legs of a basket
+pub fn basket_make_accounts(
+    program_id: &Pubkey,
     escrow_id: u64,
-    deposit_amount: u64,
-    receive_amount: u64,
+    make: &InitializeEscrowAccounts,
+) -> Vec<AccountMeta> {
+    let token_owner = make
+        .maker_multisig
+        .map_or(make.maker, |multisig| multisig.multisig);
+    let mut accounts = Vec::new();
+    for (index, mint) in make.basket_offered_mints.iter().enumerate() {
+        let (vault, _) = find_basket_vault(program_id, &make.maker, escrow_id, index as u8);
+        accounts.push(AccountMeta::new_readonly(*mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(&token_owner, mint, &make.token_program_a),
+            false,
+        ));
+        accounts.push(AccountMeta::new(vault, false));
+    }
+    for mint in &make.basket_requested_mints {
+        accounts.push(AccountMeta::new_readonly(*mint, false));
+        accounts.push(AccountMeta::new_readonly(
+            get_associated_token_address_with_program_id(&token_owner, mint, &make.token_program_b),
+            false,
+        ));
+    }
+    accounts
+}
+/// Trailing accounts of `ReleaseFunds` for the legs of a basket
+pub fn basket_take_accounts(
     program_id: &Pubkey,
-    token_mint_a: &Pubkey,
-    token_mint_b: &Pubkey,
-    vault_pda: &Pubkey,
-    escrow_pda: &Pubkey,
-    maker_token_acc_a: &Pubkey,
-    maker_token_acc_b: &Pubkey,
+    escrow: &Escrow,
+    taker: &Pubkey,
+    token_program_a: &Pubkey,
+    token_program_b: &Pubkey,
+    config: Option<&Config>,
+) -> Result<Vec<AccountMeta>, EscrowCliError> {
+    let mut accounts = Vec::new();
+    for (index, leg) in escrow.basket_offered.iter().enumerate() {
+        let (vault, _) =
+            find_basket_vault(program_id, &escrow.maker, escrow.escrow_id, index as u8);
+        accounts.push(AccountMeta::new(leg.mint, false));
+        accounts.push(AccountMeta::new(vault, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(taker, &leg.mint, token_program_a),
+            false,
+        ));
+    }
+    for leg in &escrow.basket_requested {
+        accounts.push(AccountMeta::new_readonly(leg.mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(taker, &leg.mint, token_program_b),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(
+                &escrow.token_owner(),
+                &leg.mint,
+                token_program_b,
+            ),
+            false,
+        ));
+        if let Some(config) = config {
+            let fee_amount = escrow.fee_for(leg.amount).map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to compute protocol fee: {:?}", e))
+            })?;
+            if fee_amount > 0 {
+                accounts.push(AccountMeta::new(
+                    get_associated_token_address_with_program_id(
+                        &config.fee_recipient,
+                        &leg.mint,
+                        token_program_b,
+                    ),
+                    false,
+                ));
+            }
+        }
+    }
+    Ok(accounts)
+}
+/// Trailing accounts of `CancelEscrow` for the offered legs of a basket
+pub fn basket_refund_accounts(
+    program_id: &Pubkey,
+    escrow: &Escrow,
+    token_program_a: &Pubkey,
+) -> Vec<AccountMeta> {
+    let mut accounts = Vec::new();
+    for (index, leg) in escrow.basket_offered.iter().enumerate() {
+        let (vault, _) =
+            find_basket_vault(program_id, &escrow.maker, escrow.escrow_id, index as u8);
+        accounts.push(AccountMeta::new(leg.mint, false));
+        accounts.push(AccountMeta::new(vault, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(
+                &escrow.token_owner(),
+                &leg.mint,
+                token_program_a,
+            ),
+            false,
+        ));
+    }
+    accounts
+}
+/// The accounts of `InitializeEscrow`, with the mints of any basket legs
+pub struct InitializeEscrowAccounts<'a> {
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub vault_pda: Pubkey,
+    pub escrow_pda: Pubkey,
+    pub maker_token_acc_a: Pubkey,
+    pub maker_token_acc_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub basket_offered_mints: Vec<Pubkey>,
+    pub basket_requested_mints: Vec<Pubkey>,
+    pub maker_multisig: Option<&'a MultisigSigners>,
+}
+pub fn create_initialize_escrow_instruction(
+    program_id: &Pubkey,
+    accounts: &InitializeEscrowAccounts,
+    args: InitializeEscrowArgs,
 ) -> Instruction {
-    let mut instruction_data = vec![0u8];
-    instruction_data.extend_from_slice(&escrow_id.to_le_bytes());
-    instruction_data.extend_from_slice(&deposit_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&receive_amount.to_le_bytes());
+    let escrow_id = args.escrow_id;
+    let instruction_data = EscrowInstruction::InitializeEscrow(args).pack();
 
-    let instruction = Instruction {
+    let mut instruction = Instruction {
         program_id: *program_id, // The program to call
         accounts: vec![
             // Accounts the program needs
-            AccountMeta::new(maker.pubkey(), true),
-            AccountMeta::new_readonly(*token_mint_a, false),
-            AccountMeta::new_readonly(*token_mint_b, false),
-            AccountMeta::new(*maker_token_acc_a, false),
-            AccountMeta::new(*vault_pda, false),
-            AccountMeta::new(*escrow_pda, false),
-            AccountMeta::new(*maker_token_acc_b, false),
+            AccountMeta::new(accounts.maker, true),
+            AccountMeta::new_readonly(accounts.token_mint_a, false),
+            AccountMeta::new_readonly(accounts.token_mint_b, false),
+            AccountMeta::new(accounts.maker_token_acc_a, false),
+            AccountMeta::new(accounts.vault_pda, false),
+            AccountMeta::new(accounts.escrow_pda, false),
+            AccountMeta::new(accounts.maker_token_acc_b, false),
             AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(accounts.token_program_a, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(program_id), false),
         ],
         data: instruction_data, // Instruction data
     };
+    // The legs of a basket follow the fixed accounts
+    instruction
+        .accounts
+        .extend(basket_make_accounts(program_id, escrow_id, accounts));
+    // A multisig owning the maker's tokens comes last, with the members signing for it
+    if let Some(multisig) = accounts.maker_multisig {
+        instruction
+            .accounts
+            .extend(multisig.account_metas(&accounts.maker));
+    }
     instruction
 }
+/// An escrow's Token A vault and the accounts that go with it
+pub struct VaultAccounts {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub vault_pda: Pubkey,
+    pub escrow_pda: Pubkey,
+    pub token_program_a: Pubkey,
+    pub escrow_id: u64,
+}
+/// Builds a cancel instruction; with `operator` set, the operator signs instead of the maker
 pub fn create_cancel_instruction(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    maker_token_account: &Pubkey,
+    operator: Option<&Pubkey>,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::CancelEscrow {
+        escrow_id: vault.escrow_id,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(vault.maker, operator.is_none()),
+        AccountMeta::new(vault.mint_a, false),
+        AccountMeta::new(*maker_token_account, false),
+        AccountMeta::new(vault.escrow_pda, false),
+        AccountMeta::new(vault.vault_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(vault.token_program_a, false),
+    ];
+    if let Some(operator) = operator {
+        accounts.push(AccountMeta::new_readonly(*operator, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+/// Builds a permissionless claim of a vesting escrow's unlocked Token A for its beneficiary
+pub fn create_claim_vested_instruction(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    beneficiary_token_account: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ClaimVested {
+        escrow_id: vault.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(vault.maker, false),
+            AccountMeta::new(vault.mint_a, false),
+            AccountMeta::new(*beneficiary_token_account, false),
+            AccountMeta::new(vault.escrow_pda, false),
+            AccountMeta::new(vault.vault_pda, false),
+            AccountMeta::new_readonly(vault.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_approve_milestone_instruction(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    recipient_token_account: &Pubkey,
+    milestone: u8,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ApproveMilestone {
+        escrow_id: vault.escrow_id,
+        milestone,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(vault.maker, true),
+            AccountMeta::new(vault.mint_a, false),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new(vault.escrow_pda, false),
+            AccountMeta::new(vault.vault_pda, false),
+            AccountMeta::new_readonly(vault.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_approve_instruction(
+    program_id: &Pubkey,
+    approver: &Pubkey,
+    vault: &VaultAccounts,
+    recipient_token_account: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::Approve {
+        escrow_id: vault.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new(vault.maker, false),
+            AccountMeta::new(vault.mint_a, false),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new(vault.escrow_pda, false),
+            AccountMeta::new(vault.vault_pda, false),
+            AccountMeta::new_readonly(vault.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// A held arbiter escrow and the parties it settles between
+pub struct SettleAccounts<'a> {
+    pub escrow: &'a Escrow,
+    pub taker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+/// Lists the accounts shared by `ConfirmEscrow` and `Resolve`, paying each side to its
+/// associated token accounts; `signer` signs if it is the maker or the taker
+fn settle_accounts(
+    program_id: &Pubkey,
+    settle: &SettleAccounts,
+    signer: &Pubkey,
+) -> Vec<AccountMeta> {
+    let SettleAccounts {
+        escrow,
+        taker,
+        beneficiary,
+        token_program_a,
+        token_program_b,
+    } = settle;
+    let (vault_pda, escrow_pda) = derive_pdas(program_id, &escrow.maker, escrow.escrow_id);
+    let (payment_vault_pda, _) = find_payment_vault(program_id, &escrow.maker, escrow.escrow_id);
+    let token_account = |owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey| {
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(owner, mint, token_program),
+            false,
+        )
+    };
+    vec![
+        AccountMeta::new(escrow.maker, escrow.maker == *signer),
+        AccountMeta::new(*taker, taker == signer),
+        AccountMeta::new(escrow.token_mint_a, false),
+        AccountMeta::new(escrow.token_mint_b, false),
+        token_account(&escrow.token_owner(), &escrow.token_mint_a, token_program_a),
+        token_account(&escrow.token_owner(), &escrow.token_mint_b, token_program_b),
+        token_account(beneficiary, &escrow.token_mint_a, token_program_a),
+        token_account(beneficiary, &escrow.token_mint_b, token_program_b),
+        AccountMeta::new(vault_pda, false),
+        AccountMeta::new(payment_vault_pda, false),
+        AccountMeta::new(escrow_pda, false),
+        AccountMeta::new_readonly(*token_program_a, false),
+        AccountMeta::new_readonly(*token_program_b, false),
+    ]
+}
+
+/// Builds a confirmation of a held arbiter escrow, signed by its maker or taker
+pub fn create_confirm_escrow_instruction(
+    program_id: &Pubkey,
+    settle: &SettleAccounts,
+    signer: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ConfirmEscrow {
+        escrow_id: settle.escrow.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: settle_accounts(program_id, settle, signer),
+        data: instruction_data,
+    }
+}
+
+/// Builds the arbiter's resolution of a held arbiter escrow, releasing `release_bps` of the trade
+pub fn create_resolve_instruction(
+    program_id: &Pubkey,
+    settle: &SettleAccounts,
+    arbiter: &Pubkey,
+    release_bps: u16,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::Resolve {
+        escrow_id: settle.escrow.escrow_id,
+        release_bps,
+    }
+    .pack();
+    let mut accounts = settle_accounts(program_id, settle, arbiter);
+    accounts.push(AccountMeta::new_readonly(*arbiter, true));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+pub fn create_migrate_instruction(
     program_id: &Pubkey,
     maker: &Pubkey,
-    mint_a: &Pubkey,
     vault_pda: &Pubkey,
     escrow_pda: &Pubkey,
-    maker_token_account: &Pubkey,
     escrow_id: u64,
 ) -> Instruction {
-    let mut instruction_data = vec![2u8];
-    instruction_data.extend_from_slice(&escrow_id.to_le_bytes());
+    let instruction_data = EscrowInstruction::MigrateEscrow { escrow_id }.pack();
     Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(*maker, true),
-            AccountMeta::new_readonly(*mint_a, false),
-            AccountMeta::new(*maker_token_account, false),
             AccountMeta::new(*escrow_pda, false),
-            AccountMeta::new(*vault_pda, false),
+            AccountMeta::new_readonly(*vault_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(program_id), false),
         ],
         data: instruction_data,
     }
 }
 
-pub fn create_exchange_instruction(
+pub fn create_update_instruction(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    maker_token_account: &Pubkey,
+    receive_amount: Option<u64>,
+    deposit_amount: u64,
+    withdraw_amount: u64,
+    operator: Option<&Pubkey>,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::UpdateEscrow {
+        escrow_id: vault.escrow_id,
+        receive_amount,
+        deposit_amount,
+        withdraw_amount,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(vault.maker, operator.is_none()),
+        AccountMeta::new_readonly(vault.mint_a, false),
+        AccountMeta::new(*maker_token_account, false),
+        AccountMeta::new(vault.escrow_pda, false),
+        AccountMeta::new(vault.vault_pda, false),
+        AccountMeta::new_readonly(vault.token_program_a, false),
+    ];
+    if let Some(operator) = operator {
+        accounts.push(AccountMeta::new_readonly(*operator, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+pub fn create_set_operator_instruction(
     program_id: &Pubkey,
     maker: &Pubkey,
-    taker: &Keypair,
-    vault_pda: &Pubkey,
     escrow_pda: &Pubkey,
-    taker_token_a_acc: &Pubkey,
-    taker_token_b_acc: &Pubkey,
-    maker_token_b_acc: &Pubkey,
-    mint_a: &Pubkey,
-    mint_b: &Pubkey,
     escrow_id: u64,
+    operator: Option<Pubkey>,
 ) -> Instruction {
-    let mut instruction_data = vec![1u8];
-    instruction_data.extend_from_slice(&escrow_id.to_le_bytes());
+    let instruction_data = EscrowInstruction::SetOperator {
+        escrow_id,
+        operator,
+    }
+    .pack();
     Instruction {
         program_id: *program_id,
         accounts: vec![
-            AccountMeta::new(taker.pubkey(), true),
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(*escrow_pda, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// The fixed accounts of `ReleaseFunds`
+pub struct ExchangeAccounts {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub vault_pda: Pubkey,
+    pub escrow_pda: Pubkey,
+    pub taker_token_a_acc: Pubkey,
+    pub taker_token_b_acc: Pubkey,
+    pub maker_token_b_acc: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub fee_account: Option<Pubkey>,
+}
+pub fn create_exchange_instruction(
+    program_id: &Pubkey,
+    accounts: &ExchangeAccounts,
+    args: ReleaseFundsArgs,
+) -> Instruction {
+    let ExchangeAccounts {
+        maker,
+        taker,
+        vault_pda,
+        escrow_pda,
+        taker_token_a_acc,
+        taker_token_b_acc,
+        maker_token_b_acc,
+        mint_a,
+        mint_b,
+        token_program_a,
+        token_program_b,
+        fee_account,
+    } = accounts;
+    let instruction_data = EscrowInstruction::ReleaseFunds(args).pack();
+    let mut instruction = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*taker, true),
             AccountMeta::new(*maker, false),
-            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new(*mint_a, false),
             AccountMeta::new_readonly(*mint_b, false),
             AccountMeta::new(*maker_token_b_acc, false),
             AccountMeta::new(*taker_token_a_acc, false),
             AccountMeta::new(*taker_token_b_acc, false),
             AccountMeta::new(*vault_pda, false),
             AccountMeta::new(*escrow_pda, false),
-            AccountMeta::new(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*token_program_a, false),
+            AccountMeta::new_readonly(*token_program_b, false),
+            AccountMeta::new_readonly(derive_config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ],
+        data: instruction_data,
+    };
+    if let Some(fee_account) = fee_account {
+        instruction
+            .accounts
+            .push(AccountMeta::new(*fee_account, false));
+    }
+    instruction
+}
+
+pub fn create_initialize_config_instruction(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fee_bps: u16,
+    fee_recipient: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::InitializeConfig {
+        fee_bps,
+        fee_recipient: *fee_recipient,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(derive_config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(derive_program_data_address(program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_update_config_instruction(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    new_admin: Option<Pubkey>,
+    fee_bps: Option<u16>,
+    fee_recipient: Option<Pubkey>,
+    paused: Option<bool>,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::UpdateConfig {
+        new_admin,
+        fee_bps,
+        fee_recipient,
+        paused,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(derive_config_pda(program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// A bidder's side of a bid on an escrow
+pub struct BidAccounts {
+    pub bidder: Pubkey,
+    pub mint_b: Pubkey,
+    pub bidder_token_b_acc: Pubkey,
+    pub escrow_pda: Pubkey,
+    pub token_program_b: Pubkey,
+}
+pub fn create_place_bid_instruction(
+    program_id: &Pubkey,
+    bid: &BidAccounts,
+    escrow_id: u64,
+    bid_amount: u64,
+    expected_offered: u64,
+) -> Instruction {
+    let BidAccounts {
+        bidder,
+        mint_b,
+        bidder_token_b_acc,
+        escrow_pda,
+        token_program_b,
+    } = bid;
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(program_id, escrow_pda, bidder);
+    let instruction_data = EscrowInstruction::PlaceBid {
+        escrow_id,
+        bid_amount,
+        expected_offered,
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bidder, true),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new(*bidder_token_b_acc, false),
+            AccountMeta::new_readonly(*escrow_pda, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program_b, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// The accounts of `AcceptBid`, besides the bid PDAs derived from the escrow and the bidder
+pub struct AcceptBidAccounts {
+    pub maker: Pubkey,
+    pub bidder: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub maker_token_b_acc: Pubkey,
+    pub bidder_token_a_acc: Pubkey,
+    pub vault_pda: Pubkey,
+    pub escrow_pda: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub fee_account: Option<Pubkey>,
+}
+pub fn create_accept_bid_instruction(
+    program_id: &Pubkey,
+    accounts: &AcceptBidAccounts,
+    escrow_id: u64,
+) -> Instruction {
+    let AcceptBidAccounts {
+        maker,
+        bidder,
+        mint_a,
+        mint_b,
+        maker_token_b_acc,
+        bidder_token_a_acc,
+        vault_pda,
+        escrow_pda,
+        token_program_a,
+        token_program_b,
+        fee_account,
+    } = accounts;
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(program_id, escrow_pda, bidder);
+    let instruction_data = EscrowInstruction::AcceptBid { escrow_id }.pack();
+    let mut instruction = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new(*bidder, false),
+            AccountMeta::new(*mint_a, false),
+            AccountMeta::new(*mint_b, false),
+            AccountMeta::new(*maker_token_b_acc, false),
+            AccountMeta::new(*bidder_token_a_acc, false),
+            AccountMeta::new(*vault_pda, false),
+            AccountMeta::new(*escrow_pda, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(*token_program_a, false),
+            AccountMeta::new_readonly(*token_program_b, false),
+            AccountMeta::new_readonly(derive_config_pda(program_id), false),
+        ],
+        data: instruction_data,
+    };
+    if let Some(fee_account) = fee_account {
+        instruction
+            .accounts
+            .push(AccountMeta::new(*fee_account, false));
+    }
+    instruction
+}
+
+pub fn create_withdraw_bid_instruction(program_id: &Pubkey, bid: &BidAccounts) -> Instruction {
+    let BidAccounts {
+        bidder,
+        mint_b,
+        bidder_token_b_acc,
+        escrow_pda,
+        token_program_b,
+    } = bid;
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(program_id, escrow_pda, bidder);
+    let instruction_data = EscrowInstruction::WithdrawBid.pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bidder, true),
+            AccountMeta::new(*mint_b, false),
+            AccountMeta::new(*bidder_token_b_acc, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(*token_program_b, false),
         ],
         data: instruction_data,
     }
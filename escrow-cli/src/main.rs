@@ -1,14 +1,27 @@
-use clap::{arg, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
 use dotenv::dotenv;
 use escrow_cli::{
     commands::{
-        cancel::cancel_escrow, exchange::exchange_funds, initialize::initialize_escrow,
+        admin::{initialize_config, update_config, ConfigUpdate},
+        approve::approve_escrow,
+        bid::{accept_bid, place_bid, withdraw_bid},
+        cancel::cancel_escrow,
+        claim::claim_vested,
+        events::view_events,
+        exchange::{exchange_funds, TakeOrder},
+        initialize::{initialize_escrow, EscrowTerms},
+        migrate::migrate_escrow,
+        milestone::approve_milestone,
+        operator::set_operator,
+        settle::{confirm_escrow, resolve_escrow},
+        update::{update_escrow, EscrowUpdate},
         view::view_escrow,
     },
     error::EscrowCliError,
-    helper::{check_sol_balance, connect_to_network, get_wallet},
+    helper::{check_sol_balance, connect_to_network, get_wallet, load_multisig, parse_basket_leg},
 };
+use escrow_native::state::{OfferAsset, ReceiveAsset, VestingSchedule};
 use solana_sdk::signer::Signer;
 
 #[derive(Parser)]
@@ -22,6 +35,57 @@ struct Cli {
     network: String,
 }
 #[derive(Subcommand)]
+enum AdminCommands {
+    /// Create the program config; your wallet becomes the admin
+    InitConfig {
+        /// Protocol fee taken from each fill, in basis points (at most 1000)
+        #[arg(short = 'f', long, default_value_t = 0)]
+        fee_bps: u16,
+        /// Wallet that receives protocol fees
+        #[arg(short = 'r', long)]
+        fee_recipient: String,
+    },
+    /// Change the protocol fee, fee recipient or admin
+    UpdateConfig {
+        /// New protocol fee, in basis points (at most 1000)
+        #[arg(short = 'f', long)]
+        fee_bps: Option<u16>,
+        /// New wallet that receives protocol fees
+        #[arg(short = 'r', long)]
+        fee_recipient: Option<String>,
+        /// Hand the admin role to another wallet
+        #[arg(short = 'a', long)]
+        new_admin: Option<String>,
+    },
+    /// Stop new escrows and exchanges; cancels and refunds keep working
+    Pause,
+    /// Resume trading after a pause
+    Unpause,
+}
+/// Arguments that turn an escrow into a payout to --taker instead of a trade
+#[derive(Args)]
+struct PayoutArgs {
+    /// Pay --taker this much Token A when you approve the milestone, instead of trading
+    /// (repeatable; --expires-at is the deadline after which you can cancel the rest)
+    #[arg(
+        long,
+        requires_all = ["taker", "expires_at"],
+        conflicts_with_all = ["receive", "receive_sol", "vest_start", "arbiter"]
+    )]
+    milestone: Vec<u64>,
+    /// Pay --taker all of Token A once enough of these addresses approve, instead of
+    /// trading (repeatable; with --expires-at you can only cancel after it)
+    #[arg(
+        long,
+        requires = "taker",
+        conflicts_with_all = ["receive", "receive_sol", "vest_start", "arbiter", "milestone"]
+    )]
+    approver: Vec<String>,
+    /// Number of --approver approvals needed to release Token A (defaults to all of them)
+    #[arg(long, requires = "approver")]
+    approvals: Option<u8>,
+}
+#[derive(Subcommand)]
 enum Commands {
     /// Initialize a new escrow (offer tokens for exchange)
     Initialize {
@@ -32,17 +96,55 @@ enum Commands {
         #[arg(short = 'a', long)]
         mint_a: String,
         /// Token B mint address (what you want in return)
-        #[arg(short = 'b', long)]
-        mint_b: String,
+        #[arg(short = 'b', long, required_unless_present_any = ["receive_sol", "milestone", "approver"])]
+        mint_b: Option<String>,
+        /// Receive native SOL (in lamports) instead of Token B
+        #[arg(short = 's', long, conflicts_with = "mint_b")]
+        receive_sol: bool,
         /// Amount of Token A to deposit (in smallest units)
         #[arg(short = 'd', long)]
         deposit: u64,
-        /// Amount of Token B you want (in smallest units)
-        #[arg(short = 'r', long)]
-        receive: u64,
+        /// Amount of Token B (or lamports with --receive-sol) you want (in smallest units)
+        #[arg(short = 'r', long, required_unless_present_any = ["milestone", "approver"])]
+        receive: Option<u64>,
         /// Unique escrow ID (choose any number)
         #[arg(short = 'i', long)]
         escrow_id: u64,
+        /// Unix timestamp after which the escrow expires and anyone can refund it
+        #[arg(short = 'x', long)]
+        expires_at: Option<i64>,
+        /// Only this taker address may take the escrow (private escrow)
+        #[arg(short = 't', long)]
+        taker: Option<String>,
+        /// Also deposit this MINT:AMOUNT (basket escrow; repeatable)
+        #[arg(long)]
+        basket_offer: Vec<String>,
+        /// Also ask for this MINT:AMOUNT (basket escrow; repeatable)
+        #[arg(long)]
+        basket_request: Vec<String>,
+        /// Let this address cancel or reprice the escrow for you
+        #[arg(short = 'o', long)]
+        operator: Option<String>,
+        /// Sell a single NFT: mint A must have 0 decimals and a supply of 1, and --deposit be 1
+        #[arg(long)]
+        nft: bool,
+        /// Keep Token A locked after the sale, unlocking to the taker from this unix timestamp
+        #[arg(long, requires = "vest_end")]
+        vest_start: Option<i64>,
+        /// Unix timestamp at which all of Token A has unlocked to the taker
+        #[arg(long, requires = "vest_start")]
+        vest_end: Option<i64>,
+        /// Hold the taker's payment until you both confirm, or this address resolves the trade
+        #[arg(long, conflicts_with_all = ["receive_sol", "vest_start"])]
+        arbiter: Option<String>,
+        #[command(flatten)]
+        payout: Box<PayoutArgs>,
+        /// SPL multisig that owns your token accounts (you must be one of its members)
+        #[arg(long)]
+        multisig: Option<String>,
+        /// Keypair of another multisig member co-signing for it (repeatable)
+        #[arg(long, requires = "multisig")]
+        multisig_signer: Vec<String>,
     },
     /// Take escrow
     Exchange {
@@ -54,6 +156,74 @@ enum Commands {
 
         #[arg(short, long)]
         maker: String,
+        /// Amount of Token B to pay (in smallest units); fills the whole escrow if omitted
+        #[arg(short = 'f', long)]
+        fill: Option<u64>,
+        /// SPL multisig that owns your token accounts (you must be one of its members)
+        #[arg(long)]
+        multisig: Option<String>,
+        /// Keypair of another multisig member co-signing for it (repeatable)
+        #[arg(long, requires = "multisig")]
+        multisig_signer: Vec<String>,
+    },
+    /// Release the Token A a taken vesting escrow has unlocked so far to its taker
+    Claim {
+        /// Path to the wallet paying for the transaction (anyone may claim)
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+    },
+    /// Confirm a taken arbiter escrow as its maker or taker; it settles once both have
+    Confirm {
+        /// Path to the maker's or the taker's wallet
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+    },
+    /// Settle a taken arbiter escrow as its arbiter, splitting both vaults
+    Resolve {
+        /// Path to the arbiter's wallet
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// Share of the trade that goes ahead in basis points (10000 = all of it); the rest
+        /// of each vault goes back to whoever funded it
+        #[arg(short, long)]
+        release_bps: u16,
+    },
+    /// Approve a milestone of your milestone escrow, paying its Token A to the recipient
+    ApproveMilestone {
+        /// Path to your wallet keypair, as the escrow's maker
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// Index of the milestone to approve, counting from 0
+        #[arg(long)]
+        milestone: u8,
+    },
+    /// Approve an approval escrow as one of its approvers; it pays out once enough have
+    Approve {
+        /// Path to the approver's wallet
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
     },
     /// Cancel escrow
     Cancel {
@@ -64,6 +234,100 @@ enum Commands {
         mint_a: String,
         #[arg(short, long)]
         escrow_id: u64,
+        /// Maker's address, when cancelling as the escrow's operator
+        #[arg(short, long)]
+        maker: Option<String>,
+    },
+
+    /// Manage the program config (admin only)
+    Admin {
+        /// Path to the admin wallet keypair
+        #[arg(short, long)]
+        wallet: String,
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+    /// Update an open escrow (reprice, top up or withdraw Token A)
+    Update {
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// New amount of Token B you want (in smallest units)
+        #[arg(short = 'r', long)]
+        receive: Option<u64>,
+        /// Amount of Token A to add to the vault (in smallest units)
+        #[arg(short = 'd', long, conflicts_with = "withdraw")]
+        deposit: Option<u64>,
+        /// Amount of Token A to take back from the vault (in smallest units)
+        #[arg(short = 'x', long)]
+        withdraw: Option<u64>,
+        /// Maker's address, when repricing as the escrow's operator
+        #[arg(short, long)]
+        maker: Option<String>,
+        /// SPL multisig that owns your token accounts (needed to top up from it)
+        #[arg(long)]
+        multisig: Option<String>,
+        /// Keypair of another multisig member co-signing for it (repeatable)
+        #[arg(long, requires = "multisig")]
+        multisig_signer: Vec<String>,
+    },
+    /// Set or clear the operator who may cancel or reprice your escrow
+    SetOperator {
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// Operator's address; clears the operator if omitted
+        #[arg(short, long)]
+        operator: Option<String>,
+    },
+    /// Lock a counter-offer of Token B for everything an escrow has on offer
+    Bid {
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// Amount of Token B you offer (in smallest units)
+        #[arg(short = 'b', long)]
+        amount: u64,
+    },
+    /// Accept a bid on your escrow, settling it at the bid's price
+    AcceptBid {
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+        /// Address of the bidder whose bid to accept
+        #[arg(short, long)]
+        bidder: String,
+    },
+    /// Withdraw your bid and get its Token B back
+    WithdrawBid {
+        #[arg(short, long)]
+        wallet: String,
+        /// Maker's address
+        #[arg(short, long)]
+        maker: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+    },
+    /// Migrate an escrow account to the latest layout (you pay any extra rent)
+    Migrate {
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        escrow_id: u64,
+    },
+
+    /// Print the escrow events logged by a transaction
+    Events {
+        /// Transaction signature
+        #[arg(short, long)]
+        signature: String,
     },
 
     /// View escrow details
@@ -115,6 +379,19 @@ async fn main() -> Result<(), EscrowCliError> {
             deposit,
             receive,
             escrow_id,
+            expires_at,
+            taker,
+            receive_sol,
+            basket_offer,
+            basket_request,
+            operator,
+            nft,
+            vest_start,
+            vest_end,
+            arbiter,
+            payout,
+            multisig,
+            multisig_signer,
         } => {
             // println!("INITIALIZE ESCROW");
             // println!("   Wallet path: {}", wallet);
@@ -135,17 +412,52 @@ async fn main() -> Result<(), EscrowCliError> {
                 return Err(EscrowCliError::InsufficientSol {
                     needed: 5_000_000,
                     actual: sol_balance,
-                })?;
+                });
             }
+            let basket_offered = basket_offer
+                .iter()
+                .map(|leg| parse_basket_leg(leg))
+                .collect::<Result<Vec<_>, _>>()?;
+            let basket_requested = basket_request
+                .iter()
+                .map(|leg| parse_basket_leg(leg))
+                .collect::<Result<Vec<_>, _>>()?;
+            let multisig = load_multisig(multisig.as_deref(), &multisig_signer)?;
+            let terms = EscrowTerms {
+                mint_a: &mint_a,
+                mint_b: mint_b.as_deref(),
+                deposit_amount: deposit,
+                receive_amount: receive.unwrap_or_default(),
+                escrow_id,
+                expires_at,
+                allowed_taker: taker.as_deref(),
+                receive_asset: if receive_sol {
+                    ReceiveAsset::NativeSol
+                } else {
+                    ReceiveAsset::Token
+                },
+                offer_asset: if nft {
+                    OfferAsset::Nft
+                } else {
+                    OfferAsset::Token
+                },
+                vesting: vest_start
+                    .zip(vest_end)
+                    .map(|(start, end)| VestingSchedule { start, end }),
+                operator: operator.as_deref(),
+                arbiter: arbiter.as_deref(),
+                milestones: &payout.milestone,
+                approvers: &payout.approver,
+                approval_threshold: payout.approvals.unwrap_or(payout.approver.len() as u8),
+                basket_offered: &basket_offered,
+                basket_requested: &basket_requested,
+            };
             initialize_escrow(
                 &cli.network,
                 &program_id,
                 &maker,
-                &mint_a,
-                &mint_b,
-                deposit,
-                receive,
-                escrow_id,
+                terms,
+                multisig.as_ref(),
                 &client,
             )
             .await
@@ -157,6 +469,9 @@ async fn main() -> Result<(), EscrowCliError> {
             wallet,
             escrow_id,
             maker,
+            fill,
+            multisig,
+            multisig_signer,
         } => {
             let taker = get_wallet(&wallet).map_err(|e| {
                 EscrowCliError::WalletLoad(format!("failed to get tayer keypair:{}", e))
@@ -172,14 +487,19 @@ async fn main() -> Result<(), EscrowCliError> {
                 return Err(EscrowCliError::InsufficientSol {
                     needed: 5_000_000,
                     actual: sol_balance,
-                })?;
+                });
             };
+            let multisig = load_multisig(multisig.as_deref(), &multisig_signer)?;
             exchange_funds(
                 &cli.network,
                 &program_id,
                 &taker,
-                &maker,
-                escrow_id,
+                TakeOrder {
+                    maker: &maker,
+                    escrow_id,
+                    fill_amount: fill,
+                },
+                multisig.as_ref(),
                 &client,
             )
             .await
@@ -187,10 +507,118 @@ async fn main() -> Result<(), EscrowCliError> {
                 EscrowCliError::CustomError(format!("Failed to exchange the funds:{}", e))
             })?
         }
+        Commands::Claim {
+            wallet,
+            maker,
+            escrow_id,
+        } => {
+            let payer = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
+            })?;
+            claim_vested(
+                &cli.network,
+                &payer,
+                &program_id,
+                &maker,
+                escrow_id,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to claim the vested tokens:{}", e))
+            })?;
+        }
+        Commands::ApproveMilestone {
+            wallet,
+            escrow_id,
+            milestone,
+        } => {
+            let maker = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
+            })?;
+            approve_milestone(
+                &cli.network,
+                &maker,
+                &program_id,
+                escrow_id,
+                milestone,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to approve the milestone:{}", e))
+            })?;
+        }
+        Commands::Approve {
+            wallet,
+            maker,
+            escrow_id,
+        } => {
+            let approver = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get approver keypair:{}", e))
+            })?;
+            approve_escrow(
+                &cli.network,
+                &approver,
+                &program_id,
+                &maker,
+                escrow_id,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to approve the escrow:{}", e))
+            })?;
+        }
+        Commands::Confirm {
+            wallet,
+            maker,
+            escrow_id,
+        } => {
+            let wallet = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get wallet keypair:{}", e))
+            })?;
+            confirm_escrow(
+                &cli.network,
+                &wallet,
+                &program_id,
+                &maker,
+                escrow_id,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to confirm the escrow:{}", e))
+            })?;
+        }
+        Commands::Resolve {
+            wallet,
+            maker,
+            escrow_id,
+            release_bps,
+        } => {
+            let arbiter = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get arbiter keypair:{}", e))
+            })?;
+            resolve_escrow(
+                &cli.network,
+                &arbiter,
+                &program_id,
+                &maker,
+                escrow_id,
+                release_bps,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to resolve the escrow:{}", e))
+            })?;
+        }
         Commands::Cancel {
             wallet,
             escrow_id,
             mint_a,
+            maker: maker_address,
         } => {
             let maker = get_wallet(&wallet).map_err(|e| {
                 EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
@@ -203,7 +631,7 @@ async fn main() -> Result<(), EscrowCliError> {
                 return Err(EscrowCliError::InsufficientSol {
                     needed: 5_000_000,
                     actual: sol_balance,
-                })?;
+                });
             };
             cancel_escrow(
                 &cli.network,
@@ -211,6 +639,7 @@ async fn main() -> Result<(), EscrowCliError> {
                 escrow_id,
                 &program_id,
                 &mint_a,
+                maker_address.as_deref(),
                 &client,
             )
             .await
@@ -218,6 +647,205 @@ async fn main() -> Result<(), EscrowCliError> {
                 EscrowCliError::CustomError(format!("Failed to cancel the escrow:{}", e))
             })?;
         }
+        Commands::Admin { wallet, command } => {
+            let admin = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get admin keypair:{}", e))
+            })?;
+            match command {
+                AdminCommands::InitConfig {
+                    fee_bps,
+                    fee_recipient,
+                } => {
+                    initialize_config(
+                        &cli.network,
+                        &admin,
+                        &program_id,
+                        fee_bps,
+                        &fee_recipient,
+                        &client,
+                    )
+                    .await
+                }
+                AdminCommands::UpdateConfig {
+                    fee_bps,
+                    fee_recipient,
+                    new_admin,
+                } => {
+                    update_config(
+                        &cli.network,
+                        &admin,
+                        &program_id,
+                        ConfigUpdate {
+                            new_admin: new_admin.as_deref(),
+                            fee_bps,
+                            fee_recipient: fee_recipient.as_deref(),
+                            paused: None,
+                        },
+                        &client,
+                    )
+                    .await
+                }
+                AdminCommands::Pause => {
+                    update_config(
+                        &cli.network,
+                        &admin,
+                        &program_id,
+                        ConfigUpdate {
+                            paused: Some(true),
+                            ..Default::default()
+                        },
+                        &client,
+                    )
+                    .await
+                }
+                AdminCommands::Unpause => {
+                    update_config(
+                        &cli.network,
+                        &admin,
+                        &program_id,
+                        ConfigUpdate {
+                            paused: Some(false),
+                            ..Default::default()
+                        },
+                        &client,
+                    )
+                    .await
+                }
+            }
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to update the config:{}", e))
+            })?;
+        }
+        Commands::Update {
+            wallet,
+            escrow_id,
+            receive,
+            deposit,
+            withdraw,
+            maker: maker_address,
+            multisig,
+            multisig_signer,
+        } => {
+            let maker = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
+            })?;
+            let multisig = load_multisig(multisig.as_deref(), &multisig_signer)?;
+            update_escrow(
+                &cli.network,
+                &maker,
+                &program_id,
+                EscrowUpdate {
+                    maker: maker_address.as_deref(),
+                    escrow_id,
+                    receive_amount: receive,
+                    deposit_amount: deposit.unwrap_or_default(),
+                    withdraw_amount: withdraw.unwrap_or_default(),
+                },
+                multisig.as_ref(),
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to update the escrow:{}", e))
+            })?;
+        }
+        Commands::Migrate { wallet, escrow_id } => {
+            let maker = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
+            })?;
+            migrate_escrow(&cli.network, &maker, escrow_id, &program_id, &client)
+                .await
+                .map_err(|e| {
+                    EscrowCliError::CustomError(format!("Failed to migrate the escrow:{}", e))
+                })?;
+        }
+        Commands::SetOperator {
+            wallet,
+            escrow_id,
+            operator,
+        } => {
+            let maker = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get payer keypair:{}", e))
+            })?;
+            set_operator(
+                &cli.network,
+                &maker,
+                escrow_id,
+                &program_id,
+                operator.as_deref(),
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to set the operator:{}", e))
+            })?;
+        }
+        Commands::Bid {
+            wallet,
+            maker,
+            escrow_id,
+            amount,
+        } => {
+            let bidder = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get bidder keypair:{}", e))
+            })?;
+            place_bid(
+                &cli.network,
+                &bidder,
+                &program_id,
+                &maker,
+                escrow_id,
+                amount,
+                &client,
+            )
+            .await
+            .map_err(|e| EscrowCliError::CustomError(format!("Failed to place the bid:{}", e)))?;
+        }
+        Commands::AcceptBid {
+            wallet,
+            escrow_id,
+            bidder,
+        } => {
+            let maker = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get maker keypair:{}", e))
+            })?;
+            accept_bid(
+                &cli.network,
+                &maker,
+                &program_id,
+                &bidder,
+                escrow_id,
+                &client,
+            )
+            .await
+            .map_err(|e| EscrowCliError::CustomError(format!("Failed to accept the bid:{}", e)))?;
+        }
+        Commands::WithdrawBid {
+            wallet,
+            maker,
+            escrow_id,
+        } => {
+            let bidder = get_wallet(&wallet).map_err(|e| {
+                EscrowCliError::WalletLoad(format!("failed to get bidder keypair:{}", e))
+            })?;
+            withdraw_bid(
+                &cli.network,
+                &bidder,
+                &program_id,
+                &maker,
+                escrow_id,
+                &client,
+            )
+            .await
+            .map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to withdraw the bid:{}", e))
+            })?;
+        }
+        Commands::Events { signature } => {
+            view_events(&signature, &client).await.map_err(|e| {
+                EscrowCliError::CustomError(format!("Failed to view the events:{}", e))
+            })?;
+        }
         Commands::View { escrow_id, maker } => {
             view_escrow(&cli.network, &program_id, &maker, escrow_id, &client)
                 .await
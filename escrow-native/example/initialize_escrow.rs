@@ -1,11 +1,14 @@
-use escrow_native::state::Escrow;
+use escrow_native::{
+    instructions::instruction::{EscrowInstruction, InitializeEscrowArgs},
+    state::{Config, Escrow},
+};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     instruction::{AccountMeta, Instruction},
     program_pack::Pack,
     pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair, Signer},
+    signature::{read_keypair_file, Signer},
     system_program, sysvar,
     transaction::Transaction,
 };
@@ -126,17 +129,22 @@ async fn main() {
     }
 
     let initialize_instruction = create_initialize_escrow_instruction(
-        &payer,
-        escrow_id,
-        deposit_amount,
-        receive_amount,
         program_id,
-        token_mint_a.pubkey(),
-        token_mint_b.pubkey(),
-        vault_pda,
-        escrow_pda,
-        maker_token_acc_a,
-        maker_token_acc_b,
+        &InitializeEscrowAccounts {
+            maker: payer.pubkey(),
+            token_mint_a: token_mint_a.pubkey(),
+            token_mint_b: token_mint_b.pubkey(),
+            vault_pda,
+            escrow_pda,
+            maker_token_acc_a,
+            maker_token_acc_b,
+        },
+        InitializeEscrowArgs {
+            escrow_id,
+            deposit_amount,
+            receive_amount,
+            ..Default::default()
+        },
     );
 
     let mut transaction =
@@ -172,40 +180,42 @@ async fn main() {
     println!("escrow account:{:#?}", escrow_data);
 }
 
-fn create_initialize_escrow_instruction(
-    maker: &Keypair,
-    escrow_id: u64,
-    deposit_amount: u64,
-    receive_amount: u64,
-    program_id: Pubkey,
+struct InitializeEscrowAccounts {
+    maker: Pubkey,
     token_mint_a: Pubkey,
     token_mint_b: Pubkey,
     vault_pda: Pubkey,
     escrow_pda: Pubkey,
     maker_token_acc_a: Pubkey,
     maker_token_acc_b: Pubkey,
+}
+
+fn create_initialize_escrow_instruction(
+    program_id: Pubkey,
+    accounts: &InitializeEscrowAccounts,
+    args: InitializeEscrowArgs,
 ) -> Instruction {
-    let mut instruction_data = vec![0u8];
-    instruction_data.extend_from_slice(&escrow_id.to_le_bytes());
-    instruction_data.extend_from_slice(&deposit_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&receive_amount.to_le_bytes());
+    let instruction_data = EscrowInstruction::InitializeEscrow(args).pack();
 
-    let instruction = Instruction {
-        program_id: program_id, // The program to call
+    Instruction {
+        program_id, // The program to call
         accounts: vec![
             // Accounts the program needs
-            AccountMeta::new(maker.pubkey(), true),
-            AccountMeta::new_readonly(token_mint_a, false),
-            AccountMeta::new_readonly(token_mint_b, false),
-            AccountMeta::new(maker_token_acc_a, false),
-            AccountMeta::new(vault_pda, false),
-            AccountMeta::new(escrow_pda, false),
-            AccountMeta::new(maker_token_acc_b, false),
+            AccountMeta::new(accounts.maker, true),
+            AccountMeta::new_readonly(accounts.token_mint_a, false),
+            AccountMeta::new_readonly(accounts.token_mint_b, false),
+            AccountMeta::new(accounts.maker_token_acc_a, false),
+            AccountMeta::new(accounts.vault_pda, false),
+            AccountMeta::new(accounts.escrow_pda, false),
+            AccountMeta::new(accounts.maker_token_acc_b, false),
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(
+                Pubkey::find_program_address(&[Config::SEED], &program_id).0,
+                false,
+            ),
         ],
         data: instruction_data, // Instruction data
-    };
-    instruction
+    }
 }
@@ -0,0 +1,180 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::instruction::{close_account, initialize_account3, transfer_checked};
+
+use crate::{
+    error::EscrowError,
+    instructions::create_pda_account,
+    state::Escrow,
+    token::{harvest_vault_fees, unpack_mint, unpack_token_account, vault_account_len},
+};
+
+/// Seed prefix of the vault holding the taker's payment for an arbiter escrow.
+pub const PAYMENT_VAULT_SEED: &[u8] = b"payment_vault";
+
+/// The `release_bps` of a trade that goes ahead in full.
+pub const MAX_RELEASE_BPS: u16 = 10_000;
+
+/// Derives the PDA of the vault holding the taker's token B payment for an arbiter escrow.
+///
+/// Seeds are `[b"payment_vault", maker, escrow_id_le]`, next to the token A vault's
+/// `[b"vault", maker, escrow_id_le]`.
+pub fn find_payment_vault(program_id: &Pubkey, maker: &Pubkey, escrow_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PAYMENT_VAULT_SEED, maker.as_ref(), &escrow_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Creates the payment vault of an arbiter escrow as a token B account owned by its own PDA.
+///
+/// The taker pays its rent, which goes back to the taker when the escrow settles. The vault's
+/// bump is stored on `escrow`, which the caller then writes back.
+pub fn create_payment_vault<'a>(
+    program_id: &Pubkey,
+    escrow: &mut Escrow,
+    payer_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (vault_pda, vault_bump) = find_payment_vault(program_id, &escrow.maker, escrow.escrow_id);
+    if *vault_info.key != vault_pda {
+        msg!("Error: Payment vault PDA derivation mismatch.");
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // STEP 1: Create the vault account, signed for by its PDA. It may have been funded
+    // ahead of time, which must not block the take.
+    msg!("Creating payment vault...");
+    create_pda_account(
+        payer_info,
+        vault_info,
+        vault_account_len(mint_info)?,
+        token_program_info.key,
+        system_program_info,
+        &[
+            PAYMENT_VAULT_SEED,
+            escrow.maker.as_ref(),
+            &escrow.escrow_id.to_le_bytes(),
+            &[vault_bump],
+        ],
+    )?;
+
+    // STEP 2: Initialize it as a token B account with the PDA as its authority.
+    invoke(
+        &initialize_account3(
+            token_program_info.key,
+            vault_info.key,
+            mint_info.key,
+            &vault_pda,
+        )?,
+        &[vault_info.clone(), mint_info.clone()],
+    )?;
+    escrow.payment_vault_bump = vault_bump;
+    Ok(())
+}
+
+/// The accounts `split_vault` pays out of and closes a vault with.
+pub struct SplitVaultAccounts<'a, 'b> {
+    /// The mint of the vault's tokens.
+    pub mint_info: &'b AccountInfo<'a>,
+    /// The vault PDA, closed here.
+    pub vault_info: &'b AccountInfo<'a>,
+    /// The token account receiving `share_bps` of the vault.
+    pub share_info: &'b AccountInfo<'a>,
+    /// The token account receiving the rest of the vault.
+    pub rest_info: &'b AccountInfo<'a>,
+    /// The account that gets the vault's rent back.
+    pub rent_recipient_info: &'b AccountInfo<'a>,
+    /// The token program of the vault's mint.
+    pub token_program_info: &'b AccountInfo<'a>,
+}
+
+/// Pays `share_bps` of a vault's balance to `share_info` and the rest to `rest_info`, then
+/// closes the vault, returning its rent to `rent_recipient_info`.
+///
+/// `signer_seeds` are the vault PDA's seeds, bump included. Returns the amounts paid to
+/// `share_info` and `rest_info`, which include anything sent to the vault outside the escrow.
+pub fn split_vault(
+    vault_pda: &Pubkey,
+    signer_seeds: &[&[u8]],
+    share_bps: u16,
+    accounts: SplitVaultAccounts,
+) -> Result<(u64, u64), ProgramError> {
+    let SplitVaultAccounts {
+        mint_info,
+        vault_info,
+        share_info,
+        rest_info,
+        rent_recipient_info,
+        token_program_info,
+    } = accounts;
+    let vault_amount = unpack_token_account(vault_info, token_program_info.key)?.amount;
+    let share_amount = ((vault_amount as u128)
+        .checked_mul(share_bps as u128)
+        .ok_or(EscrowError::ArithmeticOverflow)?
+        / MAX_RELEASE_BPS as u128) as u64;
+    let rest_amount = vault_amount
+        .checked_sub(share_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    let decimals = unpack_mint(mint_info)?.decimals;
+
+    // STEP 1: Pay each side its part of the vault.
+    for (destination_info, amount) in [(share_info, share_amount), (rest_info, rest_amount)] {
+        if amount == 0 {
+            continue;
+        }
+        msg!(
+            "Transferring {} of mint {} to {}...",
+            amount,
+            mint_info.key,
+            destination_info.key
+        );
+        invoke_signed(
+            &transfer_checked(
+                token_program_info.key,
+                vault_info.key,
+                mint_info.key,
+                destination_info.key,
+                vault_pda,
+                &[vault_pda],
+                amount,
+                decimals,
+            )?,
+            &[
+                vault_info.clone(),
+                mint_info.clone(),
+                destination_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+    }
+
+    // STEP 2: Close the vault, returning its rent.
+    harvest_vault_fees(token_program_info, mint_info, vault_info)?;
+    invoke_signed(
+        &close_account(
+            token_program_info.key,
+            vault_info.key,
+            rent_recipient_info.key,
+            vault_pda,
+            &[vault_pda],
+        )?,
+        &[
+            vault_info.clone(),
+            rent_recipient_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+    Ok((share_amount, rest_amount))
+}
@@ -0,0 +1,399 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::instruction::{close_account, initialize_account2, transfer_checked};
+
+use crate::{
+    error::EscrowError,
+    instructions::create_pda_account,
+    state::{Escrow, OfferedLeg},
+    token::{
+        check_mint_extensions, check_token_program, gross_amount_for_net, harvest_vault_fees,
+        unpack_mint, unpack_token_account, vault_account_len, TokenAuthority,
+    },
+};
+
+/// Seed prefix of the vaults holding the extra offered legs of a basket escrow.
+pub const BASKET_VAULT_SEED: &[u8] = b"basket_vault";
+
+/// Derives the vault PDA of the offered basket leg at `index`.
+///
+/// Seeds are `[b"basket_vault", maker, escrow_id_le, index]`; the vault of token A
+/// keeps its original `[b"vault", maker, escrow_id_le]` seeds.
+pub fn find_basket_vault(
+    program_id: &Pubkey,
+    maker: &Pubkey,
+    escrow_id: u64,
+    index: u8,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            BASKET_VAULT_SEED,
+            maker.as_ref(),
+            &escrow_id.to_le_bytes(),
+            &[index],
+        ],
+        program_id,
+    )
+}
+
+/// The accounts `deposit_basket_leg` uses to deposit one offered leg.
+pub struct DepositLegAccounts<'a, 'b> {
+    /// The maker, who pays the vault's rent.
+    pub maker_info: &'b AccountInfo<'a>,
+    /// The authority over the maker's token accounts, which signs the deposit.
+    pub maker_authority: &'b TokenAuthority<'a, 'b>,
+    /// The mint of the leg.
+    pub mint_info: &'b AccountInfo<'a>,
+    /// The maker's token account the leg is deposited from.
+    pub maker_token_info: &'b AccountInfo<'a>,
+    /// The leg's vault PDA, created here.
+    pub vault_info: &'b AccountInfo<'a>,
+    /// The System Program, which creates the vault.
+    pub system_program_info: &'b AccountInfo<'a>,
+    /// The token program of token A.
+    pub token_program_info: &'b AccountInfo<'a>,
+    /// The Rent Sysvar.
+    pub rent_sysvar_info: &'b AccountInfo<'a>,
+}
+
+/// Creates the vault of an offered basket leg and moves the maker's deposit into it.
+///
+/// The leg's mint must be owned by `token_program_info`, the token program of token A.
+/// The maker pays the vault's rent and `maker_authority` signs the deposit.
+/// Returns the leg to record, with the amount that reached the vault after any Token-2022
+/// transfer fee and the bump of its vault.
+pub fn deposit_basket_leg(
+    program_id: &Pubkey,
+    escrow_id: u64,
+    index: u8,
+    deposit_amount: u64,
+    accounts: DepositLegAccounts,
+) -> Result<OfferedLeg, ProgramError> {
+    let DepositLegAccounts {
+        maker_info,
+        maker_authority,
+        mint_info,
+        maker_token_info,
+        vault_info,
+        system_program_info,
+        token_program_info,
+        rent_sysvar_info,
+    } = accounts;
+    // --- Validation Checks ---
+    if !maker_token_info.is_writable || !vault_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    check_token_program(mint_info, token_program_info)?;
+    check_mint_extensions(mint_info)?;
+    let maker_token_data = unpack_token_account(maker_token_info, token_program_info.key)?;
+    if maker_token_data.owner != *maker_authority.key() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if maker_token_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    if maker_token_data.amount < deposit_amount {
+        msg!(
+            "Error: Maker has {} of basket mint {}, needs {}.",
+            maker_token_data.amount,
+            mint_info.key,
+            deposit_amount
+        );
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    let (vault_pda, vault_bump) = find_basket_vault(program_id, maker_info.key, escrow_id, index);
+    if *vault_info.key != vault_pda {
+        msg!("Error: Basket vault {} PDA derivation mismatch.", index);
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // STEP 1: Create the leg's vault, owned by its PDA. It may have been funded ahead of
+    // time, which must not block the escrow.
+    if vault_info.data_is_empty() {
+        msg!("Creating basket vault {}...", index);
+        create_pda_account(
+            maker_info,
+            vault_info,
+            vault_account_len(mint_info)?,
+            token_program_info.key,
+            system_program_info,
+            &[
+                BASKET_VAULT_SEED,
+                maker_info.key.as_ref(),
+                &escrow_id.to_le_bytes(),
+                &[index],
+                &[vault_bump],
+            ],
+        )?;
+        invoke(
+            &initialize_account2(
+                token_program_info.key,
+                vault_info.key,
+                mint_info.key,
+                &vault_pda,
+            )?,
+            &[
+                vault_info.clone(),
+                mint_info.clone(),
+                rent_sysvar_info.clone(),
+            ],
+        )?;
+    }
+
+    // STEP 2: Transfer the maker's deposit into the vault.
+    msg!(
+        "Transferring {} of basket mint {} to vault...",
+        deposit_amount,
+        mint_info.key
+    );
+    let vault_balance_before = unpack_token_account(vault_info, token_program_info.key)?.amount;
+    maker_authority.transfer_checked(
+        token_program_info,
+        maker_token_info,
+        mint_info,
+        vault_info,
+        deposit_amount,
+        unpack_mint(mint_info)?.decimals,
+    )?;
+    let received = unpack_token_account(vault_info, token_program_info.key)?
+        .amount
+        .checked_sub(vault_balance_before)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    if received == 0 {
+        msg!(
+            "Error: Nothing reached basket vault {} after transfer fees.",
+            index
+        );
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    Ok(OfferedLeg {
+        mint: *mint_info.key,
+        amount: received,
+        vault_bump,
+    })
+}
+
+/// The accounts `release_basket_vault` uses to empty and close one offered leg's vault.
+pub struct ReleaseLegAccounts<'a, 'b> {
+    /// The maker, who gets the vault's rent back.
+    pub maker_info: &'b AccountInfo<'a>,
+    /// The mint of the leg.
+    pub mint_info: &'b AccountInfo<'a>,
+    /// The leg's vault PDA, closed here.
+    pub vault_info: &'b AccountInfo<'a>,
+    /// The recipient's token account for the leg.
+    pub destination_info: &'b AccountInfo<'a>,
+    /// The token program of token A.
+    pub token_program_info: &'b AccountInfo<'a>,
+}
+
+/// Empties the vault of an offered basket leg to `recipient`'s token account and closes it.
+///
+/// The vault's rent goes back to the maker. Returns the amount released, which includes
+/// anything sent to the vault outside the escrow.
+pub fn release_basket_vault(
+    program_id: &Pubkey,
+    escrow: &Escrow,
+    index: u8,
+    recipient: &Pubkey,
+    accounts: ReleaseLegAccounts,
+) -> Result<u64, ProgramError> {
+    let ReleaseLegAccounts {
+        maker_info,
+        mint_info,
+        vault_info,
+        destination_info,
+        token_program_info,
+    } = accounts;
+    // --- Validation Checks ---
+    if !vault_info.is_writable || !destination_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    check_token_program(mint_info, token_program_info)?;
+    let vault_pda = escrow.basket_vault_address(program_id, index)?;
+    let vault_bump = escrow.basket_offered[index as usize].vault_bump;
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+    if vault_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let destination_data = unpack_token_account(destination_info, token_program_info.key)?;
+    if destination_data.owner != *recipient {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if destination_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    let escrow_seed = escrow.escrow_id.to_le_bytes();
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        BASKET_VAULT_SEED,
+        escrow.maker.as_ref(),
+        escrow_seed.as_ref(),
+        &[index],
+        &[vault_bump],
+    ]];
+
+    // STEP 1: Move everything in the vault to the recipient.
+    let release_amount = vault_data.amount;
+    if release_amount > 0 {
+        msg!(
+            "Transferring {} of basket mint {} to {}...",
+            release_amount,
+            mint_info.key,
+            recipient
+        );
+        invoke_signed(
+            &transfer_checked(
+                token_program_info.key,
+                vault_info.key,
+                mint_info.key,
+                destination_info.key,
+                &vault_pda,
+                &[&vault_pda],
+                release_amount,
+                unpack_mint(mint_info)?.decimals,
+            )?,
+            &[
+                vault_info.clone(),
+                mint_info.clone(),
+                destination_info.clone(),
+                token_program_info.clone(),
+            ],
+            vault_signer_seeds,
+        )?;
+    }
+
+    // STEP 2: Close the vault, returning its rent to the maker.
+    harvest_vault_fees(token_program_info, mint_info, vault_info)?;
+    invoke_signed(
+        &close_account(
+            token_program_info.key,
+            vault_info.key,
+            maker_info.key,
+            &vault_pda,
+            &[&vault_pda],
+        )?,
+        &[
+            vault_info.clone(),
+            maker_info.clone(),
+            token_program_info.clone(),
+        ],
+        vault_signer_seeds,
+    )?;
+    Ok(release_amount)
+}
+
+/// The accounts `pay_basket_leg` uses to pay one requested leg.
+pub struct PayLegAccounts<'a, 'b> {
+    /// The authority over the taker's token accounts, which signs the payment.
+    pub taker_authority: &'b TokenAuthority<'a, 'b>,
+    /// The mint of the leg.
+    pub mint_info: &'b AccountInfo<'a>,
+    /// The taker's token account the leg is paid from.
+    pub taker_token_info: &'b AccountInfo<'a>,
+    /// The maker's token account for the leg.
+    pub maker_token_info: &'b AccountInfo<'a>,
+    /// The token program of token B.
+    pub token_program_info: &'b AccountInfo<'a>,
+}
+
+/// Pays a requested basket leg from the taker to the maker, less the protocol fee.
+///
+/// `fee` is the fee recipient with their token account for the leg's mint, and
+/// `fee_amount` what they should receive. Like token B, the taker pays any
+/// Token-2022 transfer fee on top. `maker_token_owner` is the owner the maker's
+/// account must have, the maker or the maker's multisig.
+pub fn pay_basket_leg<'a>(
+    amount: u64,
+    fee_amount: u64,
+    maker_token_owner: &Pubkey,
+    fee: Option<(Pubkey, &AccountInfo<'a>)>,
+    accounts: PayLegAccounts<'a, '_>,
+) -> ProgramResult {
+    let PayLegAccounts {
+        taker_authority,
+        mint_info,
+        taker_token_info,
+        maker_token_info,
+        token_program_info,
+    } = accounts;
+    // --- Validation Checks ---
+    if !taker_token_info.is_writable || !maker_token_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    check_token_program(mint_info, token_program_info)?;
+    let taker_token_data = unpack_token_account(taker_token_info, token_program_info.key)?;
+    if taker_token_data.owner != *taker_authority.key() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if taker_token_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let maker_token_data = unpack_token_account(maker_token_info, token_program_info.key)?;
+    if maker_token_data.owner != *maker_token_owner {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    if maker_token_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    if let Some((fee_recipient, fee_account_info)) = fee {
+        let fee_token_data = unpack_token_account(fee_account_info, token_program_info.key)?;
+        if fee_token_data.owner != fee_recipient {
+            return Err(EscrowError::InvalidAccountOwner.into());
+        }
+        if fee_token_data.mint != *mint_info.key {
+            return Err(EscrowError::InvalidMint.into());
+        }
+    }
+    let maker_amount = amount
+        .checked_sub(fee_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    let maker_payment = gross_amount_for_net(mint_info, maker_amount)?;
+    let fee_payment = gross_amount_for_net(mint_info, fee_amount)?;
+    let payment_amount = maker_payment
+        .checked_add(fee_payment)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    if taker_token_data.amount < payment_amount {
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    let decimals = unpack_mint(mint_info)?.decimals;
+
+    // STEP 1: Pay the protocol fee, then the maker.
+    if let Some((_, fee_account_info)) = fee {
+        msg!(
+            "Transferring {} of basket mint {} protocol fee from taker...",
+            fee_payment,
+            mint_info.key
+        );
+        taker_authority.transfer_checked(
+            token_program_info,
+            taker_token_info,
+            mint_info,
+            fee_account_info,
+            fee_payment,
+            decimals,
+        )?;
+    }
+    msg!(
+        "Transferring {} of basket mint {} from taker to maker...",
+        maker_payment,
+        mint_info.key
+    );
+    taker_authority.transfer_checked(
+        token_program_info,
+        taker_token_info,
+        mint_info,
+        maker_token_info,
+        maker_payment,
+        decimals,
+    )
+}
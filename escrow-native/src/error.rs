@@ -27,6 +27,48 @@ pub enum EscrowError {
     InvalidEscrowId,
     #[error("Arithmetic Overflow")]
     ArithmeticOverflow,
+    #[error("Fill amount exceeds the remaining amount")]
+    FillExceedsRemaining,
+    #[error("Expiry must be in the future")]
+    InvalidExpiry,
+    #[error("Escrow has expired")]
+    EscrowExpired,
+    #[error("Escrow has not expired yet")]
+    EscrowNotExpired,
+    #[error("Mint uses an unsupported token extension")]
+    UnsupportedMintExtension,
+    #[error("Escrow is not open")]
+    EscrowNotOpen,
+    #[error("Escrow account uses an outdated layout and must be migrated")]
+    OutdatedAccountVersion,
+    #[error("Escrow terms changed since they were quoted")]
+    EscrowTermsChanged,
+    #[error("Fee exceeds the maximum")]
+    InvalidFee,
+    #[error("Program is paused")]
+    ProgramPaused,
+    #[error("Invalid basket legs")]
+    InvalidBasket,
+    #[error("Invalid bid")]
+    InvalidBid,
+    #[error("Fill is outside the taker's slippage limits")]
+    SlippageExceeded,
+    #[error("Mint A is not an NFT")]
+    InvalidNft,
+    #[error("Invalid vesting schedule")]
+    InvalidVesting,
+    #[error("No vested tokens to claim yet")]
+    NothingToClaim,
+    #[error("Invalid arbiter escrow")]
+    InvalidArbiter,
+    #[error("Invalid milestone")]
+    InvalidMilestone,
+    #[error("Invalid approvers")]
+    InvalidApprovers,
+    #[error("Approver has already approved")]
+    AlreadyApproved,
+    #[error("Escrow already exists")]
+    EscrowAlreadyExists,
 }
 impl From<EscrowError> for ProgramError {
     fn from(error: EscrowError) -> Self {
@@ -0,0 +1,184 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::ReceiveAsset;
+
+/// Emitted when a maker opens an escrow.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowCreated {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The mint of the token A on offer.
+    pub token_mint_a: Pubkey,
+    /// The mint of the token B asked for; the native mint for SOL.
+    pub token_mint_b: Pubkey,
+    /// The amount of token A that reached the vault.
+    pub offered: u64,
+    /// The amount of token B (or lamports) the maker asks for it.
+    pub receive: u64,
+    /// Whether the maker receives token B or native SOL.
+    pub receive_asset: ReceiveAsset,
+    /// Unix timestamp after which the escrow can no longer be taken, if any.
+    pub expires_at: Option<i64>,
+    /// The only account allowed to take the escrow, if it is private.
+    pub allowed_taker: Option<Pubkey>,
+}
+
+/// Emitted for every fill of an escrow, partial or final, including accepted bids.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowFilled {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The account that filled the escrow.
+    pub taker: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The amount of token A released to the taker.
+    pub token_a_released: u64,
+    /// The amount of token B (or lamports) paid for it, including the protocol fee.
+    pub token_b_paid: u64,
+    /// The part of `token_b_paid` that went to the fee recipient.
+    pub fee_paid: u64,
+    /// The amount of token A still on offer; zero once the escrow is closed.
+    pub remaining_offered: u64,
+    /// The amount of token B still expected for `remaining_offered`.
+    pub remaining_receive: u64,
+}
+
+/// Emitted when the maker cancels an escrow.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowCancelled {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The amount of token A returned to the maker.
+    pub refunded: u64,
+}
+
+/// Emitted when an expired escrow is refunded to its maker.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowExpired {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The amount of token A returned to the maker.
+    pub refunded: u64,
+}
+
+/// Emitted when unlocked token A of a vesting escrow is claimed.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowClaimed {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The owner of the token account the claim was paid to.
+    pub beneficiary: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The amount of token A released by this claim.
+    pub claimed: u64,
+    /// The amount of token A still vesting; zero once the escrow is closed.
+    pub remaining: u64,
+}
+
+/// Emitted when a held arbiter escrow is settled, by both parties or by the arbiter.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowSettled {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The account that took the escrow.
+    pub taker: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The arbiter, if it resolved the escrow rather than both parties confirming.
+    pub resolved_by: Option<Pubkey>,
+    /// The amount of token A released to the taker.
+    pub token_a_to_taker: u64,
+    /// The amount of token A returned to the maker.
+    pub token_a_to_maker: u64,
+    /// The amount of token B released to the maker.
+    pub token_b_to_maker: u64,
+    /// The amount of token B returned to the taker.
+    pub token_b_to_taker: u64,
+}
+
+/// Emitted when the maker approves a milestone, releasing its tranche to the recipient.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowMilestoneApproved {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The owner of the token account the tranche was paid to.
+    pub recipient: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The index of the approved milestone.
+    pub milestone: u8,
+    /// The amount of token A released by this approval.
+    pub released: u64,
+    /// The amount of token A left for unapproved milestones; zero once the escrow is closed.
+    pub remaining: u64,
+}
+
+/// Emitted when an approver approves an approval escrow; the approval that reaches the
+/// threshold also releases token A to the recipient.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct EscrowApproved {
+    /// The escrow state account.
+    pub escrow: Pubkey,
+    /// The account that created the escrow.
+    pub maker: Pubkey,
+    /// The approver that signed this approval.
+    pub approver: Pubkey,
+    /// The maker's identifier for the escrow.
+    pub escrow_id: u64,
+    /// The number of approvals recorded, including this one.
+    pub approvals: u8,
+    /// The number of approvals needed to release token A.
+    pub threshold: u8,
+    /// The amount of token A released to the recipient; zero until the threshold is reached.
+    pub released: u64,
+}
+
+/// A structured event logged by the program with `sol_log_data`.
+///
+/// Each event is logged as a single Borsh-serialized `EscrowEvent`, so the enum tag
+/// tells indexers which event they are decoding. RPC nodes show it as a
+/// `Program data: <base64>` log line.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum EscrowEvent {
+    Created(EscrowCreated),
+    Filled(EscrowFilled),
+    Cancelled(EscrowCancelled),
+    Expired(EscrowExpired),
+    Claimed(EscrowClaimed),
+    Settled(EscrowSettled),
+    MilestoneApproved(EscrowMilestoneApproved),
+    Approved(EscrowApproved),
+}
+impl EscrowEvent {
+    /// Logs the event with `sol_log_data`.
+    pub fn emit(&self) {
+        sol_log_data(&[&borsh::to_vec(self).expect("EscrowEvent serialization cannot fail")]);
+    }
+    /// Decodes an event from the bytes of a `Program data:` log line.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
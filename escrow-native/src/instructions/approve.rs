@@ -0,0 +1,236 @@
+use crate::{
+    error::EscrowError,
+    events::{EscrowApproved, EscrowEvent},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, harvest_vault_fees, unpack_mint, unpack_token_account},
+};
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke_signed, pubkey::Pubkey,
+};
+use spl_token_2022::instruction::{close_account, transfer_checked};
+
+/// Records an approver's approval of an approval escrow, releasing Token A to the recipient once
+/// enough approvers have approved.
+///
+/// Only the escrow's approvers can approve, each once. Every approval but the one that reaches the
+/// escrow's threshold is only recorded in the escrow state. That last one empties the vault,
+/// including anything sent to it outside the escrow, into a Token A account owned by the escrow's
+/// recipient, its `beneficiary`, and closes the vault and escrow state accounts, returning their
+/// rent to the maker.
+///
+/// Accounts expected:
+///
+/// 0. `[signer]` `approver_info`: One of the escrow's approvers.
+/// 1. `[writable]` `maker_info`: The maker of the escrow, which receives the reclaimed rent.
+/// 2. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
+/// 3. `[writable]` `recipient_token_acc_a_info`: The recipient's token account for Token A, where the deposit is paid.
+/// 4. `[writable]` `escrow_info`: The escrow state account, closed by the last approval.
+/// 5. `[writable]` `vault_info`: The token vault account holding Token A, closed by the last approval.
+/// 6. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+pub fn approve_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let approver_info = next_account_info(account_iter)?; // Approver's account
+    let maker_info = next_account_info(account_iter)?; // Maker's account (receives rent)
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let recipient_token_acc_a_info = next_account_info(account_iter)?; // Recipient's Token A account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // --- Validation Checks ---
+    msg!("Starting approval validation...");
+
+    // 1. Ensure the approver has signed the transaction.
+    if !approver_info.is_signer {
+        msg!("Error: Approver must be a signer.");
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    // 2. Ensure all writable accounts are actually writable.
+    if !maker_info.is_writable
+        || !recipient_token_acc_a_info.is_writable
+        || !escrow_info.is_writable
+        || !vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 3. Verify the token program owns Token A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 4. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 5. Verify the provided escrow ID matches the one stored in the account.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    // 6. Verify the escrow is an approval escrow that is still waiting to pay its recipient.
+    let Some(recipient) = escrow_account.beneficiary else {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidApprovers.into());
+    };
+    if !escrow_account.requires_approval() || escrow_account.status != EscrowStatus::InProgress {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidApprovers.into());
+    }
+    // 7. Verify the maker account matches the maker recorded in the escrow.
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 8. Verify the mint account matches Token A recorded in the escrow.
+    if escrow_account.token_mint_a != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    // 9. Verify the signer is one of the approvers, and has not approved yet.
+    let Some(approver) = escrow_account
+        .approvers
+        .iter_mut()
+        .find(|approver| approver.key == *approver_info.key)
+    else {
+        msg!("Error: {} is not an approver.", approver_info.key);
+        return Err(EscrowError::Unauthorized.into());
+    };
+    if approver.approved {
+        msg!("Error: {} has already approved.", approver_info.key);
+        return Err(EscrowError::AlreadyApproved.into());
+    }
+    approver.approved = true;
+
+    msg!("Validating recipient's Token A account...");
+    let recipient_token_a_data =
+        unpack_token_account(recipient_token_acc_a_info, token_program_info.key)?;
+
+    // 10. Verify the deposit goes to a Token A account owned by the recipient.
+    if recipient_token_a_data.owner != recipient {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 11. Verify the recipient's Token A account has the correct mint.
+    if recipient_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    msg!("Validating vault...");
+    // 12. Verify the vault account is owned by the token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+
+    // 13. Verify vault has the correct mint (Token A).
+    if vault_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    // 14. Verify the vault account and escrow account are derived correctly.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
+
+    if *escrow_info.key != escrow_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    let approvals = escrow_account.approvals();
+    let is_released = approvals >= escrow_account.approval_threshold as usize;
+    // The approval that reaches the threshold empties the vault so it can be closed.
+    let release_amount = if is_released { vault_data.amount } else { 0 };
+    msg!("All validations passed. Executing approval...");
+
+    EscrowEvent::Approved(EscrowApproved {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        approver: *approver_info.key,
+        escrow_id,
+        approvals: approvals as u8,
+        threshold: escrow_account.approval_threshold,
+        released: release_amount,
+    })
+    .emit();
+    if !is_released {
+        escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        msg!(
+            "Approved by {}: {} of {} approvals.",
+            approver_info.key,
+            approvals,
+            escrow_account.approval_threshold
+        );
+        return Ok(());
+    }
+
+    // --- Release Execution ---
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[vault_bump],
+    ]];
+
+    // STEP 1: Transfer the deposited Token A to the recipient.
+    if release_amount > 0 {
+        msg!("Releasing {} Token A to recipient...", release_amount);
+        let release_instruction = transfer_checked(
+            token_program_info.key,             // The token program ID
+            vault_info.key,                     // SOURCE account (the vault)
+            mint_a_info.key,                    // The mint of Token A
+            recipient_token_acc_a_info.key,     // DESTINATION account (recipient's Token A)
+            &vault_pda,                         // AUTHORITY (the vault PDA)
+            &[&vault_pda],                      // SIGNERS (the vault PDA needs to sign this)
+            release_amount,                     // The amount of tokens to transfer
+            unpack_mint(mint_a_info)?.decimals, // The decimals of Token A
+        )?;
+        invoke_signed(
+            &release_instruction,
+            &[
+                token_program_info.clone(),         // The token program
+                mint_a_info.clone(),                // The mint of Token A
+                recipient_token_acc_a_info.clone(), // Recipient's destination account
+                vault_info.clone(),                 // The vault account (source)
+            ],
+            vault_signer_seeds,
+        )?;
+        msg!("Deposit transferred successfully.");
+    }
+    // STEP 2: Close the vault token account; its rent goes to the maker.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
+    msg!("Closing vault account and reclaiming rent...");
+    let close_acc_instruction = close_account(
+        token_program_info.key, // The token program ID
+        vault_info.key,         // The account to close (vault)
+        maker_info.key,         // The recipient of the rent lamports (maker)
+        &vault_pda,             // The authority that can close the account (vault PDA)
+        &[&vault_pda],          // SIGNERS (vault PDA needs to sign)
+    )?;
+    invoke_signed(
+        &close_acc_instruction,
+        &[
+            token_program_info.clone(), // The token program
+            vault_info.clone(),         // The vault account to close
+            maker_info.clone(),         // The maker (recipient of rent)
+        ],
+        vault_signer_seeds,
+    )?;
+    msg!("Vault closed.");
+
+    // STEP 3: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+
+    msg!(
+        "Escrow: {} of {} approvals reached, released and closed.",
+        approvals,
+        escrow_account.approval_threshold
+    );
+    Ok(())
+}
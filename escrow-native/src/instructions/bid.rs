@@ -0,0 +1,670 @@
+use crate::{
+    error::EscrowError,
+    events::{EscrowEvent, EscrowFilled},
+    instructions::config::{check_not_paused, load_config},
+    instructions::{close_program_account, create_pda_account},
+    state::{Bid, Escrow, EscrowStatus, ReceiveAsset},
+    token::{
+        check_mint_extensions, check_token_program, gross_amount_for_net, harvest_vault_fees,
+        unpack_mint, unpack_token_account, vault_account_len,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program::ID as SYSTEM_PROGRAM,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_token_2022::instruction::{close_account, initialize_account2, transfer_checked};
+
+/// Locks a counter-offer of Token B against an open escrow.
+///
+/// The bidder deposits `bid_amount` of Token B into a bid vault PDA, offering it for all the
+/// Token A the escrow has on offer. `expected_offered` is that amount as the bidder saw it, and
+/// the bid can only be accepted while the escrow still offers exactly that much. Each bidder can
+/// hold one bid per escrow. Bids are only supported on single-pair Token B escrows.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `bidder_info`: The account placing the bid, which pays for the bid accounts.
+/// 1. `[]` `mint_b_info`: The mint of the escrow's Token B.
+/// 2. `[writable]` `bidder_token_b_info`: The bidder's Token B account the bid is paid from.
+/// 3. `[]` `escrow_info`: The escrow state account being bid on.
+/// 4. `[writable]` `bid_info`: The bid PDA, derived from `[b"bid", escrow, bidder]`.
+/// 5. `[writable]` `bid_vault_info`: The bid vault PDA, derived from `[b"bid_vault", escrow, bidder]`.
+/// 6. `[]` `system_program_info`: The Solana System Program account.
+/// 7. `[]` `token_program_b_info`: The token program that owns Token B (SPL Token or Token-2022).
+/// 8. `[]` `rent_sysvar_info`: The Rent Sysvar account.
+/// 9. `[]` `config_info`: The program config PDA, checked for a pause.
+pub fn place_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    bid_amount: u64,
+    expected_offered: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let bidder_info = next_account_info(account_iter)?; // Bidder's wallet account (signer)
+    let mint_b_info = next_account_info(account_iter)?; // Mint for Token B
+    let bidder_token_b_info = next_account_info(account_iter)?; // Bidder's Token B account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let bid_info = next_account_info(account_iter)?; // Bid state account (PDA)
+    let bid_vault_info = next_account_info(account_iter)?; // Bid vault holding Token B (PDA)
+    let system_program_info = next_account_info(account_iter)?; // System Program
+    let token_program_b_info = next_account_info(account_iter)?; // Token program of Token B
+    let rent_sysvar_info = next_account_info(account_iter)?; // Rent Sysvar
+    let config_info = next_account_info(account_iter)?; // Program config PDA
+
+    // --- Validation Checks ---
+    if !bidder_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if !bidder_info.is_writable
+        || !bidder_token_b_info.is_writable
+        || !bid_info.is_writable
+        || !bid_vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    if *system_program_info.key != SYSTEM_PROGRAM {
+        return Err(EscrowError::IncorrectProgramId.into());
+    }
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let escrow_acc = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+    if escrow_acc.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    if *escrow_info.key != escrow_acc.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // No bids can be placed while the admin has paused the program.
+    check_not_paused(load_config(program_id, config_info, escrow_acc.config_bump)?.as_ref())?;
+    if escrow_acc.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_acc.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
+    // A bid settles the whole escrow against a single vault of Token B, releasing Token A at once.
+    if escrow_acc.receive_asset != ReceiveAsset::Token
+        || escrow_acc.is_basket()
+        || escrow_acc.is_vesting()
+        || escrow_acc.is_arbitrated()
+    {
+        msg!("Error: Bids are only supported on single-pair Token B escrows without vesting or an arbiter.");
+        return Err(EscrowError::InvalidBid.into());
+    }
+    if let Some(allowed_taker) = escrow_acc.allowed_taker {
+        if allowed_taker != *bidder_info.key {
+            msg!("Error: Escrow is restricted to taker {}.", allowed_taker);
+            return Err(EscrowError::Unauthorized.into());
+        }
+    }
+    let clock = Clock::get()?;
+    if let Some(expires_at) = escrow_acc.expires_at {
+        if clock.unix_timestamp >= expires_at {
+            msg!("Error: Escrow expired at {}.", expires_at);
+            return Err(EscrowError::EscrowExpired.into());
+        }
+    }
+    if escrow_acc.offered != expected_offered {
+        msg!("Error: Escrow now offers {} Token A.", escrow_acc.offered);
+        return Err(EscrowError::EscrowTermsChanged.into());
+    }
+    if bid_amount == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    if escrow_acc.token_mint_b != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    check_token_program(mint_b_info, token_program_b_info)?;
+    check_mint_extensions(mint_b_info)?;
+    let bidder_token_b_data = unpack_token_account(bidder_token_b_info, token_program_b_info.key)?;
+    if bidder_token_b_data.owner != *bidder_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if bidder_token_b_data.mint != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    if bidder_token_b_data.amount < bid_amount {
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    let (bid_pda, bid_bump) = Bid::find_address(program_id, escrow_info.key, bidder_info.key);
+    if *bid_info.key != bid_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // Lamports alone don't mean a bid exists: anyone can fund the PDA ahead of time.
+    if bid_info.owner == program_id {
+        msg!("Error: Bidder already has a bid on this escrow.");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (bid_vault_pda, bid_vault_bump) =
+        Bid::find_vault_address(program_id, escrow_info.key, bidder_info.key);
+    if *bid_vault_info.key != bid_vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // STEP 1: Create the bid vault, owned by its PDA.
+    msg!("Creating bid vault...");
+    create_pda_account(
+        bidder_info,
+        bid_vault_info,
+        vault_account_len(mint_b_info)?,
+        token_program_b_info.key,
+        system_program_info,
+        &[
+            Bid::VAULT_SEED,
+            escrow_info.key.as_ref(),
+            bidder_info.key.as_ref(),
+            &[bid_vault_bump],
+        ],
+    )?;
+    invoke(
+        &initialize_account2(
+            token_program_b_info.key,
+            bid_vault_info.key,
+            mint_b_info.key,
+            &bid_vault_pda,
+        )?,
+        &[
+            bid_vault_info.clone(),
+            mint_b_info.clone(),
+            rent_sysvar_info.clone(),
+        ],
+    )?;
+
+    // STEP 2: Transfer the bid's Token B into the vault.
+    msg!("Transferring {} Token B to bid vault...", bid_amount);
+    invoke(
+        &transfer_checked(
+            token_program_b_info.key,
+            bidder_token_b_info.key,
+            mint_b_info.key,
+            bid_vault_info.key,
+            bidder_info.key,
+            &[bidder_info.key],
+            bid_amount,
+            unpack_mint(mint_b_info)?.decimals,
+        )?,
+        &[
+            token_program_b_info.clone(),
+            bidder_token_b_info.clone(),
+            mint_b_info.clone(),
+            bid_vault_info.clone(),
+            bidder_info.clone(),
+        ],
+    )?;
+    // Token-2022 transfer fees may be withheld, so record what actually reached the vault.
+    let amount = unpack_token_account(bid_vault_info, token_program_b_info.key)?.amount;
+    if amount == 0 {
+        msg!("Error: Nothing reached the bid vault after transfer fees.");
+        return Err(EscrowError::InvalidAmount.into());
+    }
+
+    // STEP 3: Create the bid state account.
+    create_pda_account(
+        bidder_info,
+        bid_info,
+        Bid::ACCOUNT_LEN,
+        program_id,
+        system_program_info,
+        &[
+            Bid::SEED,
+            escrow_info.key.as_ref(),
+            bidder_info.key.as_ref(),
+            &[bid_bump],
+        ],
+    )?;
+    let bid = Bid {
+        escrow: *escrow_info.key,
+        bidder: *bidder_info.key,
+        token_mint_b: *mint_b_info.key,
+        offered: escrow_acc.offered,
+        amount,
+        bump: bid_bump,
+        vault_bump: bid_vault_bump,
+        created_at: clock.unix_timestamp,
+    };
+    bid.pack_the_slice_data(&mut bid_info.data.borrow_mut())?;
+
+    msg!("Bid placed successfully!");
+    msg!("   - Bid: {} Token B", bid.amount);
+    msg!("   - For: {} Token A", bid.offered);
+    Ok(())
+}
+
+/// Accepts a bid, settling the whole escrow at the bid's price. Callable only by the maker.
+///
+/// The bidder receives everything in the escrow's vault and the maker receives the bid's
+/// Token B, less the protocol fee if one is configured. The escrow, its vault and the bid
+/// accounts are all closed; the bid's rent goes back to the bidder.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `maker_info`: The maker of the escrow.
+/// 1. `[writable]` `bidder_info`: The account that placed the bid. Receives the bid accounts' rent.
+/// 2. `[writable]` `mint_a_info`: The mint of Token A, which receives withheld transfer fees.
+/// 3. `[writable]` `mint_b_info`: The mint of Token B, which receives withheld transfer fees.
+/// 4. `[writable]` `maker_token_b_info`: The maker's Token B account, where the bid is paid.
+/// 5. `[writable]` `bidder_token_a_info`: The bidder's Token A account, where the offer is paid.
+/// 6. `[writable]` `vault_info`: The escrow's Token A vault. This account will be closed.
+/// 7. `[writable]` `escrow_info`: The escrow state account. This account will be closed.
+/// 8. `[writable]` `bid_info`: The bid PDA. This account will be closed.
+/// 9. `[writable]` `bid_vault_info`: The bid vault PDA. This account will be closed.
+/// 10. `[]` `token_program_a_info`: The token program that owns Token A.
+/// 11. `[]` `token_program_b_info`: The token program that owns Token B.
+/// 12. `[]` `config_info`: The program config PDA, which need not exist yet. Also checked for a pause.
+/// 13. `[writable]` `fee_account_info`: The fee recipient's Token B account. Only passed when a protocol fee is charged.
+pub fn accept_bid(program_id: &Pubkey, accounts: &[AccountInfo], escrow_id: u64) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's wallet account (signer)
+    let bidder_info = next_account_info(account_iter)?; // Bidder's wallet account
+    let mint_a_info = next_account_info(account_iter)?; // Mint for Token A
+    let mint_b_info = next_account_info(account_iter)?; // Mint for Token B
+    let maker_token_b_info = next_account_info(account_iter)?; // Maker's Token B account
+    let bidder_token_a_info = next_account_info(account_iter)?; // Bidder's Token A account
+    let vault_info = next_account_info(account_iter)?; // Escrow's Token A vault
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let bid_info = next_account_info(account_iter)?; // Bid state account (PDA)
+    let bid_vault_info = next_account_info(account_iter)?; // Bid vault holding Token B (PDA)
+    let token_program_a_info = next_account_info(account_iter)?; // Token program of Token A
+    let token_program_b_info = next_account_info(account_iter)?; // Token program of Token B
+    let config_info = next_account_info(account_iter)?; // Program config PDA
+
+    // --- Validation Checks ---
+    if !maker_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if !maker_info.is_writable
+        || !bidder_info.is_writable
+        || !maker_token_b_info.is_writable
+        || !bidder_token_a_info.is_writable
+        || !vault_info.is_writable
+        || !escrow_info.is_writable
+        || !bid_info.is_writable
+        || !bid_vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    check_token_program(mint_a_info, token_program_a_info)?;
+    check_token_program(mint_b_info, token_program_b_info)?;
+    if *escrow_info.owner != *program_id || *bid_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let escrow_acc = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+    if escrow_acc.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    if *escrow_info.key != escrow_acc.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // No bid can be accepted while the admin has paused the program.
+    let config = load_config(program_id, config_info, escrow_acc.config_bump)?;
+    check_not_paused(config.as_ref())?;
+    if escrow_acc.maker != *maker_info.key {
+        return Err(EscrowError::Unauthorized.into());
+    }
+    if escrow_acc.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_acc.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
+    if let Some(expires_at) = escrow_acc.expires_at {
+        if Clock::get()?.unix_timestamp >= expires_at {
+            msg!("Error: Escrow expired at {}.", expires_at);
+            return Err(EscrowError::EscrowExpired.into());
+        }
+    }
+    if escrow_acc.token_mint_a != *mint_a_info.key || escrow_acc.token_mint_b != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let bid = Bid::unpack_the_slice_data(&bid_info.data.borrow())?;
+    if bid.escrow != *escrow_info.key || bid.bidder != *bidder_info.key {
+        return Err(EscrowError::InvalidBid.into());
+    }
+    // The bid was priced against the offer at the time; a partial fill or withdrawal since voids it.
+    if bid.offered != escrow_acc.offered {
+        msg!(
+            "Error: Bid is for {} Token A but the escrow now offers {}.",
+            bid.offered,
+            escrow_acc.offered
+        );
+        return Err(EscrowError::EscrowTermsChanged.into());
+    }
+    if *bid_info.key != bid.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let bid_vault_pda = bid.vault_address(program_id)?;
+    let bid_vault_bump = bid.vault_bump;
+    if *bid_vault_info.key != bid_vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let bid_vault_data = unpack_token_account(bid_vault_info, token_program_b_info.key)?;
+    let maker_token_b_data = unpack_token_account(maker_token_b_info, token_program_b_info.key)?;
+    if maker_token_b_data.owner != escrow_acc.token_owner() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if maker_token_b_data.mint != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let bidder_token_a_data = unpack_token_account(bidder_token_a_info, token_program_a_info.key)?;
+    if bidder_token_a_data.owner != *bidder_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if bidder_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_acc.vault_address(program_id)?;
+    let vault_bump = escrow_acc.vault_bump;
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let vault_data = unpack_token_account(vault_info, token_program_a_info.key)?;
+    // The protocol fee comes out of the bid, as it would out of a fill.
+    let fee_amount = match &config {
+        Some(_) => escrow_acc.fee_for(bid.amount)?,
+        None => 0,
+    };
+    let fee_account_info = match (&config, fee_amount) {
+        // Fee recipient's Token B account, only passed if a fee is charged
+        (Some(config), 1..) => Some((config.fee_recipient, next_account_info(account_iter)?)),
+        _ => None,
+    };
+    if let Some((fee_recipient, fee_account_info)) = fee_account_info {
+        let fee_token_b_data = unpack_token_account(fee_account_info, token_program_b_info.key)?;
+        if fee_token_b_data.owner != fee_recipient {
+            return Err(EscrowError::InvalidAccountOwner.into());
+        }
+        if fee_token_b_data.mint != *mint_b_info.key {
+            return Err(EscrowError::InvalidMint.into());
+        }
+    }
+
+    msg!("All validations passed. Accepting bid...");
+    let bid_vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Bid::VAULT_SEED,
+        escrow_info.key.as_ref(),
+        bidder_info.key.as_ref(),
+        &[bid_vault_bump],
+    ]];
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[vault_bump],
+    ]];
+    let mint_b_decimals = unpack_mint(mint_b_info)?.decimals;
+
+    // STEP 1: Pay the protocol fee, then the maker, from the bid vault.
+    // The fee is grossed up for any Token-2022 transfer fee, so the recipient nets `fee_amount`.
+    let fee_payment = gross_amount_for_net(mint_b_info, fee_amount)?;
+    if let Some((_, fee_account_info)) = fee_account_info {
+        msg!(
+            "Transferring {} Token B protocol fee from bid vault...",
+            fee_payment
+        );
+        invoke_signed(
+            &transfer_checked(
+                token_program_b_info.key,
+                bid_vault_info.key,
+                mint_b_info.key,
+                fee_account_info.key,
+                &bid_vault_pda,
+                &[&bid_vault_pda],
+                fee_payment,
+                mint_b_decimals,
+            )?,
+            &[
+                bid_vault_info.clone(),
+                mint_b_info.clone(),
+                fee_account_info.clone(),
+                token_program_b_info.clone(),
+            ],
+            bid_vault_signer_seeds,
+        )?;
+    }
+    // Tokens sent to the bid vault outside the bid go to the maker along with it.
+    let maker_payment = bid_vault_data
+        .amount
+        .checked_sub(fee_payment)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    msg!(
+        "Transferring {} Token B from bid vault to maker...",
+        maker_payment
+    );
+    invoke_signed(
+        &transfer_checked(
+            token_program_b_info.key,
+            bid_vault_info.key,
+            mint_b_info.key,
+            maker_token_b_info.key,
+            &bid_vault_pda,
+            &[&bid_vault_pda],
+            maker_payment,
+            mint_b_decimals,
+        )?,
+        &[
+            bid_vault_info.clone(),
+            mint_b_info.clone(),
+            maker_token_b_info.clone(),
+            token_program_b_info.clone(),
+        ],
+        bid_vault_signer_seeds,
+    )?;
+
+    // STEP 2: Transfer everything in the escrow vault to the bidder and close it.
+    msg!(
+        "Transferring {} Token A from vault to bidder...",
+        vault_data.amount
+    );
+    invoke_signed(
+        &transfer_checked(
+            token_program_a_info.key,
+            vault_info.key,
+            mint_a_info.key,
+            bidder_token_a_info.key,
+            &vault_pda,
+            &[&vault_pda],
+            vault_data.amount,
+            unpack_mint(mint_a_info)?.decimals,
+        )?,
+        &[
+            vault_info.clone(),
+            mint_a_info.clone(),
+            bidder_token_a_info.clone(),
+            token_program_a_info.clone(),
+        ],
+        vault_signer_seeds,
+    )?;
+    harvest_vault_fees(token_program_a_info, mint_a_info, vault_info)?;
+    invoke_signed(
+        &close_account(
+            token_program_a_info.key,
+            vault_info.key,
+            maker_info.key,
+            &vault_pda,
+            &[&vault_pda],
+        )?,
+        &[
+            vault_info.clone(),
+            maker_info.clone(),
+            token_program_a_info.clone(),
+        ],
+        vault_signer_seeds,
+    )?;
+    msg!("Vault closed.");
+
+    // STEP 3: Close the bid accounts, returning their rent to the bidder.
+    close_bid_accounts(
+        bidder_info,
+        mint_b_info,
+        bid_info,
+        bid_vault_info,
+        token_program_b_info,
+        bid_vault_signer_seeds,
+    )?;
+
+    // STEP 4: Close the escrow state account and return its rent to the maker.
+    close_program_account(escrow_info, maker_info)?;
+    msg!("Escrow account closed.");
+    EscrowEvent::Filled(EscrowFilled {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        taker: *bidder_info.key,
+        escrow_id,
+        token_a_released: vault_data.amount,
+        token_b_paid: bid_vault_data.amount,
+        fee_paid: fee_amount,
+        remaining_offered: 0,
+        remaining_receive: 0,
+    })
+    .emit();
+
+    msg!("Bid accepted successfully!");
+    msg!("   - Bidder received: {} Token A", vault_data.amount);
+    msg!("   - Maker received: {} Token B", maker_payment);
+    Ok(())
+}
+
+/// Withdraws a bid, returning its Token B to the bidder. Callable only by the bidder.
+///
+/// This works whether or not the escrow is still open, so a bid is never stranded when
+/// the escrow is filled, cancelled or refunded.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `bidder_info`: The account that placed the bid. Receives the bid accounts' rent.
+/// 1. `[writable]` `mint_b_info`: The mint of Token B, which receives withheld transfer fees.
+/// 2. `[writable]` `bidder_token_b_info`: The bidder's Token B account, where the bid is refunded.
+/// 3. `[writable]` `bid_info`: The bid PDA. This account will be closed.
+/// 4. `[writable]` `bid_vault_info`: The bid vault PDA. This account will be closed.
+/// 5. `[]` `token_program_b_info`: The token program that owns Token B.
+pub fn withdraw_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let bidder_info = next_account_info(account_iter)?; // Bidder's wallet account (signer)
+    let mint_b_info = next_account_info(account_iter)?; // Mint for Token B
+    let bidder_token_b_info = next_account_info(account_iter)?; // Bidder's Token B account
+    let bid_info = next_account_info(account_iter)?; // Bid state account (PDA)
+    let bid_vault_info = next_account_info(account_iter)?; // Bid vault holding Token B (PDA)
+    let token_program_b_info = next_account_info(account_iter)?; // Token program of Token B
+
+    // --- Validation Checks ---
+    if !bidder_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if !bidder_info.is_writable
+        || !bidder_token_b_info.is_writable
+        || !bid_info.is_writable
+        || !bid_vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    check_token_program(mint_b_info, token_program_b_info)?;
+    if *bid_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let bid = Bid::unpack_the_slice_data(&bid_info.data.borrow())?;
+    if bid.bidder != *bidder_info.key {
+        return Err(EscrowError::Unauthorized.into());
+    }
+    if bid.token_mint_b != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    if *bid_info.key != bid.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let bid_vault_pda = bid.vault_address(program_id)?;
+    let bid_vault_bump = bid.vault_bump;
+    if *bid_vault_info.key != bid_vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let bid_vault_data = unpack_token_account(bid_vault_info, token_program_b_info.key)?;
+    let bidder_token_b_data = unpack_token_account(bidder_token_b_info, token_program_b_info.key)?;
+    if bidder_token_b_data.owner != *bidder_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if bidder_token_b_data.mint != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let bid_vault_signer_seeds: &[&[&[u8]]] = &[&[
+        Bid::VAULT_SEED,
+        bid.escrow.as_ref(),
+        bidder_info.key.as_ref(),
+        &[bid_vault_bump],
+    ]];
+
+    // STEP 1: Return everything in the bid vault to the bidder.
+    msg!(
+        "Transferring {} Token B from bid vault to bidder...",
+        bid_vault_data.amount
+    );
+    invoke_signed(
+        &transfer_checked(
+            token_program_b_info.key,
+            bid_vault_info.key,
+            mint_b_info.key,
+            bidder_token_b_info.key,
+            &bid_vault_pda,
+            &[&bid_vault_pda],
+            bid_vault_data.amount,
+            unpack_mint(mint_b_info)?.decimals,
+        )?,
+        &[
+            bid_vault_info.clone(),
+            mint_b_info.clone(),
+            bidder_token_b_info.clone(),
+            token_program_b_info.clone(),
+        ],
+        bid_vault_signer_seeds,
+    )?;
+
+    // STEP 2: Close the bid accounts, returning their rent to the bidder.
+    close_bid_accounts(
+        bidder_info,
+        mint_b_info,
+        bid_info,
+        bid_vault_info,
+        token_program_b_info,
+        bid_vault_signer_seeds,
+    )?;
+    msg!("Bid withdrawn successfully!");
+    Ok(())
+}
+
+/// Closes an emptied bid vault and the bid state account, returning their rent to the bidder.
+fn close_bid_accounts<'a>(
+    bidder_info: &AccountInfo<'a>,
+    mint_b_info: &AccountInfo<'a>,
+    bid_info: &AccountInfo<'a>,
+    bid_vault_info: &AccountInfo<'a>,
+    token_program_b_info: &AccountInfo<'a>,
+    bid_vault_signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_b_info, mint_b_info, bid_vault_info)?;
+    invoke_signed(
+        &close_account(
+            token_program_b_info.key,
+            bid_vault_info.key,
+            bidder_info.key,
+            bid_vault_info.key,
+            &[bid_vault_info.key],
+        )?,
+        &[
+            bid_vault_info.clone(),
+            bidder_info.clone(),
+            token_program_b_info.clone(),
+        ],
+        bid_vault_signer_seeds,
+    )?;
+    close_program_account(bid_info, bidder_info)?;
+    msg!("Bid accounts closed.");
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use crate::{
+    error::EscrowError,
+    events::{EscrowClaimed, EscrowEvent},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, harvest_vault_fees, unpack_mint, unpack_token_account},
+};
+use solana_program::{
+    account_info::next_account_info,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_token_2022::instruction::{close_account, transfer_checked};
+
+/// Releases the Token A of a taken vesting escrow that has unlocked so far.
+///
+/// The escrow's `offered` amount unlocks linearly between the `start` and `end` of its vesting
+/// schedule, as read from the `Clock` sysvar; each claim transfers what has unlocked since the
+/// last one. This instruction is permissionless: the tokens can only go to a Token A account
+/// owned by the escrow's beneficiary. The claim at or after `end` empties the vault, including
+/// anything sent to it outside the escrow, and closes the vault and escrow state accounts,
+/// returning their rent to the maker.
+///
+/// Accounts expected:
+///
+/// 0. `[writable]` `maker_info`: The maker of the escrow, which receives the reclaimed rent.
+/// 1. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
+/// 2. `[writable]` `beneficiary_token_acc_a_info`: The beneficiary's token account for Token A, where the claim is paid.
+/// 3. `[writable]` `escrow_info`: The escrow state account, closed by the final claim.
+/// 4. `[writable]` `vault_info`: The token vault account holding Token A, closed by the final claim.
+/// 5. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+pub fn claim_vested(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account (receives rent)
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let beneficiary_token_acc_a_info = next_account_info(account_iter)?; // Beneficiary's Token A account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // --- Validation Checks ---
+    msg!("Starting vested claim validation...");
+
+    // 1. Ensure all writable accounts are actually writable.
+    if !maker_info.is_writable
+        || !beneficiary_token_acc_a_info.is_writable
+        || !escrow_info.is_writable
+        || !vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 2. Verify the token program owns Token A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 3. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 4. Verify the provided escrow ID matches the one stored in the account.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    // 5. Verify the escrow is a vesting escrow that has been taken.
+    let (Some(vesting), Some(beneficiary)) = (escrow_account.vesting, escrow_account.beneficiary)
+    else {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidVesting.into());
+    };
+    if escrow_account.status != EscrowStatus::Vesting {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidVesting.into());
+    }
+    // 6. Verify the maker account matches the maker recorded in the escrow.
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 7. Verify the mint account matches Token A recorded in the escrow.
+    if escrow_account.token_mint_a != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    msg!("Validating beneficiary's Token A account...");
+    let beneficiary_token_a_data =
+        unpack_token_account(beneficiary_token_acc_a_info, token_program_info.key)?;
+
+    // 8. Verify the claim goes to a Token A account owned by the beneficiary, since the
+    // caller is not necessarily the beneficiary.
+    if beneficiary_token_a_data.owner != beneficiary {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 9. Verify the beneficiary's Token A account has the correct mint.
+    if beneficiary_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    msg!("Validating vault...");
+    // 10. Verify the vault account is owned by the token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+
+    // 11. Verify vault has the correct mint (Token A).
+    if vault_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    // 12. Verify the vault account and escrow account are derived correctly.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
+
+    if *escrow_info.key != escrow_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // 13. Work out what has unlocked since the last claim.
+    let now = Clock::get()?.unix_timestamp;
+    let unlocked = vesting.unlocked(escrow_account.offered, now);
+    let is_final_claim = unlocked == escrow_account.offered;
+    let claimable = unlocked
+        .checked_sub(escrow_account.claimed)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    if claimable == 0 && !is_final_claim {
+        msg!(
+            "Error: Nothing new has unlocked; {} of {} Token A claimed by time {}.",
+            escrow_account.claimed,
+            escrow_account.offered,
+            now
+        );
+        return Err(EscrowError::NothingToClaim.into());
+    }
+    if vault_data.amount < claimable {
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    // The final claim empties the vault so it can be closed.
+    let release_amount = if is_final_claim {
+        vault_data.amount
+    } else {
+        claimable
+    };
+    msg!("All validations passed. Executing vested claim...");
+
+    // --- Claim Execution ---
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[vault_bump],
+    ]];
+
+    // STEP 1: Transfer the unlocked Token A to the beneficiary.
+    if release_amount > 0 {
+        msg!("Releasing {} Token A to beneficiary...", release_amount);
+        let claim_instruction = transfer_checked(
+            token_program_info.key,             // The token program ID
+            vault_info.key,                     // SOURCE account (the vault)
+            mint_a_info.key,                    // The mint of Token A
+            beneficiary_token_acc_a_info.key,   // DESTINATION account (beneficiary's Token A)
+            &vault_pda,                         // AUTHORITY (the vault PDA)
+            &[&vault_pda],                      // SIGNERS (the vault PDA needs to sign this)
+            release_amount,                     // The amount of tokens to transfer
+            unpack_mint(mint_a_info)?.decimals, // The decimals of Token A
+        )?;
+        invoke_signed(
+            &claim_instruction,
+            &[
+                token_program_info.clone(),           // The token program
+                mint_a_info.clone(),                  // The mint of Token A
+                beneficiary_token_acc_a_info.clone(), // Beneficiary's destination account
+                vault_info.clone(),                   // The vault account (source)
+            ],
+            vault_signer_seeds,
+        )?;
+        msg!("Claim transferred successfully.");
+    }
+    escrow_account.claimed = unlocked;
+    let remaining = escrow_account
+        .offered
+        .checked_sub(escrow_account.claimed)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    EscrowEvent::Claimed(EscrowClaimed {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        beneficiary,
+        escrow_id,
+        claimed: release_amount,
+        remaining,
+    })
+    .emit();
+    if !is_final_claim {
+        escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        msg!(
+            "Vested claim completed! {} Token A still vesting until {}.",
+            remaining,
+            vesting.end
+        );
+        return Ok(());
+    }
+    // STEP 2: Close the vault token account; its rent goes to the maker.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
+    msg!("Closing vault account and reclaiming rent...");
+    let close_acc_instruction = close_account(
+        token_program_info.key, // The token program ID
+        vault_info.key,         // The account to close (vault)
+        maker_info.key,         // The recipient of the rent lamports (maker)
+        &vault_pda,             // The authority that can close the account (vault PDA)
+        &[&vault_pda],          // SIGNERS (vault PDA needs to sign)
+    )?;
+    invoke_signed(
+        &close_acc_instruction,
+        &[
+            token_program_info.clone(), // The token program
+            vault_info.clone(),         // The vault account to close
+            maker_info.clone(),         // The maker (recipient of rent)
+        ],
+        vault_signer_seeds,
+    )?;
+    msg!("Vault closed.");
+
+    // STEP 3: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+
+    msg!("Escrow: fully vested and closed.");
+    Ok(())
+}
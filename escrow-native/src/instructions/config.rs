@@ -0,0 +1,228 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program::ID as SYSTEM_PROGRAM,
+};
+
+use crate::{error::EscrowError, instructions::create_pda_account, state::Config};
+
+/// Creates the program-wide config account.
+///
+/// The config PDA can only be created once, by the program's upgrade authority as recorded in
+/// its ProgramData account, who becomes the admin. No one else can front-run it after deployment.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `admin_info`: The program's upgrade authority, who becomes the admin and pays for the config account.
+/// 1. `[writable]` `config_info`: The config PDA, derived from `[b"config"]`.
+/// 2. `[]` `system_program_info`: The Solana System Program account.
+/// 3. `[]` `program_data_info`: The program's ProgramData account, owned by the upgradeable BPF loader.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_bps: u16,
+    fee_recipient: Pubkey,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let admin_info = next_account_info(account_iter)?; // Admin account (pays rent)
+    let config_info = next_account_info(account_iter)?; // Config PDA
+    let system_program_info = next_account_info(account_iter)?; // System Program
+    let program_data_info = next_account_info(account_iter)?; // Program's ProgramData account
+
+    // --- Validation Checks ---
+    if !admin_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if upgrade_authority(program_id, program_data_info)? != Some(*admin_info.key) {
+        msg!("Error: Only the program's upgrade authority can create the config.");
+        return Err(EscrowError::Unauthorized.into());
+    }
+    if !admin_info.is_writable || !config_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    if *system_program_info.key != SYSTEM_PROGRAM {
+        return Err(EscrowError::IncorrectProgramId.into());
+    }
+    if fee_bps > Config::MAX_FEE_BPS {
+        msg!(
+            "Error: Fee of {} bps exceeds {}.",
+            fee_bps,
+            Config::MAX_FEE_BPS
+        );
+        return Err(EscrowError::InvalidFee.into());
+    }
+    let (config_pda, config_bump) = Config::find_address(program_id);
+    if *config_info.key != config_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // Lamports alone don't mean the config exists: anyone can fund the PDA ahead of time.
+    if config_info.owner == program_id {
+        msg!("Error: Config account already exists.");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // --- Create Config Account ---
+    msg!("Creating config account...");
+    create_pda_account(
+        admin_info,
+        config_info,
+        Config::ACCOUNT_LEN,
+        program_id,
+        system_program_info,
+        &[Config::SEED, &[config_bump]],
+    )?;
+
+    let config = Config {
+        admin: *admin_info.key,
+        fee_bps,
+        fee_recipient,
+        bump: config_bump,
+        paused: false,
+    };
+    config.pack_the_slice_data(&mut config_info.data.borrow_mut())?;
+    msg!(
+        "Config initialized: {} bps fee to {}.",
+        config.fee_bps,
+        config.fee_recipient
+    );
+    Ok(())
+}
+
+/// Reads the upgrade authority recorded in the program's ProgramData account.
+///
+/// The account must be the ProgramData PDA of `program_id` under the upgradeable BPF loader.
+/// Its data starts with the loader's `ProgramData` state: a `u32` tag of 3, the slot the program
+/// was last deployed in, and the authority as an `Option<Pubkey>`, `None` once immutable.
+fn upgrade_authority(
+    program_id: &Pubkey,
+    program_data_info: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    const PROGRAM_DATA_TAG: u32 = 3;
+    const AUTHORITY_OFFSET: usize = 4 + 8;
+
+    if *program_data_info.owner != bpf_loader_upgradeable::ID {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let (program_data_pda, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+    if *program_data_info.key != program_data_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    let data = program_data_info.data.borrow();
+    if data.get(..4) != Some(&PROGRAM_DATA_TAG.to_le_bytes()[..]) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    match data.get(AUTHORITY_OFFSET) {
+        Some(0) => Ok(None),
+        Some(1) => data
+            .get(AUTHORITY_OFFSET + 1..AUTHORITY_OFFSET + 33)
+            .and_then(|authority| Pubkey::try_from(authority).ok())
+            .map(Some)
+            .ok_or(ProgramError::InvalidAccountData),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Updates the program-wide config. Callable only by the current admin.
+///
+/// Accounts expected:
+///
+/// 0. `[signer]` `admin_info`: The current admin.
+/// 1. `[writable]` `config_info`: The config PDA.
+pub fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: Option<Pubkey>,
+    fee_bps: Option<u16>,
+    fee_recipient: Option<Pubkey>,
+    paused: Option<bool>,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let admin_info = next_account_info(account_iter)?; // Current admin
+    let config_info = next_account_info(account_iter)?; // Config PDA
+
+    // --- Validation Checks ---
+    if !admin_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if !config_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    if *config_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let mut config = Config::unpack_the_slice_data(&config_info.data.borrow())?;
+    if config.admin != *admin_info.key {
+        return Err(EscrowError::Unauthorized.into());
+    }
+
+    // --- Apply Changes ---
+    if let Some(fee_bps) = fee_bps {
+        if fee_bps > Config::MAX_FEE_BPS {
+            msg!(
+                "Error: Fee of {} bps exceeds {}.",
+                fee_bps,
+                Config::MAX_FEE_BPS
+            );
+            return Err(EscrowError::InvalidFee.into());
+        }
+        config.fee_bps = fee_bps;
+    }
+    if let Some(fee_recipient) = fee_recipient {
+        config.fee_recipient = fee_recipient;
+    }
+    if let Some(new_admin) = new_admin {
+        config.admin = new_admin;
+    }
+    if let Some(paused) = paused {
+        config.paused = paused;
+    }
+    config.pack_the_slice_data(&mut config_info.data.borrow_mut())?;
+    msg!(
+        "Config updated: admin {}, {} bps fee to {}, paused: {}.",
+        config.admin,
+        config.fee_bps,
+        config.fee_recipient,
+        config.paused
+    );
+    Ok(())
+}
+
+/// Loads the program-wide config, if it has been created.
+///
+/// `config_info` must be the config PDA recreated from `config_bump` even when the config
+/// does not exist yet, so callers cannot dodge the config by passing some other account.
+/// Handlers pass the bump stored on the escrow, which avoids a bump search.
+pub fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+    config_bump: u8,
+) -> Result<Option<Config>, ProgramError> {
+    if *config_info.key != Config::address(program_id, config_bump)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *config_info.owner != *program_id {
+        return Ok(None);
+    }
+    Config::unpack_the_slice_data(&config_info.data.borrow()).map(Some)
+}
+
+/// Fails with `ProgramPaused` if the admin has paused the program.
+///
+/// `config` is as read by `load_config`; a config that has not been created yet
+/// means the program cannot have been paused.
+pub fn check_not_paused(config: Option<&Config>) -> ProgramResult {
+    if let Some(config) = config {
+        if config.paused {
+            msg!("Error: Program is paused by the admin.");
+            return Err(EscrowError::ProgramPaused.into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use crate::{
+    basket::{release_basket_vault, ReleaseLegAccounts},
+    error::EscrowError,
+    events::{EscrowEvent, EscrowExpired},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, harvest_vault_fees, unpack_mint, unpack_token_account},
+};
+use solana_program::{
+    account_info::next_account_info,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_token_2022::instruction::{close_account, transfer_checked};
+
+/// Refunds an expired escrow to the maker and closes its accounts.
+///
+/// This instruction is permissionless: anyone may call it once the escrow's
+/// `expires_at` deadline has passed. The tokens can only go back to a Token A
+/// account owned by the maker, and the rent of both PDAs is returned to the maker.
+///
+/// Accounts expected:
+///
+/// 0. `[writable]` `maker_info`: The maker of the escrow, which receives the reclaimed rent.
+/// 1. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
+/// 2. `[writable]` `maker_token_acc_a_info`: The maker's token account for Token A, where funds will be refunded.
+/// 3. `[writable]` `escrow_info`: The escrow state account, which will be closed.
+/// 4. `[writable]` `vault_info`: The token vault account holding Token A, which will be closed.
+/// 5. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+///
+/// Then, for each offered basket leg:
+/// - `[writable]` `mint_info`: The mint of the leg, which receives any withheld transfer fees.
+/// - `[writable]` `vault_info`: The leg's vault PDA, which will be closed.
+/// - `[writable]` `maker_token_info`: The maker's token account for the leg, where it is refunded.
+pub fn refund_expired(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account (receives rent)
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let maker_token_acc_a_info = next_account_info(account_iter)?; // Maker's Token A account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // --- Validation Checks ---
+    msg!("Starting expired escrow refund validation...");
+
+    // 1. Ensure all writable accounts are actually writable.
+    if !maker_info.is_writable
+        || !maker_token_acc_a_info.is_writable
+        || !escrow_info.is_writable
+        || !vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 2. Verify the token program owns Token A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 3. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 4. Verify the provided escrow ID matches the one stored in the account.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    // Verify the escrow is still open.
+    if escrow_account.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
+    // 5. Verify the maker account matches the maker recorded in the escrow.
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 6. Verify the mint account matches Token A recorded in the escrow.
+    if escrow_account.token_mint_a != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    // 7. Verify the escrow has an expiry and that it has passed.
+    let expires_at = escrow_account
+        .expires_at
+        .ok_or(EscrowError::EscrowNotExpired)?;
+    let now = Clock::get()?.unix_timestamp;
+    if now < expires_at {
+        msg!(
+            "Error: Escrow expires at {}, current time is {}.",
+            expires_at,
+            now
+        );
+        return Err(EscrowError::EscrowNotExpired.into());
+    }
+
+    msg!("Validating maker's Token A account...");
+    let maker_token_a_data = unpack_token_account(maker_token_acc_a_info, token_program_info.key)?;
+
+    // 8. Verify the refund goes to a Token A account owned by the maker (or the maker's
+    // multisig), since the caller is not necessarily the maker.
+    if maker_token_a_data.owner != escrow_account.token_owner() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 9. Verify maker's Token A account has the correct mint.
+    if maker_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    msg!("Validating vault...");
+    // 10. Verify the vault account is owned by the token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+
+    // 11. Verify vault has the correct mint (Token A).
+    if vault_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    // 12. Verify the vault account and escrow account are derived correctly.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
+
+    if *escrow_info.key != escrow_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    msg!("All validations passed. Executing expired refund...");
+
+    // --- Refund Execution ---
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[vault_bump],
+    ]];
+
+    // STEP 1: Transfer whatever is left in the vault back to the maker's Token A account.
+    // This is the remaining offer plus anything sent to the vault outside the escrow.
+    let refund_amount = vault_data.amount;
+    if refund_amount > 0 {
+        msg!("Refunding {} Token A to maker...", refund_amount);
+        let refund_instruction = transfer_checked(
+            token_program_info.key,             // The token program ID
+            vault_info.key,                     // SOURCE account (the vault)
+            mint_a_info.key,                    // The mint of Token A
+            maker_token_acc_a_info.key,         // DESTINATION account (maker's Token A account)
+            &vault_pda,                         // AUTHORITY (the vault PDA)
+            &[&vault_pda],                      // SIGNERS (the vault PDA needs to sign this)
+            refund_amount,                      // The amount of tokens to transfer
+            unpack_mint(mint_a_info)?.decimals, // The decimals of Token A
+        )?;
+        invoke_signed(
+            &refund_instruction,
+            &[
+                token_program_info.clone(),     // The token program
+                mint_a_info.clone(),            // The mint of Token A
+                maker_token_acc_a_info.clone(), // Maker's destination account
+                vault_info.clone(),             // The vault account (source)
+            ],
+            vault_signer_seeds,
+        )?;
+        msg!("Refund transferred successfully.");
+    }
+
+    // STEP 2: Close the vault token account; its rent goes to the maker.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
+    msg!("Closing vault account and reclaiming rent...");
+    let close_acc_instruction = close_account(
+        token_program_info.key, // The token program ID
+        vault_info.key,         // The account to close (vault)
+        maker_info.key,         // The recipient of the rent lamports (maker)
+        &vault_pda,             // The authority that can close the account (vault PDA)
+        &[&vault_pda],          // SIGNERS (vault PDA needs to sign)
+    )?;
+    invoke_signed(
+        &close_acc_instruction,
+        &[
+            token_program_info.clone(), // The token program
+            vault_info.clone(),         // The vault account to close
+            maker_info.clone(),         // The maker (recipient of rent)
+        ],
+        vault_signer_seeds,
+    )?;
+    msg!("Vault closed.");
+
+    // STEP 3: Refund the offered legs of a basket and close their vaults.
+    for (index, leg) in escrow_account.basket_offered.iter().enumerate() {
+        let leg_mint_info = next_account_info(account_iter)?;
+        let leg_vault_info = next_account_info(account_iter)?;
+        let maker_leg_info = next_account_info(account_iter)?;
+        if *leg_mint_info.key != leg.mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        release_basket_vault(
+            program_id,
+            &escrow_account,
+            index as u8,
+            &escrow_account.token_owner(),
+            ReleaseLegAccounts {
+                maker_info,
+                mint_info: leg_mint_info,
+                vault_info: leg_vault_info,
+                destination_info: maker_leg_info,
+                token_program_info,
+            },
+        )?;
+    }
+
+    // STEP 4: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+
+    msg!("Escrow refunded and closed.");
+    EscrowEvent::Expired(EscrowExpired {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        escrow_id,
+        refunded: refund_amount,
+    })
+    .emit();
+
+    Ok(())
+}
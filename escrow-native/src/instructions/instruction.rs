@@ -1,29 +1,183 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::program_error::ProgramError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::{OfferAsset, ReceiveAsset, VestingSchedule};
+
+/// The arguments of `EscrowInstruction::InitializeEscrow`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct InitializeEscrowArgs {
+    pub escrow_id: u64,
+    pub deposit_amount: u64,
+    pub receive_amount: u64,
+    pub expires_at: Option<i64>,
+    pub allowed_taker: Option<Pubkey>,
+    pub receive_asset: ReceiveAsset,
+    pub basket_offered: Vec<u64>,
+    pub basket_requested: Vec<u64>,
+    pub operator: Option<Pubkey>,
+    pub offer_asset: OfferAsset,
+    pub vesting: Option<VestingSchedule>,
+    pub arbiter: Option<Pubkey>,
+    pub milestones: Vec<u64>,
+    pub approvers: Vec<Pubkey>,
+    pub approval_threshold: u8,
+}
+
+/// The arguments of `EscrowInstruction::ReleaseFunds`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct ReleaseFundsArgs {
+    pub escrow_id: u64,
+    pub fill_amount: u64,
+    pub expected_offered: u64,
+    pub expected_receive: u64,
+    pub min_token_a_out: u64,
+    pub max_token_b_in: u64,
+}
 
 /// Represents the various instructions that can be sent to the escrow program.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum EscrowInstruction {
-    /// Initializes a new escrow account.
+    /// Initializes a new escrow account from its `InitializeEscrowArgs`.
     ///
     /// The `escrow_id` uniquely identifies the escrow.
-    /// The `amount` specifies the value of the escrow.
+    /// The `deposit_amount` of token A is moved into the vault and put on offer.
+    /// The `receive_amount` is what the maker asks for in return for the whole offer.
+    /// The optional `expires_at` is a unix timestamp after which the escrow can no
+    /// longer be taken and anyone may refund it to the maker.
+    /// The optional `allowed_taker` restricts who may take the escrow.
+    /// The `receive_asset` selects whether `receive_amount` is token B or lamports.
+    /// The `basket_offered` and `basket_requested` amounts turn the escrow into a basket,
+    /// with one extra leg per amount; their mints are passed as trailing accounts.
+    /// The optional `operator` may cancel or reprice the escrow on the maker's behalf.
+    /// The `offer_asset` selects whether token A is fungible or a single NFT.
+    /// The optional `vesting` schedule keeps token A in the vault once the escrow is taken,
+    /// unlocking it to the taker through `ClaimVested`.
+    /// The optional `arbiter` holds the taker's payment in a vault once the escrow is taken,
+    /// until both parties confirm with `ConfirmEscrow` or the arbiter settles it with `Resolve`.
+    /// The `milestones` amounts turn the escrow into a payment to `allowed_taker`, released one
+    /// tranche at a time with `ApproveMilestone`; `receive_amount` must then be 0.
+    /// The `approvers` likewise turn the escrow into a payment to `allowed_taker`, released in
+    /// full once `approval_threshold` of them have signed `Approve`; 0 when there are none.
     /// Accounts expected:
-    InitializeEscrow {
-        escrow_id: u64,
-        deposit_amount: u64,
-        receive_amount: u64,
-    },
+    InitializeEscrow(InitializeEscrowArgs),
     /// Releases funds from an existing escrow account.
     ///
     /// The `escrow_id` identifies the escrow to release funds from.
+    /// The `fill_amount` is the amount of token B the taker pays; the token A
+    /// released is pro-rated against the remaining offer.
+    /// The `expected_offered` and `expected_receive` are the escrow's remaining
+    /// amounts as the taker last saw them; the fill fails if the escrow now gives less
+    /// token A per token B than they imply, but not if another partial fill has only
+    /// shrunk both.
+    /// The fill also fails unless the taker receives at least `min_token_a_out` of
+    /// token A and pays at most `max_token_b_in` of token B, transfer fees included.
     /// Accounts expected:
-    ReleaseFunds { escrow_id: u64 },
+    ReleaseFunds(ReleaseFundsArgs),
     /// Cancels an existing escrow account, returning funds to the initializer.
     ///
     /// The `escrow_id` parameter identifies the escrow to cancel.
     /// Accounts expected:
     CancelEscrow { escrow_id: u64 },
+    /// Refunds an expired escrow to the maker. Callable by anyone once the deadline has passed.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to refund.
+    /// Accounts expected:
+    RefundExpired { escrow_id: u64 },
+    /// Updates the terms of an open escrow. Callable only by the maker.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to update.
+    /// The optional `receive_amount` replaces the amount of token B the maker expects.
+    /// The `deposit_amount` of token A is added to the vault, or the `withdraw_amount`
+    /// taken out of it; at most one of the two may be non-zero.
+    /// Accounts expected:
+    UpdateEscrow {
+        escrow_id: u64,
+        receive_amount: Option<u64>,
+        deposit_amount: u64,
+        withdraw_amount: u64,
+    },
+    /// Creates the program-wide config account; the signer, which must be the program's
+    /// upgrade authority, becomes its admin.
+    ///
+    /// The `fee_bps` is the protocol fee taken from each fill's token B, paid to `fee_recipient`.
+    /// Accounts expected:
+    InitializeConfig { fee_bps: u16, fee_recipient: Pubkey },
+    /// Updates the program-wide config. Callable only by the admin.
+    ///
+    /// Each field that is set replaces the current value. While `paused` is set,
+    /// escrows can no longer be initialized or taken, but can still be cancelled.
+    /// Accounts expected:
+    UpdateConfig {
+        new_admin: Option<Pubkey>,
+        fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+        paused: Option<bool>,
+    },
+    /// Migrates an escrow state account written in an older layout to the current one.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to migrate.
+    /// Accounts expected:
+    MigrateEscrow { escrow_id: u64 },
+    /// Locks a counter-offer of token B against an open escrow.
+    ///
+    /// The `bid_amount` of token B is offered for all of the escrow's remaining token A,
+    /// which the bidder quotes as `expected_offered`.
+    /// Accounts expected:
+    PlaceBid {
+        escrow_id: u64,
+        bid_amount: u64,
+        expected_offered: u64,
+    },
+    /// Accepts a bid, settling the whole escrow at the bid's price. Callable only by the maker.
+    ///
+    /// The `escrow_id` parameter identifies the escrow the bid was placed on.
+    /// Accounts expected:
+    AcceptBid { escrow_id: u64 },
+    /// Withdraws a bid, returning its token B to the bidder. Callable only by the bidder.
+    /// Accounts expected:
+    WithdrawBid,
+    /// Registers, replaces or, with `None`, removes the operator of an open escrow.
+    /// Callable only by the maker.
+    ///
+    /// The `escrow_id` parameter identifies the escrow.
+    /// Accounts expected:
+    SetOperator {
+        escrow_id: u64,
+        operator: Option<Pubkey>,
+    },
+    /// Releases the token A of a taken vesting escrow that has unlocked so far to its
+    /// beneficiary. Callable by anyone.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to claim from.
+    /// Accounts expected:
+    ClaimVested { escrow_id: u64 },
+    /// Confirms a held arbiter escrow for whichever of the maker and taker signs. Once both have
+    /// confirmed, the trade completes: token A goes to the taker and the payment to the maker.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to confirm.
+    /// Accounts expected:
+    ConfirmEscrow { escrow_id: u64 },
+    /// Settles a held arbiter escrow by splitting both vaults. Callable only by the arbiter.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to settle. The `release_bps` is the share
+    /// of the trade, in basis points, that goes ahead: the taker receives that share of token A
+    /// and the maker that share of the payment, and the rest of each vault goes back to the
+    /// party that funded it.
+    /// Accounts expected:
+    Resolve { escrow_id: u64, release_bps: u16 },
+    /// Approves a milestone of a milestone escrow, releasing its tranche of token A to the
+    /// recipient. Callable only by the maker.
+    ///
+    /// The `escrow_id` parameter identifies the escrow, and `milestone` the index of the
+    /// milestone to approve.
+    /// Accounts expected:
+    ApproveMilestone { escrow_id: u64, milestone: u8 },
+    /// Records the signing approver's approval of an approval escrow. The approval that reaches
+    /// the escrow's threshold releases token A to the recipient and closes the escrow.
+    ///
+    /// The `escrow_id` parameter identifies the escrow to approve.
+    /// Accounts expected:
+    Approve { escrow_id: u64 },
 }
 impl EscrowInstruction {
     /// Deserializes an `Escrow` struct from a byte slice.
@@ -33,4 +187,8 @@ impl EscrowInstruction {
     pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
         Self::try_from_slice(data).map_err(|_| ProgramError::InvalidAccountData)
     }
+    /// Serializes the instruction into the byte layout expected by `unpack`.
+    pub fn pack(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("EscrowInstruction serialization cannot fail")
+    }
 }
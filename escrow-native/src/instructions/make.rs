@@ -3,20 +3,30 @@ use solana_program::{
     entrypoint::ProgramResult,
     msg,
     program::{invoke, invoke_signed},
-    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction::create_account,
-    sysvar::Sysvar,
+    sysvar::{clock::Clock, Sysvar},
 };
-use spl_token::{
-    instruction::{initialize_account2, transfer},
-    state::Account, // The public key of the SPL Token program
-    ID as TOKEN_PROGRAM_ID,
+use spl_token::native_mint;
+use spl_token_2022::instruction::initialize_account2;
+
+use crate::{
+    basket::{deposit_basket_leg, DepositLegAccounts}, // Basket leg deposits
+    error::EscrowError,                               // Custom error
+    events::{EscrowCreated, EscrowEvent},             // Structured events
+    instructions::config::{check_not_paused, load_config}, // Program config
+    instructions::instruction::InitializeEscrowArgs,  // Instruction arguments
+    state::{
+        Approver, BasketLeg, Config, Escrow, EscrowStatus, Milestone, OfferAsset, OfferedLeg,
+        ReceiveAsset,
+    }, // Escrow state structure
+    token::{
+        check_mint_extensions, check_token_program, is_token_program, unpack_mint,
+        unpack_token_account, vault_account_len, TokenAuthority,
+    },
 };
 
-use crate::{error::EscrowError, state::Escrow}; // Custom error and escrow state structure
-
 /// Initializes a new escrow transaction.
 ///
 /// This function sets up a new escrow by:
@@ -26,6 +36,42 @@ use crate::{error::EscrowError, state::Escrow}; // Custom error and escrow state
 /// 4. Transferring the maker's (depositor's) tokens into this vault.
 /// 5. Creating and populating the escrow state account with transaction details.
 ///
+/// Token A and Token B may each be owned by either the SPL Token or the Token-2022 program.
+/// If Token A charges a transfer fee, the escrow records the amount that actually reached the vault.
+/// With `ReceiveAsset::NativeSol` the maker asks for lamports instead of token B; mint B must then
+/// be the native mint and the maker's wallet is passed in place of their Token B account.
+///
+/// A basket escrow deposits `basket_offered` amounts of further mints next to Token A, each in a
+/// vault derived from `[b"basket_vault", maker, escrow_id, index]`, and asks for `basket_requested`
+/// amounts of further mints next to Token B. Offered legs must share Token A's token program and
+/// requested legs Token B's, so baskets cannot receive native SOL. The legs' accounts follow the
+/// fixed accounts below.
+///
+/// With `OfferAsset::Nft` the escrow sells a single NFT: mint A must have no decimals and a supply
+/// of one, and the maker deposits exactly that one token. Takers must then buy it in a single fill.
+///
+/// With a `vesting` schedule the escrow sells token A locked: the taker pays in a single fill, but
+/// token A stays in the vault and unlocks linearly from `start` to `end`, to be released with
+/// `ClaimVested`. Basket legs cannot vest, so a vesting escrow has none.
+///
+/// With an `arbiter` the escrow settles in two stages: the taker's payment is held in a second
+/// vault, derived from `[b"payment_vault", maker, escrow_id]`, next to token A, until both parties
+/// confirm or the arbiter splits the two vaults. Only single-pair token B escrows sold in full can
+/// have an arbiter, so an arbiter escrow cannot vest or receive SOL, and the arbiter cannot be the maker.
+///
+/// With `milestones` the escrow pays rather than trades: it asks for nothing in return, and is never
+/// taken. The deposit is split into one tranche per milestone, each released to the `allowed_taker`
+/// when the maker approves it with `ApproveMilestone`. Once `expires_at` has passed, the maker can
+/// cancel the escrow to get back the tranches still unapproved.
+///
+/// With `approvers` the escrow also pays the `allowed_taker` rather than trading, but in one go:
+/// the deposit is released once `approval_threshold` of the approvers have each signed `Approve`.
+/// The maker can cancel the escrow until then, or only once `expires_at` has passed if it is set.
+///
+/// The maker's token accounts may belong to an SPL multisig the maker is a member of, such as a
+/// treasury. The maker still signs and pays rent, the multisig's members sign the deposits, and
+/// the escrow records the multisig so every refund and payment goes to its accounts.
+///
 /// Accounts expected:
 /// 0. `[signer]`       maker_info:        The account of the person initializing the escrow. Must be a signer.
 /// 1. `[]`             mint_a_info:       The mint account of token A (the token being deposited).
@@ -33,17 +79,47 @@ use crate::{error::EscrowError, state::Escrow}; // Custom error and escrow state
 /// 3. `[writable]`     maker_token_acc_a_info: The maker's SPL Token account holding token A.
 /// 4. `[writable]`     deposit_vault_info: The PDA token account where token A will be deposited.
 /// 5. `[writable]`     escrow_info:       The PDA account to store the escrow state.
-/// 6. `[]`             token_to_receive_account: The maker's SPL Token account for token B, where they expect to receive tokens.
+/// 6. `[]`             token_to_receive_account: The maker's token account for token B, or the maker's wallet when receiving SOL.
 /// 7. `[]`             system_program_info: The Solana System Program account.
-/// 8. `[]`             token_program_info: The SPL Token Program account.
+/// 8. `[]`             token_program_info: The token program that owns mint A (SPL Token or Token-2022).
 /// 9. `[]`             rent_sysvar_info:  The Rent Sysvar account.
+/// 10. `[]`            config_info:       The program config PDA, checked for a pause. Its fee is recorded on the escrow.
+///
+/// Then, for each offered basket leg:
+/// - `[]`           mint_info:          The mint of the leg.
+/// - `[writable]`   maker_token_info:   The maker's token account the leg is deposited from.
+/// - `[writable]`   vault_info:         The leg's vault PDA, created here.
+///
+/// Then, for each requested basket leg:
+/// - `[]`           mint_info:          The mint of the leg.
+/// - `[]`           maker_token_info:   The maker's token account the leg will be paid to.
+///
+/// Then, only if the maker's token accounts belong to an SPL multisig:
+/// - `[]`           multisig_info:      The multisig account, owned by Token A's token program.
+/// - `[signer]`     signer_info:        At least `m` of the multisig's members, one account each.
 pub fn initialize_escrow(
-    program_id: &Pubkey,      // The public key of this escrow program.
-    accounts: &[AccountInfo], // All accounts required for the transaction.
-    escrow_id: u64,           // A unique identifier for this specific escrow.
-    deposit_amount: u64,      // The amount of token A the maker is depositing.
-    receive_amount: u64,      // The amount of token B the maker expects to receive.
+    program_id: &Pubkey,        // The public key of this escrow program.
+    accounts: &[AccountInfo],   // All accounts required for the transaction.
+    args: InitializeEscrowArgs, // The escrow's terms, as sent in the instruction.
 ) -> ProgramResult {
+    let InitializeEscrowArgs {
+        escrow_id,          // A unique identifier for this specific escrow.
+        deposit_amount,     // The amount of token A the maker is depositing.
+        receive_amount,     // The amount of token B the maker expects to receive.
+        expires_at,         // Optional unix timestamp after which the escrow expires.
+        allowed_taker,      // Optional taker the escrow is restricted to.
+        receive_asset,      // Whether the maker receives token B or native SOL.
+        basket_offered,     // Amounts of the extra mints the maker is depositing.
+        basket_requested,   // Amounts of the extra mints the maker wants in return.
+        operator,           // Optional key that may cancel or reprice for the maker.
+        offer_asset,        // Whether token A is fungible or a single NFT.
+        vesting,            // Optional schedule on which token A unlocks to the taker.
+        arbiter,            // Optional key that may settle a disputed trade.
+        milestones,         // Amounts of token A released one approval at a time.
+        approvers,          // Keys whose approvals release token A to the recipient.
+        approval_threshold, // How many of the approvers must approve.
+    } = args;
+
     // Iterate through the accounts to parse them.
     let account_iter = &mut accounts.iter();
 
@@ -63,10 +139,18 @@ pub fn initialize_escrow(
     let token_to_receive_account = next_account_info(account_iter)?;
     // 7. The Solana System Program, required for creating new accounts.
     let system_program_info = next_account_info(account_iter)?;
-    // 8. The SPL Token Program, required for token operations.
+    // 8. The token program that owns Token A, required for token operations.
     let token_program_info = next_account_info(account_iter)?;
     // 9. The Rent Sysvar, required for calculating rent exemption.
     let rent_sysvar_info = next_account_info(account_iter)?;
+    // 10. The program config PDA, checked for a pause.
+    let config_info = next_account_info(account_iter)?;
+
+    // Nothing new can be created while the admin has paused the program. The config's bump
+    // is found once here and stored on the escrow for every later handler.
+    let (_config_pda, config_bump) = Config::find_address(program_id);
+    let config = load_config(program_id, config_info, config_bump)?;
+    check_not_paused(config.as_ref())?;
 
     // --- Validation Checks ---
 
@@ -83,32 +167,202 @@ pub fn initialize_escrow(
         msg!("Error: Maker's Token A account, Deposit Vault, and Escrow State account must be writable.");
         return Err(EscrowError::InvalidAccountData.into());
     }
-    // 3. Verify that the provided token program account matches the known SPL Token Program ID.
-    if token_program_info.key != &TOKEN_PROGRAM_ID {
+    // 3. Verify that the provided token program is a supported token program and owns mint A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 4. Verify the asset the maker wants in return.
+    match receive_asset {
+        // Native SOL is paid straight to the maker's wallet, marked by the native mint.
+        ReceiveAsset::NativeSol => {
+            if *mint_b_info.key != native_mint::ID || token_to_receive_account.key != maker_info.key
+            {
+                msg!("Error: A native SOL escrow expects the native mint and the maker's wallet.");
+                return Err(EscrowError::InvalidMint.into());
+            }
+        }
+        // Mint B and the maker's Token B account must be owned by the same token program.
+        ReceiveAsset::Token => {
+            if !is_token_program(mint_b_info.owner)
+                || token_to_receive_account.owner != mint_b_info.owner
+            {
+                msg!(
+                    "Error: Maker's Token B account must be owned by the token program of mint B."
+                );
+                return Err(EscrowError::IncorrectProgramId.into());
+            }
+            check_mint_extensions(mint_b_info)?;
+        }
+    }
+    check_mint_extensions(mint_a_info)?;
+    // 5. Ensure that the deposit and receive amounts are positive. Milestone and approval escrows
+    // ask for nothing in return, see below.
+    let pays_recipient = !milestones.is_empty() || !approvers.is_empty();
+    if deposit_amount == 0 || (receive_amount == 0 && !pays_recipient) {
+        msg!("ERROR: Amounts must be greater than 0.");
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    // 6. An NFT escrow sells the one token of a mint with no decimals and a supply of one.
+    if offer_asset == OfferAsset::Nft {
+        let mint_a_data = unpack_mint(mint_a_info)?;
+        if mint_a_data.decimals != 0 || mint_a_data.supply != 1 || deposit_amount != 1 {
+            msg!(
+                "Error: An NFT needs 0 decimals, a supply of 1 and a deposit of 1, got {}, {} and {}.",
+                mint_a_data.decimals,
+                mint_a_data.supply,
+                deposit_amount
+            );
+            return Err(EscrowError::InvalidNft.into());
+        }
+    }
+    // 7. Ensure the expiry, if any, is in the future.
+    let clock = Clock::get()?;
+    if let Some(expires_at) = expires_at {
+        if expires_at <= clock.unix_timestamp {
+            msg!(
+                "Error: Expiry {} is not after the current time {}.",
+                expires_at,
+                clock.unix_timestamp
+            );
+            return Err(EscrowError::InvalidExpiry.into());
+        }
+    }
+    // 8. Verify the basket legs, if any.
+    if basket_offered.len() > Escrow::MAX_BASKET_LEGS
+        || basket_requested.len() > Escrow::MAX_BASKET_LEGS
+    {
         msg!(
-            "Error: Token program ID mismatch. Expected {}, got {}.",
-            TOKEN_PROGRAM_ID,
-            token_program_info.key
+            "Error: A basket can have at most {} extra legs on each side.",
+            Escrow::MAX_BASKET_LEGS
         );
-        return Err(EscrowError::IncorrectProgramId.into());
+        return Err(EscrowError::InvalidBasket.into());
     }
-    // 4. Verify that the maker's token_to_receive_account (Token B account) is owned by the SPL Token Program.
-    if *token_to_receive_account.owner != TOKEN_PROGRAM_ID {
-        msg!("Error: Maker's Token B account must be owned by the SPL Token Program.");
-        return Err(EscrowError::IncorrectProgramId.into());
+    if !basket_requested.is_empty() && receive_asset == ReceiveAsset::NativeSol {
+        msg!("Error: A native SOL escrow cannot request basket legs.");
+        return Err(EscrowError::InvalidBasket.into());
     }
-    // 5. Ensure that the deposit and receive amounts are positive.
-    if deposit_amount == 0 || receive_amount == 0 {
-        msg!("ERROR: Amounts must be greater than 0.");
+    if basket_offered.contains(&0) || basket_requested.contains(&0) {
+        msg!("ERROR: Basket amounts must be greater than 0.");
         return Err(EscrowError::InvalidAmount.into());
     }
+    // 9. A vesting schedule must end after it starts, and only token A can vest.
+    if let Some(vesting) = vesting {
+        if vesting.end <= vesting.start {
+            msg!(
+                "Error: Vesting end {} is not after its start {}.",
+                vesting.end,
+                vesting.start
+            );
+            return Err(EscrowError::InvalidVesting.into());
+        }
+        if !basket_offered.is_empty() || !basket_requested.is_empty() {
+            msg!("Error: A vesting escrow cannot have basket legs.");
+            return Err(EscrowError::InvalidVesting.into());
+        }
+    }
+    // 10. An arbiter holds a single token B payment, and must be neutral.
+    if let Some(arbiter) = arbiter {
+        if receive_asset != ReceiveAsset::Token
+            || vesting.is_some()
+            || !basket_offered.is_empty()
+            || !basket_requested.is_empty()
+        {
+            msg!(
+                "Error: An arbiter escrow must be a single-pair token B escrow that does not vest."
+            );
+            return Err(EscrowError::InvalidArbiter.into());
+        }
+        if arbiter == *maker_info.key {
+            msg!("Error: The maker cannot arbitrate their own escrow.");
+            return Err(EscrowError::InvalidArbiter.into());
+        }
+    }
+    // 11. Milestones split the whole deposit into tranches for a named recipient, and need a
+    // deadline after which the unapproved ones can be refunded.
+    if !milestones.is_empty() {
+        if milestones.len() > Escrow::MAX_MILESTONES || milestones.contains(&0) {
+            msg!(
+                "Error: A milestone escrow needs at most {} milestones, each greater than 0.",
+                Escrow::MAX_MILESTONES
+            );
+            return Err(EscrowError::InvalidMilestone.into());
+        }
+        let milestones_total = milestones
+            .iter()
+            .try_fold(0u64, |total, amount| total.checked_add(*amount))
+            .ok_or(EscrowError::ArithmeticOverflow)?;
+        if milestones_total != deposit_amount {
+            msg!(
+                "Error: Milestones add up to {} Token A, but {} is deposited.",
+                milestones_total,
+                deposit_amount
+            );
+            return Err(EscrowError::InvalidMilestone.into());
+        }
+        if allowed_taker.is_none() || expires_at.is_none() || receive_amount != 0 {
+            msg!("Error: A milestone escrow needs a recipient and a deadline, and asks for nothing in return.");
+            return Err(EscrowError::InvalidMilestone.into());
+        }
+        if vesting.is_some()
+            || arbiter.is_some()
+            || !basket_offered.is_empty()
+            || !basket_requested.is_empty()
+        {
+            msg!("Error: A milestone escrow cannot vest, have an arbiter or basket legs.");
+            return Err(EscrowError::InvalidMilestone.into());
+        }
+    }
+    // 12. Approvers release the whole deposit to a named recipient once enough of them, all
+    // distinct, have approved.
+    if !approvers.is_empty() {
+        if approvers.len() > Escrow::MAX_APPROVERS
+            || approval_threshold == 0
+            || approval_threshold as usize > approvers.len()
+        {
+            msg!(
+                "Error: An approval escrow needs at most {} approvers, and between 1 and all of them to approve.",
+                Escrow::MAX_APPROVERS
+            );
+            return Err(EscrowError::InvalidApprovers.into());
+        }
+        if approvers
+            .iter()
+            .enumerate()
+            .any(|(index, approver)| approvers[..index].contains(approver))
+        {
+            msg!("Error: Approvers must be distinct.");
+            return Err(EscrowError::InvalidApprovers.into());
+        }
+        if allowed_taker.is_none() || receive_amount != 0 {
+            msg!("Error: An approval escrow needs a recipient, and asks for nothing in return.");
+            return Err(EscrowError::InvalidApprovers.into());
+        }
+        if !milestones.is_empty()
+            || vesting.is_some()
+            || arbiter.is_some()
+            || !basket_offered.is_empty()
+            || !basket_requested.is_empty()
+        {
+            msg!("Error: An approval escrow cannot have milestones, vest, have an arbiter or basket legs.");
+            return Err(EscrowError::InvalidApprovers.into());
+        }
+    } else if approval_threshold != 0 {
+        msg!("Error: An approval threshold needs approvers.");
+        return Err(EscrowError::InvalidApprovers.into());
+    }
+    // 13. Resolve who owns the maker's tokens: the maker, or an SPL multisig the maker belongs
+    // to, whose account and signing members follow the basket legs.
+    let multisig_accounts = account_iter
+        .as_slice()
+        .get(basket_offered.len() * 3 + basket_requested.len() * 2..)
+        .unwrap_or_default();
+    let maker_authority =
+        TokenAuthority::resolve(maker_info, multisig_accounts, token_program_info.key)?;
 
     msg!("Validating maker's Token A account...");
     // Unpack the data of the maker's Token A account to access its state.
-    let maker_token_a_data = Account::unpack(&maker_token_acc_a_info.data.borrow())?;
+    let maker_token_a_data = unpack_token_account(maker_token_acc_a_info, token_program_info.key)?;
 
-    // Check: Is this Token A account actually owned by the maker?
-    if maker_token_a_data.owner != *maker_info.key {
+    // Check: Is this Token A account actually owned by the maker (or the maker's multisig)?
+    if maker_token_a_data.owner != *maker_authority.key() {
         msg!("Error: Maker's Token A account is not owned by the maker.");
         return Err(EscrowError::InvalidAccountOwner.into());
     }
@@ -132,24 +386,27 @@ pub fn initialize_escrow(
     msg!("Maker's Token A account validated.");
 
     // Validate token_to_receive_account (Maker's Token B account)
-    msg!("Validating maker's Token B account...");
-    // Unpack the data of the maker's Token B account.
-    let token_to_receive_data = Account::unpack(&token_to_receive_account.data.borrow())?;
-
-    // Check: Is this Token B account actually owned by the maker?
-    if token_to_receive_data.owner != *maker_info.key {
-        msg!("Error: Maker's Token B account is not owned by the maker.");
-        return Err(EscrowError::InvalidAccountOwner.into());
+    if receive_asset == ReceiveAsset::Token {
+        msg!("Validating maker's Token B account...");
+        // Unpack the data of the maker's Token B account.
+        let token_to_receive_data =
+            unpack_token_account(token_to_receive_account, mint_b_info.owner)?;
+
+        // Check: Is this Token B account actually owned by the maker (or the maker's multisig)?
+        if token_to_receive_data.owner != *maker_authority.key() {
+            msg!("Error: Maker's Token B account is not owned by the maker.");
+            return Err(EscrowError::InvalidAccountOwner.into());
+        }
+
+        // Check: Is this Token B account for the correct mint (mint_b)?
+        if token_to_receive_data.mint != *mint_b_info.key {
+            msg!("Error: Maker's Token B account mint does not match expected Token B mint.");
+            return Err(EscrowError::InvalidMint.into());
+        }
+
+        msg!("Maker's Token B account validated.");
     }
 
-    // Check: Is this Token B account for the correct mint (mint_b)?
-    if token_to_receive_data.mint != *mint_b_info.key {
-        msg!("Error: Maker's Token B account mint does not match expected Token B mint.");
-        return Err(EscrowError::InvalidMint.into());
-    }
-
-    msg!("Maker's Token B account validated.");
-
     // Convert the escrow ID into bytes for use as a seed.
     let escrow_seed = escrow_id.to_le_bytes();
 
@@ -180,6 +437,12 @@ pub fn initialize_escrow(
         msg!("Error: Escrow state PDA derivation mismatch.");
         return Err(EscrowError::PDADerivationMismatch.into());
     }
+    // Never overwrite an existing escrow: once taken it may still hold the taker's tokens,
+    // a held payment or tranches owed to a recipient.
+    if escrow_info.owner == program_id {
+        msg!("Error: Escrow {} already exists.", escrow_id);
+        return Err(EscrowError::EscrowAlreadyExists.into());
+    }
 
     // --- Account Creation and Initialization ---
 
@@ -191,8 +454,9 @@ pub fn initialize_escrow(
     if deposit_vault_info.data_is_empty() {
         msg!("Creating vault token account...");
 
-        // Calculate the space required for an SPL Token Account.
-        let space = spl_token::state::Account::LEN;
+        // Calculate the space required for a token account of mint A, including any
+        // account extensions a Token-2022 mint requires (e.g. for transfer fees).
+        let space = vault_account_len(mint_a_info)?;
         // Calculate the minimum lamports required for rent exemption for the vault.
         let rent_lamports = rent.minimum_balance(space);
 
@@ -201,13 +465,13 @@ pub fn initialize_escrow(
         // The `deposit_vault_info.key` is the address of the new account.
         // `rent_lamports` is the amount of SOL for rent exemption.
         // `space` is the size of the account data.
-        // `token_program_info.key` is the owner of the new account (SPL Token Program).
+        // `token_program_info.key` is the owner of the new account (the token program of mint A).
         let create_vault_instruction = create_account(
-            &maker_info.key,
-            &deposit_vault_info.key,
+            maker_info.key,
+            deposit_vault_info.key,
             rent_lamports,
             space as u64,
-            &token_program_info.key,
+            token_program_info.key,
         );
 
         // Signer seeds for the vault PDA to sign the `create_account` instruction.
@@ -237,14 +501,14 @@ pub fn initialize_escrow(
         msg!("Initializing vault as token account...");
 
         // Instruction to initialize the token account.
-        // `TOKEN_PROGRAM_ID`: The program that owns the vault account.
+        // `token_program_info.key`: The program that owns the vault account.
         // `deposit_vault_info.key`: The token account to initialize.
         // `mint_a_info.key`: The mint for this token account (Token A).
         // `vault_pda`: The authority for this token account. This PDA will control tokens in the vault.
         let vault_token_instruction = initialize_account2(
-            &TOKEN_PROGRAM_ID,
-            &deposit_vault_info.key,
-            &mint_a_info.key,
+            token_program_info.key,
+            deposit_vault_info.key,
+            mint_a_info.key,
             &vault_pda, // The vault PDA will be the authority for this token account
         )?;
 
@@ -264,93 +528,200 @@ pub fn initialize_escrow(
 
     // STEP 5: Transfer the maker's Token A from their account into the newly created vault.
     msg!("Transferring {} tokens to vault...", deposit_amount);
-
-    // Create the transfer instruction.
-    // `TOKEN_PROGRAM_ID`: The token program.
-    // `maker_token_acc_a_info.key`: The source token account (maker's Token A account).
-    // `deposit_vault_info.key`: The destination token account (the vault).
-    // `maker_info.key`: The authority (owner) of the source token account.
-    // `&[maker_info.key]`: The signers required for this transfer (the maker).
-    // `deposit_amount`: The amount of tokens to transfer.
-    let transfer_instruction = transfer(
-        &TOKEN_PROGRAM_ID,
-        &maker_token_acc_a_info.key,
-        &deposit_vault_info.key,
-        &maker_info.key,
-        &[maker_info.key],
+    let vault_balance_before =
+        unpack_token_account(deposit_vault_info, token_program_info.key)?.amount;
+    let mint_a_decimals = unpack_mint(mint_a_info)?.decimals;
+
+    // Invoke the token program to execute the transfer, signed by the maker or by the
+    // members of the maker's multisig.
+    maker_authority.transfer_checked(
+        token_program_info,     // The token program of mint A
+        maker_token_acc_a_info, // Maker's source Token A account
+        mint_a_info,            // The mint of Token A
+        deposit_vault_info,     // Destination vault account
         deposit_amount,
+        mint_a_decimals,
     )?;
 
-    // Invoke the SPL Token Program to execute the transfer.
-    invoke(
-        &transfer_instruction,
+    // A Token-2022 transfer fee is withheld from the deposit, so only what reached the vault is on offer.
+    let offered = unpack_token_account(deposit_vault_info, token_program_info.key)?
+        .amount
+        .checked_sub(vault_balance_before)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    if offered == 0 {
+        msg!("Error: No Token A reached the vault after transfer fees.");
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    msg!("Tokens transferred to vault. {} Token A on offer.", offered);
+
+    // STEP 6: Deposit the offered legs of a basket and record the requested ones.
+    // Every mint may appear only once on each side of the trade.
+    let mut basket_offered_legs: Vec<OfferedLeg> = Vec::with_capacity(basket_offered.len());
+    for (index, deposit_amount) in basket_offered.iter().enumerate() {
+        let mint_info = next_account_info(account_iter)?;
+        let maker_token_info = next_account_info(account_iter)?;
+        let vault_info = next_account_info(account_iter)?;
+        if mint_info.key == mint_a_info.key
+            || basket_offered_legs
+                .iter()
+                .any(|leg| leg.mint == *mint_info.key)
+        {
+            msg!("Error: Basket mint {} is offered twice.", mint_info.key);
+            return Err(EscrowError::InvalidBasket.into());
+        }
+        let leg = deposit_basket_leg(
+            program_id,
+            escrow_id,
+            index as u8,
+            *deposit_amount,
+            DepositLegAccounts {
+                maker_info,
+                maker_authority: &maker_authority,
+                mint_info,
+                maker_token_info,
+                vault_info,
+                system_program_info,
+                token_program_info,
+                rent_sysvar_info,
+            },
+        )?;
+        basket_offered_legs.push(leg);
+    }
+    let mut basket_requested_legs: Vec<BasketLeg> = Vec::with_capacity(basket_requested.len());
+    for receive_amount in &basket_requested {
+        let mint_info = next_account_info(account_iter)?;
+        let maker_token_info = next_account_info(account_iter)?;
+        if mint_info.key == mint_b_info.key
+            || basket_requested_legs
+                .iter()
+                .any(|leg| leg.mint == *mint_info.key)
+        {
+            msg!("Error: Basket mint {} is requested twice.", mint_info.key);
+            return Err(EscrowError::InvalidBasket.into());
+        }
+        // Requested legs are paid with Token B's token program.
+        if mint_info.owner != mint_b_info.owner {
+            return Err(EscrowError::IncorrectProgramId.into());
+        }
+        check_mint_extensions(mint_info)?;
+        let maker_token_data = unpack_token_account(maker_token_info, mint_info.owner)?;
+        if maker_token_data.owner != *maker_authority.key() {
+            return Err(EscrowError::InvalidAccountOwner.into());
+        }
+        if maker_token_data.mint != *mint_info.key {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        basket_requested_legs.push(BasketLeg {
+            mint: *mint_info.key,
+            amount: *receive_amount,
+        });
+    }
+
+    // STEP 7: Create the escrow state account.
+    // This account will store the details of the escrow.
+    msg!("Creating escrow state account...");
+
+    // Calculate the space required for the Escrow state struct, its basket legs, milestones
+    // and approvers.
+    let space = Escrow::account_len(
+        basket_offered_legs.len(),
+        basket_requested_legs.len(),
+        milestones.len(),
+        approvers.len(),
+    );
+    // Calculate the minimum lamports required for rent exemption for the escrow state.
+    let rent_lamports = rent.minimum_balance(space);
+
+    // Instruction to create a new account for the escrow state.
+    // `maker_info.key` pays for the rent.
+    // `escrow_info.key` is the address of the new account (escrow PDA).
+    // `rent_lamports` is the amount of SOL for rent exemption.
+    // `space` is the size of the account data.
+    // `program_id` is the owner of the new account (this escrow program).
+    let escrow_instruction = create_account(
+        maker_info.key,
+        escrow_info.key,
+        rent_lamports,
+        space as u64,
+        program_id, // The escrow program owns this account
+    );
+
+    // Signer seeds for the escrow PDA to sign the `create_account` instruction.
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"escrow_vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[escrow_bump], // The bump seed for the escrow PDA.
+    ]];
+
+    // Invoke the System Program to create the escrow state account.
+    // `invoke_signed` is used because the `escrow_info` (which is a PDA)
+    // is effectively signing its own creation as its `owner` is set.
+    invoke_signed(
+        &escrow_instruction,
         &[
-            token_program_info.clone(),     // The SPL Token Program
-            maker_token_acc_a_info.clone(), // Maker's source Token A account
-            maker_info.clone(),             // Maker's account (as signer of the transfer)
-            deposit_vault_info.clone(),     // Destination vault account
+            maker_info.clone(),          // Payer for the account creation
+            escrow_info.clone(),         // The escrow state account being created
+            system_program_info.clone(), // The System Program
         ],
+        signer_seeds,
     )?;
-    msg!("Tokens transferred to vault.");
-
-    // STEP 6: Create the escrow state account if it doesn't already exist.
-    // This account will store the details of the escrow.
-    if escrow_info.owner != program_id {
-        msg!("Creating escrow state account...");
-
-        // Calculate the space required for the Escrow state struct.
-        let space = Escrow::ACCOUNT_LEN;
-        // Calculate the minimum lamports required for rent exemption for the escrow state.
-        let rent_lamports = rent.minimum_balance(space);
-
-        // Instruction to create a new account for the escrow state.
-        // `maker_info.key` pays for the rent.
-        // `escrow_info.key` is the address of the new account (escrow PDA).
-        // `rent_lamports` is the amount of SOL for rent exemption.
-        // `space` is the size of the account data.
-        // `program_id` is the owner of the new account (this escrow program).
-        let escrow_instruction = create_account(
-            &maker_info.key,
-            &escrow_info.key,
-            rent_lamports,
-            space as u64,
-            program_id, // The escrow program owns this account
-        );
-
-        // Signer seeds for the escrow PDA to sign the `create_account` instruction.
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"escrow_vault",
-            maker_info.key.as_ref(),
-            escrow_seed.as_ref(),
-            &[escrow_bump], // The bump seed for the escrow PDA.
-        ]];
-
-        // Invoke the System Program to create the escrow state account.
-        // `invoke_signed` is used because the `escrow_info` (which is a PDA)
-        // is effectively signing its own creation as its `owner` is set.
-        invoke_signed(
-            &escrow_instruction,
-            &[
-                maker_info.clone(),          // Payer for the account creation
-                escrow_info.clone(),         // The escrow state account being created
-                system_program_info.clone(), // The System Program
-            ],
-            signer_seeds,
-        )?;
-        msg!("Escrow state account created.");
-    }
+    msg!("Escrow state account created.");
 
-    // STEP 7: Store the escrow data into the newly created or existing escrow state account.
+    // STEP 8: Store the escrow data into the newly created escrow state account.
     msg!("Storing escrow data...");
 
+    // Milestone and approval escrows are never taken: they pay their recipient from the start.
+    let (status, beneficiary) = if pays_recipient {
+        (EscrowStatus::InProgress, allowed_taker)
+    } else {
+        (EscrowStatus::Open, None)
+    };
+
     // Create an instance of the Escrow struct with the transaction details.
     let escrow_account = Escrow {
         escrow_id,
-        maker: *maker_info.key,         // Public key of the maker.
-        token_mint_a: *mint_a_info.key, // Mint of the deposited token.
-        token_mint_b: *mint_b_info.key, // Mint of the token to receive.
-        receive: receive_amount,        // receive amount of Token B.
-        bump: escrow_bump,              // Bump seed for the escrow PDA.
+        maker: *maker_info.key,                     // Public key of the maker.
+        token_mint_a: *mint_a_info.key,             // Mint of the deposited token.
+        token_mint_b: *mint_b_info.key,             // Mint of the token to receive.
+        offered,                                    // Token A on offer to takers.
+        receive: receive_amount,                    // receive amount of Token B.
+        bump: escrow_bump,                          // Bump seed for the escrow PDA.
+        vault_bump,                                 // Bump seed for the vault PDA.
+        payment_vault_bump: 0,                      // Set when an arbiter escrow is taken.
+        config_bump,                                // Bump seed for the config PDA.
+        expires_at,                                 // Optional expiry timestamp.
+        allowed_taker,                              // Optional designated taker.
+        operator,                                   // Optional operator.
+        maker_multisig: maker_authority.multisig(), // Optional multisig owning the maker's tokens.
+        offer_asset,                                // Fungible token A or a single NFT.
+        vesting,                                    // Optional unlock schedule for the taker.
+        beneficiary, // Recipient of a milestone or approval escrow, or set once taken.
+        claimed: 0,  // Nothing vested has been claimed.
+        arbiter,     // Optional arbiter of the trade.
+        taker: None, // Set once an arbiter escrow is taken.
+        maker_confirmed: false, // Not confirmed by the maker yet.
+        taker_confirmed: false, // Not confirmed by the taker yet.
+        milestones: milestones
+            .iter()
+            .map(|amount| Milestone {
+                amount: *amount,
+                approved: false,
+            })
+            .collect(), // Tranches paid to the recipient, none approved yet.
+        approvers: approvers
+            .iter()
+            .map(|key| Approver {
+                key: *key,
+                approved: false,
+            })
+            .collect(), // Keys that release token A, none approved yet.
+        approval_threshold, // Approvals needed to release token A.
+        fee_bps: config.map_or(0, |config| config.fee_bps), // Protocol fee, fixed for this escrow.
+        receive_asset, // Token B or native SOL.
+        status,      // Open for takers, or paying a milestone or approval escrow's recipient.
+        basket_offered: basket_offered_legs, // Extra mints on offer.
+        basket_requested: basket_requested_legs, // Extra mints requested.
     };
 
     // Pack the Escrow struct data into the escrow_info account's data buffer.
@@ -360,9 +731,68 @@ pub fn initialize_escrow(
     // --- Final Logging ---
     msg!("Escrow initialized successfully!");
     msg!("   -Escrow ID: {}", escrow_id);
-    msg!("   -Deposited: {} tokens", deposit_amount);
-    msg!("   -Expects: {} tokens in return", receive_amount);
-    msg!(" Escrow account: {:#?}", escrow_account);
+    match offer_asset {
+        OfferAsset::Token => msg!("   -Deposited: {} tokens", offered),
+        OfferAsset::Nft => msg!("   -Deposited: NFT {}", mint_a_info.key),
+    }
+    match receive_asset {
+        ReceiveAsset::Token => msg!("   -Expects: {} tokens in return", receive_amount),
+        ReceiveAsset::NativeSol => msg!("   -Expects: {} lamports in return", receive_amount),
+    }
+    if let Some(expires_at) = expires_at {
+        msg!("   -Expires at: {}", expires_at);
+    }
+    if let Some(allowed_taker) = allowed_taker {
+        if pays_recipient {
+            msg!("   -Recipient: {}", allowed_taker);
+        } else {
+            msg!("   -Private to taker: {}", allowed_taker);
+        }
+    }
+    if let Some(operator) = operator {
+        msg!("   -Operator: {}", operator);
+    }
+    if let Some(vesting) = vesting {
+        msg!("   -Vests from {} to {}", vesting.start, vesting.end);
+    }
+    if let Some(arbiter) = arbiter {
+        msg!("   -Arbiter: {}", arbiter);
+    }
+    if let Some(maker_multisig) = escrow_account.maker_multisig {
+        msg!("   -Maker multisig: {}", maker_multisig);
+    }
+    for (index, milestone) in escrow_account.milestones.iter().enumerate() {
+        msg!("   -Milestone {}: {} tokens", index, milestone.amount);
+    }
+    if !escrow_account.approvers.is_empty() {
+        msg!(
+            "   -Released on {} of {} approvals",
+            approval_threshold,
+            escrow_account.approvers.len()
+        );
+    }
+    for approver in &escrow_account.approvers {
+        msg!("   -Approver: {}", approver.key);
+    }
+    for leg in &escrow_account.basket_offered {
+        msg!("   -Basket deposit: {} of {}", leg.amount, leg.mint);
+    }
+    for leg in &escrow_account.basket_requested {
+        msg!("   -Basket request: {} of {}", leg.amount, leg.mint);
+    }
+    EscrowEvent::Created(EscrowCreated {
+        escrow: *escrow_info.key,
+        maker: escrow_account.maker,
+        escrow_id,
+        token_mint_a: escrow_account.token_mint_a,
+        token_mint_b: escrow_account.token_mint_b,
+        offered,
+        receive: receive_amount,
+        receive_asset,
+        expires_at,
+        allowed_taker,
+    })
+    .emit();
 
     Ok(())
 }
@@ -0,0 +1,131 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::transfer,
+    system_program::ID as SYSTEM_PROGRAM,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::EscrowError,
+    instructions::config::load_config,
+    state::{Config, Escrow, MigrationContext, VersionedEscrow},
+    token::{is_token_program, unpack_token_account},
+};
+
+/// Upgrades an escrow state account to the current layout in place.
+///
+/// The account is reallocated to `Escrow::ACCOUNT_LEN` and rewritten with the current
+/// header, including the vault bump derived here. The maker pays for the extra rent.
+/// Accounts that are already current are left untouched.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `maker_info`: The maker of the escrow, who pays for the larger account.
+/// 1. `[writable]` `escrow_info`: The escrow state account to migrate.
+/// 2. `[]` `vault_info`: The token vault holding Token A. For version 0, its balance becomes the offer.
+/// 3. `[]` `system_program_info`: The Solana System Program account.
+/// 4. `[]` `config_info`: The program config PDA, which need not exist yet. Its current fee is
+///    recorded on the migrated escrow.
+pub fn migrate_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account (pays rent)
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let system_program_info = next_account_info(account_iter)?; // System Program
+    let config_info = next_account_info(account_iter)?; // Program config PDA
+
+    // --- Validation Checks ---
+    msg!("Starting escrow migration validation...");
+
+    // 1. Ensure the maker has signed the transaction and can pay rent.
+    if !maker_info.is_signer || !maker_info.is_writable || !escrow_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 2. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 3. Verify the System Program ID.
+    if *system_program_info.key != SYSTEM_PROGRAM {
+        return Err(EscrowError::IncorrectProgramId.into());
+    }
+    // 4. Verify the vault and escrow PDAs.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let seeds = &[b"vault", maker_info.key.as_ref(), escrow_seed.as_ref()];
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(seeds, program_id);
+    let seeds = &[
+        b"escrow_vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+    ];
+    let (escrow_pda, _escrow_bump) = Pubkey::find_program_address(seeds, program_id);
+    if *escrow_info.key != escrow_pda || *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    let legacy = match VersionedEscrow::unpack(&escrow_info.data.borrow())? {
+        VersionedEscrow::Current(_) => {
+            msg!("Escrow is already at version {}.", Escrow::VERSION);
+            return Ok(());
+        }
+        VersionedEscrow::V0(legacy) => legacy,
+    };
+    // 5. Version 0 offered its whole vault, so its vault balance becomes the offer.
+    if !is_token_program(vault_info.owner) {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let vault_data = unpack_token_account(vault_info, vault_info.owner)?;
+    if vault_data.mint != legacy.token_mint_a {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let (_config_pda, config_bump) = Config::find_address(program_id);
+    let fee_bps =
+        load_config(program_id, config_info, config_bump)?.map_or(0, |config| config.fee_bps);
+    let escrow_account = legacy.migrate(&MigrationContext {
+        vault_amount: vault_data.amount,
+        vault_bump,
+        config_bump,
+        fee_bps,
+    });
+    // 6. Verify the stored escrow ID and maker.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let account_len = Escrow::ACCOUNT_LEN;
+    msg!("All validations passed. Migrating escrow from version 0...");
+
+    // STEP 1: Top up the escrow account so it stays rent exempt at the new size.
+    let required_lamports = Rent::get()?.minimum_balance(account_len);
+    let top_up = required_lamports.saturating_sub(escrow_info.lamports());
+    if top_up > 0 {
+        msg!("Transferring {} lamports of rent from the maker...", top_up);
+        invoke(
+            &transfer(maker_info.key, escrow_info.key, top_up),
+            &[
+                maker_info.clone(),
+                escrow_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    // STEP 2: Grow the account and rewrite it in the current layout.
+    escrow_info.realloc(account_len, true)?;
+    escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+
+    msg!("Escrow migrated to version {}.", Escrow::VERSION);
+    Ok(())
+}
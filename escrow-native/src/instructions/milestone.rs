@@ -0,0 +1,247 @@
+use crate::{
+    error::EscrowError,
+    events::{EscrowEvent, EscrowMilestoneApproved},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, harvest_vault_fees, unpack_mint, unpack_token_account},
+};
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke_signed, pubkey::Pubkey,
+};
+use spl_token_2022::instruction::{close_account, transfer_checked};
+
+/// Approves one milestone of a milestone escrow, releasing its tranche of Token A to the recipient.
+///
+/// Only the maker can approve a milestone, each milestone once, in any order. The tranche can only
+/// go to a Token A account owned by the escrow's recipient, its `beneficiary`. Approving the last
+/// unapproved milestone empties the vault, including anything sent to it outside the escrow, and
+/// closes the vault and escrow state accounts, returning their rent to the maker. Tranches left
+/// unapproved at the escrow's deadline are refunded with `CancelEscrow` instead.
+///
+/// Accounts expected:
+///
+/// 0. `[signer, writable]` `maker_info`: The maker of the escrow, which receives the reclaimed rent.
+/// 1. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
+/// 2. `[writable]` `recipient_token_acc_a_info`: The recipient's token account for Token A, where the tranche is paid.
+/// 3. `[writable]` `escrow_info`: The escrow state account, closed by the last approval.
+/// 4. `[writable]` `vault_info`: The token vault account holding Token A, closed by the last approval.
+/// 5. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+pub fn approve_milestone(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    milestone: u8,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account (approves, receives rent)
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let recipient_token_acc_a_info = next_account_info(account_iter)?; // Recipient's Token A account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // --- Validation Checks ---
+    msg!("Starting milestone approval validation...");
+
+    // 1. Ensure the maker has signed the transaction.
+    if !maker_info.is_signer {
+        msg!("Error: Maker must be a signer.");
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    // 2. Ensure all writable accounts are actually writable.
+    if !maker_info.is_writable
+        || !recipient_token_acc_a_info.is_writable
+        || !escrow_info.is_writable
+        || !vault_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 3. Verify the token program owns Token A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 4. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 5. Verify the provided escrow ID matches the one stored in the account.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    // 6. Verify the escrow is a milestone escrow that is still paying its recipient.
+    let Some(recipient) = escrow_account.beneficiary else {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidMilestone.into());
+    };
+    if !escrow_account.has_milestones() || escrow_account.status != EscrowStatus::InProgress {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidMilestone.into());
+    }
+    // 7. Verify the maker account matches the maker recorded in the escrow.
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 8. Verify the mint account matches Token A recorded in the escrow.
+    if escrow_account.token_mint_a != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    msg!("Validating recipient's Token A account...");
+    let recipient_token_a_data =
+        unpack_token_account(recipient_token_acc_a_info, token_program_info.key)?;
+
+    // 9. Verify the tranche goes to a Token A account owned by the recipient.
+    if recipient_token_a_data.owner != recipient {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 10. Verify the recipient's Token A account has the correct mint.
+    if recipient_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    msg!("Validating vault...");
+    // 11. Verify the vault account is owned by the token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+
+    // 12. Verify vault has the correct mint (Token A).
+    if vault_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    // 13. Verify the vault account and escrow account are derived correctly.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
+
+    if *escrow_info.key != escrow_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // 14. Verify the milestone exists and has not been approved yet.
+    let Some(approved) = escrow_account
+        .milestones
+        .get(milestone as usize)
+        .filter(|approved| !approved.approved)
+        .copied()
+    else {
+        msg!(
+            "Error: Milestone {} does not exist or is already approved.",
+            milestone
+        );
+        return Err(EscrowError::InvalidMilestone.into());
+    };
+    escrow_account.milestones[milestone as usize].approved = true;
+    let is_last_milestone = escrow_account
+        .milestones
+        .iter()
+        .all(|milestone| milestone.approved);
+    // The last approval empties the vault so it can be closed.
+    let release_amount = if is_last_milestone {
+        vault_data.amount
+    } else {
+        approved.amount
+    };
+    if vault_data.amount < release_amount {
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    msg!("All validations passed. Executing milestone approval...");
+
+    // --- Approval Execution ---
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        maker_info.key.as_ref(),
+        escrow_seed.as_ref(),
+        &[vault_bump],
+    ]];
+
+    // STEP 1: Transfer the milestone's tranche of Token A to the recipient.
+    if release_amount > 0 {
+        msg!(
+            "Releasing {} Token A for milestone {} to recipient...",
+            release_amount,
+            milestone
+        );
+        let release_instruction = transfer_checked(
+            token_program_info.key,             // The token program ID
+            vault_info.key,                     // SOURCE account (the vault)
+            mint_a_info.key,                    // The mint of Token A
+            recipient_token_acc_a_info.key,     // DESTINATION account (recipient's Token A)
+            &vault_pda,                         // AUTHORITY (the vault PDA)
+            &[&vault_pda],                      // SIGNERS (the vault PDA needs to sign this)
+            release_amount,                     // The amount of tokens to transfer
+            unpack_mint(mint_a_info)?.decimals, // The decimals of Token A
+        )?;
+        invoke_signed(
+            &release_instruction,
+            &[
+                token_program_info.clone(),         // The token program
+                mint_a_info.clone(),                // The mint of Token A
+                recipient_token_acc_a_info.clone(), // Recipient's destination account
+                vault_info.clone(),                 // The vault account (source)
+            ],
+            vault_signer_seeds,
+        )?;
+        msg!("Tranche transferred successfully.");
+    }
+    // Tokens sent to the vault outside the escrow are not on offer, so `offered` may run out first.
+    escrow_account.offered = if is_last_milestone {
+        0
+    } else {
+        escrow_account.offered.saturating_sub(release_amount)
+    };
+    EscrowEvent::MilestoneApproved(EscrowMilestoneApproved {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        recipient,
+        escrow_id,
+        milestone,
+        released: release_amount,
+        remaining: escrow_account.offered,
+    })
+    .emit();
+    if !is_last_milestone {
+        escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        msg!(
+            "Milestone {} approved! {} Token A left for the other milestones.",
+            milestone,
+            escrow_account.offered
+        );
+        return Ok(());
+    }
+    // STEP 2: Close the vault token account; its rent goes to the maker.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
+    msg!("Closing vault account and reclaiming rent...");
+    let close_acc_instruction = close_account(
+        token_program_info.key, // The token program ID
+        vault_info.key,         // The account to close (vault)
+        maker_info.key,         // The recipient of the rent lamports (maker)
+        &vault_pda,             // The authority that can close the account (vault PDA)
+        &[&vault_pda],          // SIGNERS (vault PDA needs to sign)
+    )?;
+    invoke_signed(
+        &close_acc_instruction,
+        &[
+            token_program_info.clone(), // The token program
+            vault_info.clone(),         // The vault account to close
+            maker_info.clone(),         // The maker (recipient of rent)
+        ],
+        vault_signer_seeds,
+    )?;
+    msg!("Vault closed.");
+
+    // STEP 3: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+
+    msg!("Escrow: every milestone approved and closed.");
+    Ok(())
+}
@@ -1,4 +1,101 @@
+pub mod approve;
+pub mod bid;
+pub mod claim;
+pub mod config;
+pub mod expire;
 pub mod instruction;
 pub mod make;
+pub mod migrate;
+pub mod milestone;
+pub mod operator;
 pub mod refund;
+pub mod settle;
 pub mod take;
+pub mod update;
+
+use crate::error::EscrowError;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{allocate, assign, create_account, transfer},
+    system_program::ID as SYSTEM_PROGRAM,
+    sysvar::Sysvar,
+};
+
+/// Creates the PDA `account_info` with `space` bytes owned by `owner`, paid for by `payer_info`.
+///
+/// Anyone can send lamports to a PDA before it exists, which makes `create_account` fail.
+/// When the address already holds lamports, the payer only tops it up to rent exemption and
+/// the PDA then allocates and assigns itself, signed with `signer_seeds`.
+pub fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    system_program_info: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account_info.lamports();
+    if current_lamports == 0 {
+        return invoke_signed(
+            &create_account(
+                payer_info.key,
+                account_info.key,
+                rent_lamports,
+                space as u64,
+                owner,
+            ),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[signer_seeds],
+        );
+    }
+
+    let shortfall = rent_lamports.saturating_sub(current_lamports);
+    if shortfall > 0 {
+        invoke(
+            &transfer(payer_info.key, account_info.key, shortfall),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &assign(account_info.key, owner),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Closes an account owned by this program, moving its rent to `destination_info`.
+///
+/// The account is handed back to the System Program so its PDA can be reused.
+pub fn close_program_account(
+    account_info: &AccountInfo,
+    destination_info: &AccountInfo,
+) -> ProgramResult {
+    let mut destination_lamports = destination_info.lamports.borrow_mut();
+    **destination_lamports = destination_lamports
+        .checked_add(account_info.lamports())
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    drop(destination_lamports);
+    **account_info.lamports.borrow_mut() = 0;
+    account_info.data.borrow_mut().fill(0);
+    account_info.realloc(0, false)?;
+    account_info.assign(&SYSTEM_PROGRAM);
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::EscrowError,
+    state::{Escrow, EscrowStatus},
+};
+
+/// Registers, replaces or removes the operator of an open escrow.
+///
+/// The operator can cancel or reprice the escrow in place of the maker, so the maker's
+/// key can stay offline, but never receives its funds: a cancel still refunds the maker.
+/// Only the maker can change the operator; passing `None` revokes it.
+///
+/// Accounts expected:
+///
+/// 0. `[signer]` `maker_info`: The maker of the escrow.
+/// 1. `[writable]` `escrow_info`: The escrow state account.
+pub fn set_operator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    operator: Option<Pubkey>,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+
+    // --- Validation Checks ---
+    if !maker_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    if !escrow_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    if *escrow_info.key != escrow_account.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // The operator cannot hand the escrow to someone else; only the maker can.
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::Unauthorized.into());
+    }
+    if escrow_account.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
+
+    escrow_account.operator = operator;
+    escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+    match operator {
+        Some(operator) => msg!("Escrow {} operator set to {}.", escrow_id, operator),
+        None => msg!("Escrow {} operator removed.", escrow_id),
+    }
+    Ok(())
+}
@@ -1,29 +1,48 @@
-use crate::{error::EscrowError, state::Escrow};
+use crate::{
+    basket::{release_basket_vault, ReleaseLegAccounts},
+    error::EscrowError,
+    events::{EscrowCancelled, EscrowEvent},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, harvest_vault_fees, unpack_mint, unpack_token_account},
+};
 use solana_program::{
-    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
-    program::invoke_signed, program_pack::Pack, pubkey::Pubkey,
+    account_info::next_account_info,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    pubkey::Pubkey,
     system_program::ID as SYSTEM_PROGRAM,
+    sysvar::{clock::Clock, Sysvar},
 };
-use spl_token::{
-    instruction::{close_account, transfer},
-    state::Account,
-    ID as TOKEN_PROGRAM,
-};
+use spl_token_2022::instruction::{close_account, transfer_checked};
 
 /// Cancels an existing escrow, refunding the tokens to the maker and closing the accounts.
 ///
-/// This instruction is callable only by the original maker of the escrow.
+/// This instruction is callable only by the original maker of the escrow or its operator.
+/// Either way the tokens are refunded to the maker's Token A account and the rent to the maker.
+/// A milestone escrow can only be cancelled once its `expires_at` deadline has passed, which
+/// refunds the tranches of every milestone still unapproved. So can an approval escrow that has
+/// a deadline; one without can be cancelled until it is approved.
 /// It performs several validation checks.
 ///
 /// Accounts expected:
 ///
-/// 1. `[signer]` `maker_info`: The account of the person who initiated the escrow (maker).
-/// 2. `[]` `mint_a_info`: The mint account of Token A.
+/// 1. `[signer, writable]` `maker_info`: The account of the person who initiated the escrow (maker).
+///    Signs unless the operator does.
+/// 2. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
 /// 3. `[writable]` `maker_token_acc_a_info`: The maker's token account for Token A, where funds will be refunded.
 /// 4. `[writable]` `escrow_info`: The escrow state account, which will be closed.
 /// 5. `[writable]` `vault_info`: The token vault account holding Token A, which will be closed.
 /// 6. `[]` `system_program_info`: The Solana System Program account.
-/// 7. `[]` `token_program_info`: The SPL Token Program account.
+/// 7. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+/// 8. `[signer]` `operator_info`: The escrow's operator. Only passed when the maker does not sign.
+///
+/// Then, for each offered basket leg:
+/// - `[writable]` `mint_info`: The mint of the leg, which receives any withheld transfer fees.
+/// - `[writable]` `vault_info`: The leg's vault PDA, which will be closed.
+/// - `[writable]` `maker_token_info`: The maker's token account for the leg, where it is refunded.
 ///
 /// Parameters:
 /// - `program_id`: The public key of the current program.
@@ -43,25 +62,34 @@ pub fn cancel_escrow(
     let escrow_info = next_account_info(account_iter)?; // Escrow state account
     let vault_info = next_account_info(account_iter)?; // Token vault account
     let system_program_info = next_account_info(account_iter)?; // System Program
-    let token_program_info = next_account_info(account_iter)?; // SPL Token Program
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // The operator signs in place of the maker, so it is only passed when the maker does not.
+    let authority_info = if maker_info.is_signer {
+        maker_info
+    } else {
+        next_account_info(account_iter)? // Escrow operator
+    };
 
     // --- Validation Checks ---
     msg!("Starting escrow cancellation validation...");
 
-    // 1. Ensure the maker has signed the transaction.
-    if !maker_info.is_signer {
+    // 1. Ensure the maker or the operator has signed the transaction.
+    if !authority_info.is_signer {
         return Err(EscrowError::InvalidAccountData.into());
     }
     // 2. Ensure all writable accounts are actually writable.
-    if !maker_token_acc_a_info.is_writable || !escrow_info.is_writable || !vault_info.is_writable {
+    if !maker_info.is_writable
+        || !maker_token_acc_a_info.is_writable
+        || !escrow_info.is_writable
+        || !vault_info.is_writable
+    {
         return Err(EscrowError::InvalidAccountData.into());
     }
 
-    // 3. Verify the SPL Token Program ID.
-    // Ensures that the correct token program is being used.
-    if *token_program_info.key != TOKEN_PROGRAM {
-        return Err(EscrowError::IncorrectProgramId.into());
-    }
+    // 3. Verify the token program.
+    // Ensures that the token program which owns Token A is being used.
+    check_token_program(mint_a_info, token_program_info)?;
     // 4. Verify the escrow account is owned by this program.
     // Essential for ensuring program control over its state.
     if *escrow_info.owner != *program_id {
@@ -80,34 +108,56 @@ pub fn cancel_escrow(
     if escrow_account.escrow_id != escrow_id {
         return Err(EscrowError::InvalidEscrowId.into());
     }
-    // 6. Verify that only the original maker can refund the escrow.
+    // Verify the escrow is still open, or is a milestone or approval escrow past its deadline.
+    match escrow_account.status {
+        EscrowStatus::Open => {}
+        EscrowStatus::InProgress => {
+            let now = Clock::get()?.unix_timestamp;
+            if escrow_account
+                .expires_at
+                .is_some_and(|deadline| now < deadline)
+            {
+                msg!(
+                    "Error: The recipient can still be paid until {:?}; it is {}.",
+                    escrow_account.expires_at,
+                    now
+                );
+                return Err(EscrowError::EscrowNotExpired.into());
+            }
+        }
+        _ => {
+            msg!("Error: Escrow is {:?}.", escrow_account.status);
+            return Err(EscrowError::EscrowNotOpen.into());
+        }
+    }
+    // 6. Verify that only the original maker or its operator can refund the escrow.
     // Prevents unauthorized refunds by others.
     if escrow_account.maker != *maker_info.key {
         return Err(EscrowError::InvalidAccountOwner.into());
     }
+    if !escrow_account.can_manage(authority_info.key) {
+        return Err(EscrowError::Unauthorized.into());
+    }
     // 7. Verify the mint account associated with Token A in the escrow matches the provided maker's token account.
     if escrow_account.token_mint_a != *mint_a_info.key {
         return Err(EscrowError::InvalidMint.into());
     }
     msg!("Validating maker's Token A account...");
     // Unpack the maker's Token A account data to access its properties.
-    let maker_token_a_data = Account::unpack(&maker_token_acc_a_info.data.borrow())?;
+    let maker_token_a_data = unpack_token_account(maker_token_acc_a_info, token_program_info.key)?;
 
-    // 8. Verify maker's Token A account is owned by the maker.
-    // if maker_token_acc_a_info.owner != maker_info.key {
-    //     return Err(EscrowError::InvalidAccountOwner.into());
-    // }
+    // 8. Verify maker's Token A account is owned by the maker (or the maker's multisig),
+    // so an operator cannot redirect the refund.
+    if maker_token_a_data.owner != escrow_account.token_owner() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
     // 9. Verify maker's Token A account has the correct mint.
     if maker_token_a_data.mint != *mint_a_info.key {
         return Err(EscrowError::InvalidMint.into());
     }
-    // 10. Verify the vault account is owned by the token program.
-    if *vault_info.owner != TOKEN_PROGRAM {
-        return Err(EscrowError::IncorrectProgramId.into());
-    }
     msg!("Validating vault...");
-    // Unpack the vault account data.
-    let vault_data = Account::unpack(&vault_info.data.borrow())?;
+    // 10. Unpack the vault account data, verifying it is owned by the token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
 
     // 11. Verify vault has the correct mint (Token A).
     if vault_data.mint != *mint_a_info.key {
@@ -115,16 +165,11 @@ pub fn cancel_escrow(
     }
 
     // 12. Verify the vault account and escrow account are derived correctly.
-    // Recalculate the PDA for the vault and escrow based on the seeds.
+    // Recreate both PDAs from the bumps stored in the escrow, which avoids a bump search.
     let escrow_seed = escrow_id.to_le_bytes();
-    let seeds = &[b"vault", maker_info.key.as_ref(), escrow_seed.as_ref()];
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(seeds, program_id);
-    let seeds = &[
-        b"escrow_vault",
-        maker_info.key.as_ref(),
-        escrow_seed.as_ref(),
-    ];
-    let (escrow_pda, _escrow_bump) = Pubkey::find_program_address(seeds, program_id);
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
 
     // Ensure the provided escrow_info key matches the derived PDA.
     if *escrow_info.key != escrow_pda {
@@ -135,26 +180,33 @@ pub fn cancel_escrow(
         return Err(EscrowError::PDADerivationMismatch.into());
     }
 
-    // Check if the vault actually contains tokens.
-    if vault_data.amount == 0 {
-        msg!("Error: Vault is empty! No tokens to refund.");
+    // Check the vault still holds everything the escrow has on offer.
+    if vault_data.amount < escrow_account.offered {
+        msg!(
+            "Error: Vault holds {} Token A, escrow has {} on offer.",
+            vault_data.amount,
+            escrow_account.offered
+        );
         return Err(EscrowError::InsufficientFunds.into());
     }
     msg!("All validations passed. Executing refund...");
 
     // --- Refund Execution ---
-    // Get the amount of tokens to refund from the vault.
+    // Get the amount of tokens to refund from the vault. This is the remaining offer plus
+    // anything sent to the vault outside the escrow, so the vault can be closed.
     let refund_amount = vault_data.amount;
     msg!("Refunding {} Token A to maker...", refund_amount);
 
     // STEP 1: Transfer the tokens from the vault back to the maker's Token A account.
-    let refund_instruction = transfer(
-        &TOKEN_PROGRAM,              // The token program ID
-        &vault_info.key,             // SOURCE account (the vault)
-        &maker_token_acc_a_info.key, // DESTINATION account (maker's Token A account)
-        &vault_pda,                  // AUTHORITY (the vault PDA, which owns the vault account)
-        &[&vault_pda],               // SIGNERS (the vault PDA needs to sign this)
-        refund_amount,               // The amount of tokens to transfer
+    let refund_instruction = transfer_checked(
+        token_program_info.key,             // The token program ID
+        vault_info.key,                     // SOURCE account (the vault)
+        mint_a_info.key,                    // The mint of Token A
+        maker_token_acc_a_info.key,         // DESTINATION account (maker's Token A account)
+        &vault_pda,    // AUTHORITY (the vault PDA, which owns the vault account)
+        &[&vault_pda], // SIGNERS (the vault PDA needs to sign this)
+        refund_amount, // The amount of tokens to transfer
+        unpack_mint(mint_a_info)?.decimals, // The decimals of Token A
     )?;
 
     // Define the signer seeds for the vault PDA. These seeds are used to sign the transaction.
@@ -170,6 +222,7 @@ pub fn cancel_escrow(
         &refund_instruction,
         &[
             token_program_info.clone(),     // The token program
+            mint_a_info.clone(),            // The mint of Token A
             maker_token_acc_a_info.clone(), // Maker's destination account
             vault_info.clone(),             // The vault account (source)
         ],
@@ -178,14 +231,16 @@ pub fn cancel_escrow(
     msg!("Refund transferred successfully.");
 
     // STEP 2: Close the vault token account to reclaim its rent.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
     // The remaining lamports in the vault will be sent to the maker.
     msg!("Closing vault account and reclaiming rent...");
     let close_acc_instruction = close_account(
-        &TOKEN_PROGRAM,  // The token program ID
-        &vault_info.key, // The account to close (vault)
-        &maker_info.key, // The recipient of the rent lamports (maker)
-        &vault_pda,      // The authority that can close the account (vault PDA)
-        &[&vault_pda],   // SIGNERS (vault PDA needs to sign)
+        token_program_info.key, // The token program ID
+        vault_info.key,         // The account to close (vault)
+        maker_info.key,         // The recipient of the rent lamports (maker)
+        &vault_pda,             // The authority that can close the account (vault PDA)
+        &[&vault_pda],          // SIGNERS (vault PDA needs to sign)
     )?;
     // Invoke the close account instruction, signed by the vault PDA.
     invoke_signed(
@@ -199,24 +254,42 @@ pub fn cancel_escrow(
     )?;
     msg!("Vault closed.");
 
-    // STEP 3: Close the escrow state account to reclaim rent.
-    // This is done by transferring all lamports from the escrow account to the maker,
-    // then marking the account as closed by zeroing its data.
-    msg!("Closing escrow state account and reclaiming rent...");
-
-    // Transfer all lamports from the escrow account back to the maker.
-    let mut maker_lamports = maker_info.lamports.borrow_mut();
-    let escrow_lamports = escrow_info.lamports();
-    **maker_lamports = maker_lamports
-        .checked_add(escrow_lamports) // Add escrow's lamports to maker's
-        .ok_or(EscrowError::ArithmeticOverflow)?; // Handle potential overflow
+    // STEP 3: Refund the offered legs of a basket and close their vaults.
+    for (index, leg) in escrow_account.basket_offered.iter().enumerate() {
+        let leg_mint_info = next_account_info(account_iter)?;
+        let leg_vault_info = next_account_info(account_iter)?;
+        let maker_leg_info = next_account_info(account_iter)?;
+        if *leg_mint_info.key != leg.mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        release_basket_vault(
+            program_id,
+            &escrow_account,
+            index as u8,
+            &escrow_account.token_owner(),
+            ReleaseLegAccounts {
+                maker_info,
+                mint_info: leg_mint_info,
+                vault_info: leg_vault_info,
+                destination_info: maker_leg_info,
+                token_program_info,
+            },
+        )?;
+    }
 
-    // Set the escrow account's lamports to 0, effectively closing it and making it rent-exempt.
-    **escrow_info.lamports.borrow_mut() = 0;
-    // Zero out the data of the escrow account to clear its state.
-    escrow_info.data.borrow_mut().fill(0);
+    // STEP 4: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
 
     msg!("Escrow account closed.");
+    EscrowEvent::Cancelled(EscrowCancelled {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        escrow_id,
+        refunded: refund_amount,
+    })
+    .emit();
 
     Ok(()) // Return success.
 }
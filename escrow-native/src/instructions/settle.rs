@@ -0,0 +1,313 @@
+use crate::{
+    arbiter::{split_vault, SplitVaultAccounts, MAX_RELEASE_BPS, PAYMENT_VAULT_SEED},
+    error::EscrowError,
+    events::{EscrowEvent, EscrowSettled},
+    instructions::close_program_account,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, unpack_token_account},
+};
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    pubkey::Pubkey,
+};
+
+/// Confirms a held arbiter escrow for the maker, the taker, or both, whichever sign.
+///
+/// Each confirmation is recorded in the escrow state. Once both parties have confirmed, the
+/// trade completes as a plain take would have: all of Token A goes to the taker and all of the
+/// payment vault to the maker. Both vaults and the escrow state account are then closed; the
+/// payment vault's rent goes back to the taker, who paid it, and the rest to the maker.
+///
+/// Accounts expected:
+///
+/// 0. `[writable, signer?]` `maker_info`: The maker of the escrow, which receives the reclaimed rent. Signs to confirm as the maker.
+/// 1. `[writable, signer?]` `taker_info`: The wallet that took the escrow, which receives the payment vault's rent. Signs to confirm as the taker.
+/// 2. `[writable]` `mint_a_info`: The mint account of Token A, which receives any withheld transfer fees.
+/// 3. `[writable]` `mint_b_info`: The mint account of Token B, which receives any withheld transfer fees.
+/// 4. `[writable]` `maker_token_acc_a_info`: The maker's token account for Token A, owned by the maker or the maker's multisig.
+/// 5. `[writable]` `maker_token_acc_b_info`: The maker's token account for Token B, owned by the maker or the maker's multisig.
+/// 6. `[writable]` `taker_token_acc_a_info`: The beneficiary's token account for Token A.
+/// 7. `[writable]` `taker_token_acc_b_info`: The beneficiary's token account for Token B.
+/// 8. `[writable]` `vault_info`: The token vault account holding Token A, closed on settlement.
+/// 9. `[writable]` `payment_vault_info`: The payment vault holding Token B, closed on settlement.
+/// 10. `[writable]` `escrow_info`: The escrow state account, closed on settlement.
+/// 11. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+/// 12. `[]` `token_program_b_info`: The token program that owns Token B (SPL Token or Token-2022).
+pub fn confirm_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+) -> ProgramResult {
+    settle(program_id, accounts, escrow_id, None)
+}
+
+/// Settles a held arbiter escrow on the arbiter's ruling, whatever the parties have confirmed.
+///
+/// The taker receives `release_bps` of the Token A vault and the maker the rest; the maker
+/// receives `release_bps` of the payment vault and the taker the rest. Shares are rounded down
+/// in favour of the party that funded each vault. Both vaults and the escrow state account are
+/// then closed as for `ConfirmEscrow`.
+///
+/// Accounts expected: the same as `confirm_escrow`, where neither party needs to sign, then:
+///
+/// 13. `[signer]` `arbiter_info`: The arbiter recorded in the escrow.
+pub fn resolve_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    release_bps: u16,
+) -> ProgramResult {
+    settle(program_id, accounts, escrow_id, Some(release_bps))
+}
+
+/// Validates the accounts of a held arbiter escrow and settles it, either on the parties'
+/// confirmations or, given a `ruling`, on the arbiter's `release_bps`.
+fn settle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    ruling: Option<u16>,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account (receives rent)
+    let taker_info = next_account_info(account_iter)?; // Taker's wallet (receives payment vault rent)
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let mint_b_info = next_account_info(account_iter)?; // Mint account for Token B
+    let maker_token_acc_a_info = next_account_info(account_iter)?; // Maker's Token A account
+    let maker_token_acc_b_info = next_account_info(account_iter)?; // Maker's Token B account
+    let taker_token_acc_a_info = next_account_info(account_iter)?; // Beneficiary's Token A account
+    let taker_token_acc_b_info = next_account_info(account_iter)?; // Beneficiary's Token B account
+    let vault_info = next_account_info(account_iter)?; // Token A vault
+    let payment_vault_info = next_account_info(account_iter)?; // Token B payment vault
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+    let token_program_b_info = next_account_info(account_iter)?; // Token program of Token B
+
+    // --- Validation Checks ---
+    msg!("Starting settlement validation...");
+
+    // 1. Ensure all writable accounts are actually writable.
+    if !maker_info.is_writable
+        || !taker_info.is_writable
+        || !maker_token_acc_a_info.is_writable
+        || !maker_token_acc_b_info.is_writable
+        || !taker_token_acc_a_info.is_writable
+        || !taker_token_acc_b_info.is_writable
+        || !vault_info.is_writable
+        || !payment_vault_info.is_writable
+        || !escrow_info.is_writable
+    {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 2. Verify the token programs own Token A and Token B.
+    check_token_program(mint_a_info, token_program_info)?;
+    check_token_program(mint_b_info, token_program_b_info)?;
+    // 3. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 4. Verify the provided escrow ID matches the one stored in the account.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    // 5. Verify the escrow is an arbiter escrow that has been taken.
+    let (Some(arbiter), Some(taker), Some(beneficiary)) = (
+        escrow_account.arbiter,
+        escrow_account.taker,
+        escrow_account.beneficiary,
+    ) else {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidArbiter.into());
+    };
+    if escrow_account.status != EscrowStatus::Held {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::InvalidArbiter.into());
+    }
+    // 6. Verify the maker and taker match the ones recorded in the escrow.
+    if escrow_account.maker != *maker_info.key || taker != *taker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    // 7. Verify the mint accounts match the ones recorded in the escrow.
+    if escrow_account.token_mint_a != *mint_a_info.key
+        || escrow_account.token_mint_b != *mint_b_info.key
+    {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    // 8. Verify every side is paid to its own token accounts, since the caller may be the arbiter.
+    msg!("Validating token accounts...");
+    let maker_owner = escrow_account.token_owner();
+    check_token_account(
+        maker_token_acc_a_info,
+        &maker_owner,
+        mint_a_info,
+        token_program_info,
+    )?;
+    check_token_account(
+        maker_token_acc_b_info,
+        &maker_owner,
+        mint_b_info,
+        token_program_b_info,
+    )?;
+    check_token_account(
+        taker_token_acc_a_info,
+        &beneficiary,
+        mint_a_info,
+        token_program_info,
+    )?;
+    check_token_account(
+        taker_token_acc_b_info,
+        &beneficiary,
+        mint_b_info,
+        token_program_b_info,
+    )?;
+    // 9. Verify the vaults and escrow account are derived correctly.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let payment_vault_pda = escrow_account.payment_vault_address(program_id)?;
+    if *escrow_info.key != escrow_account.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    if *vault_info.key != vault_pda || *payment_vault_info.key != payment_vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+
+    // 10. Work out how much of the trade goes ahead.
+    let release_bps = match ruling {
+        // The arbiter rules, whatever the parties have confirmed.
+        Some(release_bps) => {
+            let arbiter_info = next_account_info(account_iter)?; // The escrow's arbiter (signer)
+            if !arbiter_info.is_signer {
+                return Err(EscrowError::MissingRequiredSignature.into());
+            }
+            if *arbiter_info.key != arbiter {
+                msg!("Error: Only arbiter {} can resolve this escrow.", arbiter);
+                return Err(EscrowError::Unauthorized.into());
+            }
+            if release_bps > MAX_RELEASE_BPS {
+                msg!(
+                    "Error: Release share {} exceeds {} basis points.",
+                    release_bps,
+                    MAX_RELEASE_BPS
+                );
+                return Err(EscrowError::InvalidArbiter.into());
+            }
+            release_bps
+        }
+        // Each party that signs confirms; the trade completes once both have.
+        None => {
+            if !maker_info.is_signer && !taker_info.is_signer {
+                return Err(EscrowError::MissingRequiredSignature.into());
+            }
+            escrow_account.maker_confirmed |= maker_info.is_signer;
+            escrow_account.taker_confirmed |= taker_info.is_signer;
+            if !escrow_account.maker_confirmed || !escrow_account.taker_confirmed {
+                escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+                msg!(
+                    "Confirmation recorded. Maker confirmed: {}, taker confirmed: {}.",
+                    escrow_account.maker_confirmed,
+                    escrow_account.taker_confirmed
+                );
+                return Ok(());
+            }
+            MAX_RELEASE_BPS
+        }
+    };
+    msg!(
+        "All validations passed. Settling {} bps of the trade...",
+        release_bps
+    );
+
+    // --- Settlement Execution ---
+
+    // STEP 1: Split Token A between the taker and the maker, and close its vault.
+    let (token_a_to_taker, token_a_to_maker) = split_vault(
+        &vault_pda,
+        &[
+            Escrow::VAULT_SEED,
+            maker_info.key.as_ref(),
+            escrow_seed.as_ref(),
+            &[escrow_account.vault_bump],
+        ],
+        release_bps,
+        SplitVaultAccounts {
+            mint_info: mint_a_info,
+            vault_info,
+            share_info: taker_token_acc_a_info,
+            rest_info: maker_token_acc_a_info,
+            rent_recipient_info: maker_info,
+            token_program_info,
+        },
+    )?;
+
+    // STEP 2: Split the payment between the maker and the taker, and close its vault.
+    let (token_b_to_maker, token_b_to_taker) = split_vault(
+        &payment_vault_pda,
+        &[
+            PAYMENT_VAULT_SEED,
+            maker_info.key.as_ref(),
+            escrow_seed.as_ref(),
+            &[escrow_account.payment_vault_bump],
+        ],
+        release_bps,
+        SplitVaultAccounts {
+            mint_info: mint_b_info,
+            vault_info: payment_vault_info,
+            share_info: maker_token_acc_b_info,
+            rest_info: taker_token_acc_b_info,
+            rent_recipient_info: taker_info,
+            token_program_info: token_program_b_info,
+        },
+    )?;
+    EscrowEvent::Settled(EscrowSettled {
+        escrow: *escrow_info.key,
+        maker: *maker_info.key,
+        taker,
+        escrow_id,
+        resolved_by: ruling.map(|_| arbiter),
+        token_a_to_taker,
+        token_a_to_maker,
+        token_b_to_maker,
+        token_b_to_taker,
+    })
+    .emit();
+
+    // STEP 3: Close the escrow state account, returning its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+
+    msg!("Escrow settled and closed!");
+    msg!(
+        "   - Taker received: {} Token A and {} Token B back",
+        token_a_to_taker,
+        token_b_to_taker
+    );
+    msg!(
+        "   - Maker received: {} Token B and {} Token A back",
+        token_b_to_maker,
+        token_a_to_maker
+    );
+    Ok(())
+}
+
+/// Verifies a settlement destination is a token account of `mint_info` owned by `owner`.
+fn check_token_account(
+    token_account_info: &AccountInfo,
+    owner: &Pubkey,
+    mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+) -> ProgramResult {
+    let token_data = unpack_token_account(token_account_info, token_program_info.key)?;
+    if token_data.owner != *owner {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if token_data.mint != *mint_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    Ok(())
+}
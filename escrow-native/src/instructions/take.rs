@@ -1,37 +1,119 @@
-use crate::{error::EscrowError, state::Escrow};
+use crate::{
+    arbiter::create_payment_vault,
+    basket::{pay_basket_leg, release_basket_vault, PayLegAccounts, ReleaseLegAccounts},
+    error::EscrowError,
+    events::{EscrowEvent, EscrowFilled},
+    instructions::close_program_account,
+    instructions::config::{check_not_paused, load_config},
+    instructions::instruction::ReleaseFundsArgs,
+    state::{Escrow, EscrowStatus, ReceiveAsset},
+    token::{
+        check_token_program, create_associated_account_if_missing, gross_amount_for_net,
+        harvest_vault_fees, net_amount_after_fee, unpack_mint, unpack_token_account,
+        TokenAuthority,
+    },
+};
 use solana_program::{
     account_info::next_account_info,
     account_info::AccountInfo,
     entrypoint::ProgramResult,
     msg,
     program::{invoke, invoke_signed},
-    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM,
+    sysvar::{clock::Clock, Sysvar},
 };
-use spl_token::{
-    instruction::{close_account, transfer},
-    state::Account,
-    ID as TOKEN_PROGRAM_ID,
-};
+use spl_token_2022::instruction::{close_account, transfer_checked};
 
-/// Completes an escrow exchange by releasing funds to the respective parties.
+/// Fills all or part of an escrow by releasing funds to the respective parties.
+///
+/// The taker pays `fill_amount` of Token B and receives a pro-rated share of the
+/// Token A still on offer, at the maker's price. The remaining amounts are kept in
+/// the escrow state. Once the escrow is fully filled, the vault and escrow state accounts
+/// are closed and their rent is returned to the maker.
+///
+/// The taker passes the `offered` and `receive` amounts they quoted against; the fill fails
+/// if the maker has since repriced the escrow to give less Token A per Token B. A partial fill
+/// by another taker keeps the price, rounded in the taker's favour, so it does not invalidate
+/// the quote. The fill also fails with
+/// `SlippageExceeded` if the taker would receive less than `min_token_a_out` of Token A or pay
+/// more than `max_token_b_in` of Token B once transfer fees are counted, which bound the exact
+/// amounts and can change between the quote and the fill even when the price has not.
+///
+/// Token A and Token B may each be owned by either the SPL Token or the Token-2022 program.
+/// If a protocol fee was configured when the escrow was created, that share of `fill_amount` goes
+/// to the fee recipient before the maker is paid the rest.
+/// If Token B charges a transfer fee, the taker pays it on top of each transfer.
+/// If the taker's Token A account or the maker's Token B account does not exist yet, it is
+/// created as an associated token account at the taker's expense, so a take needs no setup
+/// transactions.
+/// For a native SOL escrow the taker instead pays `fill_amount` lamports with a system transfer,
+/// passing the maker's and taker's wallets as the Token B accounts and the System Program as
+/// the Token B program.
+///
+/// A basket escrow must be filled in one go: the taker also receives every offered basket leg and
+/// pays every requested one, less the protocol fee on each, in the same instruction. An NFT
+/// escrow is likewise only sold in a single fill.
+///
+/// A vesting escrow is also sold in a single fill, but Token A stays in the vault: the taker pays
+/// up front and becomes the escrow's beneficiary, claiming Token A with `ClaimVested` as it
+/// unlocks. The slippage limits then apply to the Token A the taker will receive in total.
+///
+/// An arbiter escrow is sold in a single fill too, and holds both sides: the taker passes the
+/// escrow's payment vault in place of the maker's Token B account, and it is created here at the
+/// taker's expense. The maker's share of the payment goes into it while Token A stays in the
+/// vault, until `ConfirmEscrow` or `Resolve` settles the trade. The protocol fee is paid at once.
+///
+/// The taker's token accounts may belong to an SPL multisig the taker is a member of; its members
+/// then sign the Token B payments. The maker's accounts must belong to the multisig the escrow
+/// recorded, if any, and the maker's Token B account must already exist in that case.
 ///
 /// Accounts expected:
 /// 0. `[signer]`       taker_info:         The account of the person taking the escrow. Must be a signer.
 /// 1. `[writable]`     maker_info:         The account of the person who initialized the escrow. Used to reclaim rent.
-/// 2. `[]`             mint_a_info:        The mint account of token A (the token originally deposited by maker).
+/// 2. `[writable]`     mint_a_info:        The mint account of token A (the token originally deposited by maker). Receives withheld transfer fees.
 /// 3. `[]`             mint_b_info:        The mint account of token B (the token the maker wants to receive).
-/// 4. `[writable]`     maker_ata_b_info:   The maker's SPL Token account holding token B, where they receive funds.
-/// 5. `[writable]`     taker_ata_a_info:   The taker's SPL Token account holding token A, where they receive funds.
-/// 6. `[writable]`     taker_ata_b_info:   The taker's SPL Token account holding token B, from which they pay.
+/// 4. `[writable]`     maker_ata_b_info:   The maker's token account holding token B (or wallet for SOL), where they receive funds. The payment vault PDA for an arbiter escrow.
+/// 5. `[writable]`     taker_ata_a_info:   The taker's token account holding token A, where they receive funds.
+/// 6. `[writable]`     taker_ata_b_info:   The taker's token account holding token B (or wallet for SOL), from which they pay.
 /// 7. `[writable]`     vault_info:         The PDA token account where token A was deposited. This account will be closed.
 /// 8. `[writable]`     escrow_info:        The PDA account storing the escrow state. This account will be closed.
-/// 9. `[]`             token_program_info: The SPL Token Program account.
+/// 9. `[]`             token_program_info: The token program that owns mint A (SPL Token or Token-2022).
+/// 10. `[]`            token_program_b_info: The token program that owns mint B, or the System Program for SOL.
+/// 11. `[]`            config_info:        The program config PDA, which need not exist yet. Also checked for a pause.
+/// 12. `[]`            system_program_info: The System Program, used to create missing token accounts.
+/// 13. `[]`            associated_token_program_info: The Associated Token Account program.
+/// 14. `[writable]`    fee_account_info:   The fee recipient's Token B account (or wallet for SOL). Only passed when a protocol fee is charged.
+///
+/// Then, for each offered basket leg:
+/// - `[writable]`   mint_info:          The mint of the leg, which receives withheld transfer fees.
+/// - `[writable]`   vault_info:         The leg's vault PDA. This account will be closed.
+/// - `[writable]`   taker_token_info:   The taker's token account for the leg.
+///
+/// Then, for each requested basket leg:
+/// - `[]`           mint_info:          The mint of the leg.
+/// - `[writable]`   taker_token_info:   The taker's token account the leg is paid from.
+/// - `[writable]`   maker_token_info:   The maker's token account for the leg.
+/// - `[writable]`   fee_account_info:   The fee recipient's token account for the leg. Only passed when a protocol fee is charged on it.
+///
+/// Then, only if the taker's token accounts belong to an SPL multisig:
+/// - `[]`           multisig_info:      The multisig account, owned by Token B's token program.
+/// - `[signer]`     signer_info:        At least `m` of the multisig's members, one account each.
 pub fn release_funds(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    escrow_id: u64,
+    args: ReleaseFundsArgs,
 ) -> ProgramResult {
+    let ReleaseFundsArgs {
+        escrow_id,
+        fill_amount,
+        expected_offered,
+        expected_receive,
+        min_token_a_out,
+        max_token_b_in,
+    } = args;
     // Create an iterator for the accounts array to process them in order.
     let account_iter = &mut accounts.iter();
 
@@ -39,12 +121,16 @@ pub fn release_funds(
     let maker_info = next_account_info(account_iter)?; // Maker's wallet account (receives rent back)
     let mint_a_info = next_account_info(account_iter)?; // Mint for Token A (deposited by maker)
     let mint_b_info = next_account_info(account_iter)?; // Mint for Token B (desired by maker, paid by taker)
-    let maker_ata_b_info = next_account_info(account_iter)?; // Maker's SPL Token account for Token B
-    let taker_ata_a_info = next_account_info(account_iter)?; // Taker's SPL Token account for Token A
-    let taker_ata_b_info = next_account_info(account_iter)?; // Taker's SPL Token account for Token B
+    let maker_ata_b_info = next_account_info(account_iter)?; // Maker's token account for Token B
+    let taker_ata_a_info = next_account_info(account_iter)?; // Taker's token account for Token A
+    let taker_ata_b_info = next_account_info(account_iter)?; // Taker's token account for Token B
     let vault_info = next_account_info(account_iter)?; // Program's vault holding Token A
     let escrow_info = next_account_info(account_iter)?; // Escrow state account (PDA)
-    let token_program_info = next_account_info(account_iter)?; // SPL Token program account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+    let token_program_b_info = next_account_info(account_iter)?; // Token program of Token B
+    let config_info = next_account_info(account_iter)?; // Program config PDA
+    let system_program_info = next_account_info(account_iter)?; // System Program, for missing token accounts
+    let associated_token_program_info = next_account_info(account_iter)?; // Associated Token Account program
 
     // --- Validation Checks ---
 
@@ -62,135 +148,474 @@ pub fn release_funds(
     {
         return Err(EscrowError::InvalidAccountData.into());
     }
-    // Verify the SPL Token program ID to ensure correct interaction with the token program.
-    if *token_program_info.key != TOKEN_PROGRAM_ID {
+    // Verify the programs used to create missing token accounts.
+    if *system_program_info.key != SYSTEM_PROGRAM
+        || *associated_token_program_info.key != spl_associated_token_account::ID
+    {
         return Err(EscrowError::IncorrectProgramId.into());
     }
+    // Verify mint A is owned by the token program provided for it.
+    check_token_program(mint_a_info, token_program_info)?;
     // Verify the escrow account is owned by the current program to ensure its authenticity.
     if escrow_info.owner != program_id {
         return Err(EscrowError::InvalidAccountOwner.into());
     }
     msg!("Unpacking escrow account...");
     // Unpack the escrow account data to access its state.
-    let escrow_acc = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())
-        .expect("unable to unpack the escrow account");
+    let mut escrow_acc = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
     // Verify the provided escrow_id matches the one stored in the escrow account.
     if escrow_acc.escrow_id != escrow_id {
         return Err(EscrowError::InvalidEscrowId.into());
     }
+    // Verify the escrow account is the PDA recreated from its stored bump.
+    if *escrow_info.key != escrow_acc.address(program_id)? {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // Nothing can be taken while the admin has paused the program.
+    let config = load_config(program_id, config_info, escrow_acc.config_bump)?;
+    check_not_paused(config.as_ref())?;
+    // Verify the escrow is still open.
+    if escrow_acc.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_acc.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
     // Verify that the mint A account provided matches the one recorded in the escrow.
     if escrow_acc.token_mint_a != *mint_a_info.key {
         return Err(EscrowError::InvalidMint.into());
     }
+    // Verify that the mint B account provided matches the one recorded in the escrow.
+    if escrow_acc.token_mint_b != *mint_b_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
     // Verify that the maker account provided matches the maker recorded in the escrow.
     if escrow_acc.maker != *maker_info.key {
         return Err(EscrowError::InvalidAccountData.into());
     }
-    msg!("Validating taker's Token B account...");
-    // Unpack the taker's Token B account data.
-    let taker_token_b_data = Account::unpack(&taker_ata_b_info.data.borrow())?;
-    // Verify taker's Token B account is owned by the taker.
-    if taker_token_b_data.owner != *taker_info.key {
-        return Err(EscrowError::InvalidAccountOwner.into());
+    // Verify the taker is allowed to take a private escrow.
+    if let Some(allowed_taker) = escrow_acc.allowed_taker {
+        if allowed_taker != *taker_info.key {
+            msg!("Error: Escrow is restricted to taker {}.", allowed_taker);
+            return Err(EscrowError::Unauthorized.into());
+        }
     }
-    // Verify taker's Token B account holds the correct mint.
-    if taker_token_b_data.mint != *mint_b_info.key {
-        return Err(EscrowError::InvalidMint.into());
+    // Verify the escrow has not passed its deadline.
+    if let Some(expires_at) = escrow_acc.expires_at {
+        if Clock::get()?.unix_timestamp >= expires_at {
+            msg!("Error: Escrow expired at {}.", expires_at);
+            return Err(EscrowError::EscrowExpired.into());
+        }
     }
-    // Get the amount of Token B the maker expects to receive.
-    let receive_amount = escrow_acc.receive;
-    // Verify taker has sufficient Token B balance to fulfill the exchange.
-    if taker_token_b_data.amount < receive_amount {
-        return Err(EscrowError::InsufficientFunds.into());
+    // Verify the maker has not repriced the escrow against the taker since they quoted it.
+    // Fills by other takers shrink both amounts at the same price, so only the price is
+    // compared. Their Token A is rounded down, which leaves the rest of the offer a little
+    // richer, so the escrow's `offered / receive` need only be at least the quoted
+    // `expected_offered / expected_receive`.
+    if expected_offered == 0 || expected_receive == 0 {
+        return Err(EscrowError::InvalidAmount.into());
     }
-    msg!("Validating maker's Token B account...");
-    // Unpack the maker's Token B account data.
-    let maker_token_b_data = Account::unpack(&maker_ata_b_info.data.borrow())?;
-
-    // Verify maker's Token B account is owned by the maker.
-    if maker_token_b_data.owner != *maker_info.key {
-        return Err(EscrowError::InvalidAccountData.into());
+    if (escrow_acc.offered as u128) * (expected_receive as u128)
+        < (expected_offered as u128) * (escrow_acc.receive as u128)
+    {
+        msg!(
+            "Error: Escrow now offers {} Token A for {} Token B.",
+            escrow_acc.offered,
+            escrow_acc.receive
+        );
+        return Err(EscrowError::EscrowTermsChanged.into());
     }
-    // Verify maker's Token B account has the correct mint.
-    if maker_token_b_data.mint != *mint_b_info.key {
-        return Err(EscrowError::InvalidMint.into());
+    // Verify the fill amount is positive and does not exceed what the maker still expects.
+    if fill_amount == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    if fill_amount > escrow_acc.receive {
+        msg!(
+            "Error: Fill amount {} exceeds the remaining {} Token B.",
+            fill_amount,
+            escrow_acc.receive
+        );
+        return Err(EscrowError::FillExceedsRemaining.into());
+    }
+    // A basket settles every leg at once, so it cannot be partially filled.
+    if escrow_acc.is_basket() && fill_amount != escrow_acc.receive {
+        msg!("Error: A basket escrow must be filled in full.");
+        return Err(EscrowError::InvalidBasket.into());
+    }
+    // An NFT cannot be split, so it is only sold for the full price.
+    if escrow_acc.is_nft() && fill_amount != escrow_acc.receive {
+        msg!("Error: An NFT escrow must be filled in full.");
+        return Err(EscrowError::InvalidNft.into());
+    }
+    // A vesting escrow has a single beneficiary, so it is only sold in full.
+    if escrow_acc.is_vesting() && fill_amount != escrow_acc.receive {
+        msg!("Error: A vesting escrow must be filled in full.");
+        return Err(EscrowError::InvalidVesting.into());
+    }
+    // An arbiter escrow settles between one maker and one taker, so it is only sold in full.
+    if escrow_acc.is_arbitrated() && fill_amount != escrow_acc.receive {
+        msg!("Error: An arbiter escrow must be filled in full.");
+        return Err(EscrowError::InvalidArbiter.into());
+    }
+    // The amount of Token B paid for this fill, of which the protocol fee, at the rate recorded
+    // when the escrow was created, goes to the fee recipient.
+    let receive_amount = fill_amount;
+    let fee_amount = match &config {
+        Some(_) => escrow_acc.fee_for(receive_amount)?,
+        None => 0,
+    };
+    let maker_amount = receive_amount
+        .checked_sub(fee_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    let fee_account_info = match (&config, fee_amount) {
+        // Fee recipient's Token B account, only passed if a fee is charged
+        (Some(config), 1..) => Some((config.fee_recipient, next_account_info(account_iter)?)),
+        _ => None,
+    };
+    // The accounts of an SPL multisig owning the taker's tokens, if any, follow the basket legs:
+    // three accounts per offered leg, and three per requested leg plus its fee account.
+    let mut basket_account_count = escrow_acc.basket_offered.len() * 3;
+    for leg in &escrow_acc.basket_requested {
+        let leg_fee_amount = match &config {
+            Some(_) => escrow_acc.fee_for(leg.amount)?,
+            None => 0,
+        };
+        basket_account_count += if leg_fee_amount > 0 { 4 } else { 3 };
     }
+    let multisig_accounts = account_iter
+        .as_slice()
+        .get(basket_account_count..)
+        .unwrap_or_default();
+    let taker_authority =
+        TokenAuthority::resolve(taker_info, multisig_accounts, token_program_b_info.key)?;
+    let (maker_payment, fee_payment) = match escrow_acc.receive_asset {
+        ReceiveAsset::Token => {
+            // Verify mint B is owned by the token program provided for it.
+            check_token_program(mint_b_info, token_program_b_info)?;
+            msg!("Validating taker's Token B account...");
+            // Unpack the taker's Token B account data.
+            let taker_token_b_data =
+                unpack_token_account(taker_ata_b_info, token_program_b_info.key)?;
+            // Verify taker's Token B account is owned by the taker (or the taker's multisig).
+            if taker_token_b_data.owner != *taker_authority.key() {
+                return Err(EscrowError::InvalidAccountOwner.into());
+            }
+            // Verify taker's Token B account holds the correct mint.
+            if taker_token_b_data.mint != *mint_b_info.key {
+                return Err(EscrowError::InvalidMint.into());
+            }
+            // The amounts of Token B the taker pays, including any Token-2022 transfer fees.
+            let maker_payment = gross_amount_for_net(mint_b_info, maker_amount)?;
+            let fee_payment = gross_amount_for_net(mint_b_info, fee_amount)?;
+            // Verify taker has sufficient Token B balance to fulfill the exchange.
+            let payment_amount = maker_payment
+                .checked_add(fee_payment)
+                .ok_or(EscrowError::ArithmeticOverflow)?;
+            if taker_token_b_data.amount < payment_amount {
+                return Err(EscrowError::InsufficientFunds.into());
+            }
+            // Verify the fee account is the fee recipient's Token B account.
+            if let Some((fee_recipient, fee_account_info)) = fee_account_info {
+                let fee_token_b_data =
+                    unpack_token_account(fee_account_info, token_program_b_info.key)?;
+                if fee_token_b_data.owner != fee_recipient {
+                    return Err(EscrowError::InvalidAccountOwner.into());
+                }
+                if fee_token_b_data.mint != *mint_b_info.key {
+                    return Err(EscrowError::InvalidMint.into());
+                }
+            }
+            if escrow_acc.is_arbitrated() {
+                // Hold the maker's payment in the escrow's payment vault until it settles.
+                create_payment_vault(
+                    program_id,
+                    &mut escrow_acc,
+                    taker_info,
+                    mint_b_info,
+                    maker_ata_b_info,
+                    system_program_info,
+                    token_program_b_info,
+                )?;
+            } else {
+                // Create the maker's Token B associated token account if they do not have one yet.
+                create_associated_account_if_missing(
+                    taker_info,
+                    maker_info,
+                    mint_b_info,
+                    maker_ata_b_info,
+                    system_program_info,
+                    token_program_b_info,
+                    associated_token_program_info,
+                )?;
+                msg!("Validating maker's Token B account...");
+                // Unpack the maker's Token B account data.
+                let maker_token_b_data =
+                    unpack_token_account(maker_ata_b_info, token_program_b_info.key)?;
+
+                // Verify maker's Token B account is owned by the maker (or the maker's multisig).
+                if maker_token_b_data.owner != escrow_acc.token_owner() {
+                    return Err(EscrowError::InvalidAccountData.into());
+                }
+                // Verify maker's Token B account has the correct mint.
+                if maker_token_b_data.mint != *mint_b_info.key {
+                    return Err(EscrowError::InvalidMint.into());
+                }
+            }
+            (maker_payment, fee_payment)
+        }
+        ReceiveAsset::NativeSol => {
+            // Lamports move between the wallets themselves, through the System Program.
+            if *token_program_b_info.key != SYSTEM_PROGRAM {
+                return Err(EscrowError::IncorrectProgramId.into());
+            }
+            if maker_ata_b_info.key != maker_info.key || taker_ata_b_info.key != taker_info.key {
+                msg!("Error: A native SOL escrow is paid from the taker's wallet to the maker's.");
+                return Err(EscrowError::InvalidAccountData.into());
+            }
+            // Verify taker has sufficient lamports to fulfill the exchange and stay rent exempt.
+            let spendable_lamports = taker_info
+                .lamports()
+                .saturating_sub(Rent::get()?.minimum_balance(taker_info.data_len()));
+            if spendable_lamports < receive_amount {
+                return Err(EscrowError::InsufficientFunds.into());
+            }
+            // Verify the fee account is the fee recipient's wallet.
+            if let Some((fee_recipient, fee_account_info)) = fee_account_info {
+                if *fee_account_info.key != fee_recipient {
+                    return Err(EscrowError::InvalidAccountData.into());
+                }
+            }
+            (maker_amount, fee_amount)
+        }
+    };
+    // Create the taker's Token A associated token account if they do not have one yet.
+    create_associated_account_if_missing(
+        taker_info,
+        taker_authority.owner_info,
+        mint_a_info,
+        taker_ata_a_info,
+        system_program_info,
+        token_program_info,
+        associated_token_program_info,
+    )?;
     msg!("Validating taker's Token A account...");
     // Unpack the taker's Token A account data.
-    let takers_token_a_data = Account::unpack(&taker_ata_a_info.data.borrow())?;
-    // Verify taker's Token A account is owned by the taker.
-    if takers_token_a_data.owner != *taker_info.key {
+    let takers_token_a_data = unpack_token_account(taker_ata_a_info, token_program_info.key)?;
+    // Verify taker's Token A account is owned by the taker (or the taker's multisig).
+    if takers_token_a_data.owner != *taker_authority.key() {
         return Err(EscrowError::InvalidAccountOwner.into());
     }
     // Verify taker's Token A account has the correct mint.
     if takers_token_a_data.mint != *mint_a_info.key {
         return Err(EscrowError::InvalidMint.into());
     }
-    // Verify the vault account is owned by the SPL Token program.
-    if *vault_info.owner != TOKEN_PROGRAM_ID {
-        return Err(EscrowError::InvalidAccountOwner.into());
-    }
     msg!("Validating vault...");
-    // Unpack the vault account data.
-    let vault_data = Account::unpack(&vault_info.data.borrow())?;
+    // Unpack the vault account data, verifying it is owned by Token A's token program.
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
     // Verify vault has the correct mint (Token A).
     if vault_data.mint != *mint_a_info.key {
         return Err(EscrowError::InvalidMint.into());
     }
 
-    // Derive the vault PDA to verify its ownership and generate signer seeds.
+    // Recreate the vault PDA from its stored bump to verify its ownership and sign for it.
     let escrow_seed = escrow_id.to_le_bytes();
-    let vault_seeds: &[&[u8]] = &[b"vault", maker_info.key.as_ref(), escrow_seed.as_ref()];
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(vault_seeds, &program_id);
+    let vault_pda = escrow_acc.vault_address(program_id)?;
+    let vault_bump = escrow_acc.vault_bump;
     // Verify the vault token account's authority matches the derived vault PDA.
     if vault_data.owner != vault_pda {
         return Err(EscrowError::InvalidAccountOwner.into());
     }
 
+    // Pro-rate the Token A released for this fill at the maker's price, using the
+    // recorded offer rather than the vault balance, which anyone can top up.
+    // The final fill takes everything still on offer.
+    let is_final_fill = fill_amount == escrow_acc.receive;
+    let deposit_amount = if is_final_fill {
+        escrow_acc.offered
+    } else {
+        (escrow_acc.offered as u128)
+            .checked_mul(fill_amount as u128)
+            .and_then(|amount| amount.checked_div(escrow_acc.receive as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(EscrowError::ArithmeticOverflow)?
+    };
+    // Reject fills too small to release any Token A.
+    if deposit_amount == 0 {
+        msg!("Error: Fill amount is too small to release any Token A.");
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    // Verify the vault holds enough Token A for this fill.
+    if vault_data.amount < deposit_amount {
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    // The final fill empties the vault so it can be closed; tokens sent to the vault
+    // outside the escrow are not part of the offer but go to the taker along with it.
+    let vault_release_amount = if is_final_fill {
+        vault_data.amount
+    } else {
+        deposit_amount
+    };
+    // Verify the fill is within the taker's limits, counting the transfer fees on both tokens.
+    let token_a_out = net_amount_after_fee(mint_a_info, vault_release_amount)?;
+    let token_b_in = maker_payment
+        .checked_add(fee_payment)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    if token_a_out < min_token_a_out || token_b_in > max_token_b_in {
+        msg!(
+            "Error: Fill would release {} Token A for {} Token B; the taker asked for at least {} for at most {}.",
+            token_a_out,
+            token_b_in,
+            min_token_a_out,
+            max_token_b_in
+        );
+        return Err(EscrowError::SlippageExceeded.into());
+    }
+
     msg!("All validations passed. Executing exchange...");
 
     // --- Exchange Execution ---
 
-    // STEP 1: Transfer Token B from taker to maker.
-    // The taker pays `receive_amount` of Token B to the maker.
-    msg!(
-        "Transferring {} Token B from taker to maker...",
-        receive_amount
-    );
-    let transfer_b_instruction = transfer(
-        &TOKEN_PROGRAM_ID,     // Token program ID
-        &taker_ata_b_info.key, // Source: Taker's Token B account
-        &maker_ata_b_info.key, // Destination: Maker's Token B account
-        &taker_info.key,       // Authority: Taker
-        &[taker_info.key],     // Signers: Taker
-        receive_amount,        // Amount to transfer
-    )?;
-    // Invoke the transfer instruction.
-    invoke(
-        &transfer_b_instruction,
-        &[
-            token_program_info.clone(), // Token program
-            taker_ata_b_info.clone(),   // Taker's Token B account
-            maker_ata_b_info.clone(),   // Maker's Token B account
-            taker_info.clone(),         // Taker (signer)
-        ],
-    )?;
-    msg!("Token B transferred successfully.");
+    // STEP 1: Pay the protocol fee, then the maker, for this fill.
+    match escrow_acc.receive_asset {
+        // The taker pays `fee_payment` and `maker_payment` of Token B so the fee recipient
+        // nets `fee_amount` and the maker nets the rest of `receive_amount`.
+        ReceiveAsset::Token => {
+            let mint_b_decimals = unpack_mint(mint_b_info)?.decimals;
+            if let Some((_, fee_account_info)) = fee_account_info {
+                msg!(
+                    "Transferring {} Token B protocol fee from taker...",
+                    fee_payment
+                );
+                taker_authority.transfer_checked(
+                    token_program_b_info,
+                    taker_ata_b_info,
+                    mint_b_info,
+                    fee_account_info,
+                    fee_payment,
+                    mint_b_decimals,
+                )?;
+            }
+            msg!(
+                "Transferring {} Token B from taker to maker...",
+                maker_payment
+            );
+            // Signed by the taker, or by the members of the taker's multisig.
+            taker_authority.transfer_checked(
+                token_program_b_info, // Token program of Token B
+                taker_ata_b_info,     // Source: Taker's Token B account
+                mint_b_info,          // Mint of Token B
+                maker_ata_b_info,     // Destination: Maker's Token B account
+                maker_payment,        // Amount to transfer
+                mint_b_decimals,      // Decimals of Token B
+            )?;
+            msg!("Token B transferred successfully.");
+        }
+        // The taker pays `receive_amount` lamports straight to the fee recipient's and maker's wallets.
+        ReceiveAsset::NativeSol => {
+            if let Some((_, fee_account_info)) = fee_account_info {
+                msg!(
+                    "Transferring {} lamports protocol fee from taker...",
+                    fee_payment
+                );
+                invoke(
+                    &system_instruction::transfer(
+                        taker_info.key,
+                        fee_account_info.key,
+                        fee_payment,
+                    ),
+                    &[
+                        taker_info.clone(),
+                        fee_account_info.clone(),
+                        token_program_b_info.clone(),
+                    ],
+                )?;
+            }
+            msg!(
+                "Transferring {} lamports from taker to maker...",
+                maker_payment
+            );
+            invoke(
+                &system_instruction::transfer(taker_info.key, maker_info.key, maker_payment),
+                &[
+                    taker_info.clone(),           // Taker (signer)
+                    maker_info.clone(),           // Maker's wallet
+                    token_program_b_info.clone(), // System Program
+                ],
+            )?;
+            msg!("SOL transferred successfully.");
+        }
+    }
+
+    // A vesting escrow keeps Token A in the vault, to be claimed by the taker as it unlocks.
+    if let Some(vesting) = escrow_acc.vesting {
+        escrow_acc.receive = 0;
+        escrow_acc.status = EscrowStatus::Vesting;
+        escrow_acc.beneficiary = Some(*taker_authority.key());
+        escrow_acc.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        EscrowEvent::Filled(EscrowFilled {
+            escrow: *escrow_info.key,
+            maker: escrow_acc.maker,
+            taker: *taker_info.key,
+            escrow_id,
+            token_a_released: 0,
+            token_b_paid: receive_amount,
+            fee_paid: fee_amount,
+            remaining_offered: 0,
+            remaining_receive: 0,
+        })
+        .emit();
+        msg!("Vesting escrow taken!");
+        msg!(
+            "   - Taker vests: {} Token A from {} to {}",
+            escrow_acc.offered,
+            vesting.start,
+            vesting.end
+        );
+        msg!("   - Maker received: {} Token B", receive_amount);
+        return Ok(());
+    }
+
+    // An arbiter escrow holds Token A and the payment until the parties or the arbiter settle it.
+    if escrow_acc.is_arbitrated() {
+        escrow_acc.receive = 0;
+        escrow_acc.status = EscrowStatus::Held;
+        escrow_acc.beneficiary = Some(*taker_authority.key());
+        escrow_acc.taker = Some(*taker_info.key);
+        escrow_acc.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        EscrowEvent::Filled(EscrowFilled {
+            escrow: *escrow_info.key,
+            maker: escrow_acc.maker,
+            taker: *taker_info.key,
+            escrow_id,
+            token_a_released: 0,
+            token_b_paid: receive_amount,
+            fee_paid: fee_amount,
+            remaining_offered: 0,
+            remaining_receive: 0,
+        })
+        .emit();
+        msg!("Arbiter escrow taken!");
+        msg!(
+            "   - Held until settled: {} Token A and {} Token B",
+            escrow_acc.offered,
+            maker_payment
+        );
+        return Ok(());
+    }
 
     // STEP 2: Transfer Token A from vault to taker.
-    // The amount of Token A in the vault is transferred to the taker.
-    let deposit_amount = vault_data.amount; // Get the total amount of Token A held in the vault.
+    // The pro-rated amount of Token A is transferred to the taker.
     msg!(
         "Transferring {} Token A from vault to taker...",
-        deposit_amount
+        vault_release_amount
     );
-    let transfer_a_instrcution = transfer(
-        &TOKEN_PROGRAM_ID,     // Token program ID
-        &vault_info.key,       // Source: Vault (holding Token A)
-        &taker_ata_a_info.key, // Destination: Taker's Token A account
-        &vault_pda,            // Authority: Vault PDA
-        &[&vault_pda],         // Signers: Vault PDA (program signed)
-        deposit_amount,        // Amount to transfer
+    let transfer_a_instrcution = transfer_checked(
+        token_program_info.key,             // Token program of Token A
+        vault_info.key,                     // Source: Vault (holding Token A)
+        mint_a_info.key,                    // Mint of Token A
+        taker_ata_a_info.key,               // Destination: Taker's Token A account
+        &vault_pda,                         // Authority: Vault PDA
+        &[&vault_pda],                      // Signers: Vault PDA (program signed)
+        vault_release_amount,               // Amount to transfer
+        unpack_mint(mint_a_info)?.decimals, // Decimals of Token A
     )?;
     // Define the signer seeds for the vault PDA.
     let vault_signer_seeds: &[&[&[u8]]] = &[&[
@@ -205,6 +630,7 @@ pub fn release_funds(
         &transfer_a_instrcution,
         &[
             vault_info.clone(),         // Vault account
+            mint_a_info.clone(),        // Mint of Token A
             taker_ata_a_info.clone(),   // Taker's Token A account
             token_program_info.clone(), // Token program
         ],
@@ -212,15 +638,103 @@ pub fn release_funds(
     )?;
     msg!("Token A transferred successfully.");
 
-    // STEP 3: Close the vault account to reclaim rent.
+    // STEP 3: Settle the other legs of a basket escrow.
+    for (index, leg) in escrow_acc.basket_offered.iter().enumerate() {
+        let leg_mint_info = next_account_info(account_iter)?;
+        let leg_vault_info = next_account_info(account_iter)?;
+        let taker_leg_info = next_account_info(account_iter)?;
+        if *leg_mint_info.key != leg.mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        release_basket_vault(
+            program_id,
+            &escrow_acc,
+            index as u8,
+            taker_authority.key(),
+            ReleaseLegAccounts {
+                maker_info,
+                mint_info: leg_mint_info,
+                vault_info: leg_vault_info,
+                destination_info: taker_leg_info,
+                token_program_info,
+            },
+        )?;
+    }
+    for leg in &escrow_acc.basket_requested {
+        let leg_mint_info = next_account_info(account_iter)?;
+        let taker_leg_info = next_account_info(account_iter)?;
+        let maker_leg_info = next_account_info(account_iter)?;
+        if *leg_mint_info.key != leg.mint {
+            return Err(EscrowError::InvalidMint.into());
+        }
+        let leg_fee_amount = match &config {
+            Some(_) => escrow_acc.fee_for(leg.amount)?,
+            None => 0,
+        };
+        let leg_fee_account_info = match (&config, leg_fee_amount) {
+            (Some(config), 1..) => Some((config.fee_recipient, next_account_info(account_iter)?)),
+            _ => None,
+        };
+        pay_basket_leg(
+            leg.amount,
+            leg_fee_amount,
+            &escrow_acc.token_owner(),
+            leg_fee_account_info,
+            PayLegAccounts {
+                taker_authority: &taker_authority,
+                mint_info: leg_mint_info,
+                taker_token_info: taker_leg_info,
+                maker_token_info: maker_leg_info,
+                token_program_info: token_program_b_info,
+            },
+        )?;
+    }
+
+    // STEP 4: Record the remaining amounts in the escrow state.
+    escrow_acc.offered = escrow_acc
+        .offered
+        .checked_sub(deposit_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    escrow_acc.receive = escrow_acc
+        .receive
+        .checked_sub(receive_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    EscrowEvent::Filled(EscrowFilled {
+        escrow: *escrow_info.key,
+        maker: escrow_acc.maker,
+        taker: *taker_info.key,
+        escrow_id,
+        token_a_released: vault_release_amount,
+        token_b_paid: receive_amount,
+        fee_paid: fee_amount,
+        remaining_offered: escrow_acc.offered,
+        remaining_receive: escrow_acc.receive,
+    })
+    .emit();
+    if !is_final_fill {
+        escrow_acc.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+        msg!("Escrow partially filled!");
+        msg!("   - Taker received: {} Token A", deposit_amount);
+        msg!("   - Maker received: {} Token B", receive_amount);
+        msg!(
+            "   - Remaining: {} Token A for {} Token B",
+            escrow_acc.offered,
+            escrow_acc.receive
+        );
+        return Ok(());
+    }
+
+    // STEP 5: Close the vault account to reclaim rent.
+    // Transfer fees withheld in a Token-2022 vault must be harvested before it can be closed.
+    harvest_vault_fees(token_program_info, mint_a_info, vault_info)?;
     // The rent collected for the vault account is returned to the maker.
     msg!("Closing vault account and reclaiming rent...");
     let close_vault_instrution = close_account(
-        &TOKEN_PROGRAM_ID, // Token program ID
-        &vault_info.key,   // Account to close: Vault
-        &maker_info.key,   // Destination for rent: Maker's wallet
-        &vault_pda,        // Authority: Vault PDA
-        &[&vault_pda],     // Signers: Vault PDA (program signed)
+        token_program_info.key, // Token program of Token A
+        vault_info.key,         // Account to close: Vault
+        maker_info.key,         // Destination for rent: Maker's wallet
+        &vault_pda,             // Authority: Vault PDA
+        &[&vault_pda],          // Signers: Vault PDA (program signed)
     )?;
     // Invoke the close account instruction with the vault PDA as signer.
     invoke_signed(
@@ -234,12 +748,21 @@ pub fn release_funds(
     )?;
     msg!("Vault closed.");
 
-    // The escrow account is also implicitly closed and its rent returned to the maker
-    // since it is writable and its data has been consumed.
+    // STEP 6: Close the escrow state account and return its rent to the maker.
+    // The account is handed back to the System Program so the same `escrow_id` can be reused.
+    msg!("Closing escrow state account and reclaiming rent...");
+    close_program_account(escrow_info, maker_info)?;
+    msg!("Escrow account closed.");
 
     // --- Final Logging ---
     msg!("Escrow exchange completed successfully!");
-    msg!("   - Taker received: {} Token A", deposit_amount);
+    msg!("   - Taker received: {} Token A", vault_release_amount);
     msg!("   - Maker received: {} Token B", receive_amount);
+    for leg in &escrow_acc.basket_offered {
+        msg!("   - Taker received: {} of {}", leg.amount, leg.mint);
+    }
+    for leg in &escrow_acc.basket_requested {
+        msg!("   - Maker received: {} of {}", leg.amount, leg.mint);
+    }
     Ok(())
 }
@@ -0,0 +1,220 @@
+use crate::{
+    error::EscrowError,
+    state::{Escrow, EscrowStatus},
+    token::{check_token_program, unpack_mint, unpack_token_account, TokenAuthority},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    pubkey::Pubkey,
+};
+use spl_token_2022::instruction::transfer_checked;
+
+/// Updates the terms of an open escrow in place.
+///
+/// The maker can change the amount of Token B they expect, add Token A to the vault,
+/// or withdraw part of the Token A still on offer, without cancelling and re-creating
+/// the escrow. `receive` is left unchanged unless `receive_amount` is given, so topping
+/// up or withdrawing alone also changes the price. Takers that quoted the old terms are
+/// rejected by `release_funds`.
+///
+/// The escrow's operator may reprice it in place of the maker, but cannot move Token A.
+/// If the maker's tokens belong to an SPL multisig, its members sign a top up and a
+/// withdrawal can only go back to the multisig.
+///
+/// Accounts expected:
+///
+/// 0. `[signer]` `maker_info`: The maker of the escrow. Signs unless the operator does.
+/// 1. `[]` `mint_a_info`: The mint account of Token A.
+/// 2. `[writable]` `maker_token_acc_a_info`: The maker's Token A account, funding a top up or receiving a withdrawal.
+/// 3. `[writable]` `escrow_info`: The escrow state account.
+/// 4. `[writable]` `vault_info`: The token vault account holding Token A.
+/// 5. `[]` `token_program_info`: The token program that owns Token A (SPL Token or Token-2022).
+/// 6. `[signer]` `operator_info`: The escrow's operator. Only passed when the maker does not sign.
+///
+/// Then, only for a top up from an SPL multisig's account:
+/// - `[]` `multisig_info`: The multisig account, owned by Token A's token program.
+/// - `[signer]` `signer_info`: At least `m` of the multisig's members, one account each.
+pub fn update_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    escrow_id: u64,
+    receive_amount: Option<u64>,
+    deposit_amount: u64,
+    withdraw_amount: u64,
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+
+    let maker_info = next_account_info(account_iter)?; // Maker's account
+    let mint_a_info = next_account_info(account_iter)?; // Mint account for Token A
+    let maker_token_acc_a_info = next_account_info(account_iter)?; // Maker's Token A account
+    let escrow_info = next_account_info(account_iter)?; // Escrow state account
+    let vault_info = next_account_info(account_iter)?; // Token vault account
+    let token_program_info = next_account_info(account_iter)?; // Token program of Token A
+
+    // The operator signs in place of the maker, so it is only passed when the maker does not.
+    let authority_info = if maker_info.is_signer {
+        maker_info
+    } else {
+        next_account_info(account_iter)? // Escrow operator
+    };
+
+    // --- Validation Checks ---
+    msg!("Starting escrow update validation...");
+
+    // 1. Ensure the maker or the operator has signed the transaction.
+    if !authority_info.is_signer {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+    // 2. Ensure all writable accounts are actually writable.
+    if !maker_token_acc_a_info.is_writable || !escrow_info.is_writable || !vault_info.is_writable {
+        return Err(EscrowError::InvalidAccountData.into());
+    }
+    // 3. Verify the requested changes.
+    if receive_amount == Some(0) || (deposit_amount > 0 && withdraw_amount > 0) {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    if receive_amount.is_none() && deposit_amount == 0 && withdraw_amount == 0 {
+        msg!("Error: Nothing to update.");
+        return Err(EscrowError::InvalidAmount.into());
+    }
+    // 4. Verify the token program owns Token A.
+    check_token_program(mint_a_info, token_program_info)?;
+    // 5. Verify the escrow account is owned by this program.
+    if *escrow_info.owner != *program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+
+    msg!("Unpacking escrow account...");
+    let mut escrow_account = Escrow::unpack_the_slice_data(&escrow_info.data.borrow())?;
+
+    // 6. Verify the escrow ID, status, maker and mint.
+    if escrow_account.escrow_id != escrow_id {
+        return Err(EscrowError::InvalidEscrowId.into());
+    }
+    if escrow_account.status != EscrowStatus::Open {
+        msg!("Error: Escrow is {:?}.", escrow_account.status);
+        return Err(EscrowError::EscrowNotOpen.into());
+    }
+    if escrow_account.maker != *maker_info.key {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if !escrow_account.can_manage(authority_info.key) {
+        return Err(EscrowError::Unauthorized.into());
+    }
+    // The operator may only reprice; moving Token A in or out takes the maker.
+    if authority_info.key != maker_info.key && (deposit_amount > 0 || withdraw_amount > 0) {
+        msg!("Error: Only the maker can deposit or withdraw Token A.");
+        return Err(EscrowError::Unauthorized.into());
+    }
+    if escrow_account.token_mint_a != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    // 7. Verify the maker's Token A account and the vault.
+    let maker_token_a_data = unpack_token_account(maker_token_acc_a_info, token_program_info.key)?;
+    if maker_token_a_data.owner != escrow_account.token_owner() {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    if maker_token_a_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    let vault_data = unpack_token_account(vault_info, token_program_info.key)?;
+    if vault_data.mint != *mint_a_info.key {
+        return Err(EscrowError::InvalidMint.into());
+    }
+    // 8. Verify the vault and escrow PDAs.
+    let escrow_seed = escrow_id.to_le_bytes();
+    let vault_pda = escrow_account.vault_address(program_id)?;
+    let vault_bump = escrow_account.vault_bump;
+    let escrow_pda = escrow_account.address(program_id)?;
+    if *escrow_info.key != escrow_pda || *vault_info.key != vault_pda {
+        return Err(EscrowError::PDADerivationMismatch.into());
+    }
+    // 9. A withdrawal must leave something on offer; cancel the escrow to take everything back.
+    if withdraw_amount >= escrow_account.offered && withdraw_amount > 0 {
+        msg!(
+            "Error: Cannot withdraw {} of the {} Token A on offer.",
+            withdraw_amount,
+            escrow_account.offered
+        );
+        return Err(EscrowError::InsufficientFunds.into());
+    }
+    msg!("All validations passed. Updating escrow...");
+
+    let mint_a_decimals = unpack_mint(mint_a_info)?.decimals;
+
+    // STEP 1: Top up the vault from the maker's Token A account.
+    if deposit_amount > 0 {
+        msg!("Transferring {} Token A to vault...", deposit_amount);
+        // The maker signs, or the members of the maker's multisig that follow.
+        let maker_authority =
+            TokenAuthority::resolve(maker_info, account_iter.as_slice(), token_program_info.key)?;
+        maker_authority.transfer_checked(
+            token_program_info,
+            maker_token_acc_a_info,
+            mint_a_info,
+            vault_info,
+            deposit_amount,
+            mint_a_decimals,
+        )?;
+        // Only what reached the vault after any transfer fee is added to the offer.
+        let received = unpack_token_account(vault_info, token_program_info.key)?
+            .amount
+            .checked_sub(vault_data.amount)
+            .ok_or(EscrowError::ArithmeticOverflow)?;
+        escrow_account.offered = escrow_account
+            .offered
+            .checked_add(received)
+            .ok_or(EscrowError::ArithmeticOverflow)?;
+    }
+
+    // STEP 2: Withdraw Token A from the vault back to the maker.
+    if withdraw_amount > 0 {
+        msg!("Withdrawing {} Token A from vault...", withdraw_amount);
+        let transfer_instruction = transfer_checked(
+            token_program_info.key,
+            vault_info.key,
+            mint_a_info.key,
+            maker_token_acc_a_info.key,
+            &vault_pda,
+            &[&vault_pda],
+            withdraw_amount,
+            mint_a_decimals,
+        )?;
+        let vault_signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            maker_info.key.as_ref(),
+            escrow_seed.as_ref(),
+            &[vault_bump],
+        ]];
+        invoke_signed(
+            &transfer_instruction,
+            &[
+                token_program_info.clone(),
+                vault_info.clone(),
+                mint_a_info.clone(),
+                maker_token_acc_a_info.clone(),
+            ],
+            vault_signer_seeds,
+        )?;
+        escrow_account.offered = escrow_account
+            .offered
+            .checked_sub(withdraw_amount)
+            .ok_or(EscrowError::ArithmeticOverflow)?;
+    }
+
+    // STEP 3: Reprice the escrow.
+    if let Some(receive_amount) = receive_amount {
+        escrow_account.receive = receive_amount;
+    }
+    escrow_account.pack_the_slice_data(&mut escrow_info.data.borrow_mut())?;
+
+    msg!(
+        "Escrow updated: {} Token A for {} Token B.",
+        escrow_account.offered,
+        escrow_account.receive
+    );
+    Ok(())
+}
@@ -1,5 +1,9 @@
+pub mod arbiter;
+pub mod basket;
 pub mod entrypoint;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod processor;
 pub mod state;
+pub mod token;
@@ -4,8 +4,20 @@ use solana_program::{
 };
 
 use crate::instructions::{
-    instruction::EscrowInstruction, make::initialize_escrow, refund::cancel_escrow,
+    approve::approve_escrow,
+    bid::{accept_bid, place_bid, withdraw_bid},
+    claim::claim_vested,
+    config::{initialize_config, update_config},
+    expire::refund_expired,
+    instruction::EscrowInstruction,
+    make::initialize_escrow,
+    migrate::migrate_escrow,
+    milestone::approve_milestone,
+    operator::set_operator,
+    refund::cancel_escrow,
+    settle::{confirm_escrow, resolve_escrow},
     take::release_funds,
+    update::update_escrow,
 };
 pub struct Process;
 impl Process {
@@ -13,22 +25,84 @@ impl Process {
         let instruction =
             EscrowInstruction::unpack(data).map_err(|_| ProgramError::InvalidAccountData)?;
         match instruction {
-            EscrowInstruction::InitializeEscrow {
+            EscrowInstruction::InitializeEscrow(args) => {
+                initialize_escrow(program_id, accounts, args)
+            }
+            EscrowInstruction::ReleaseFunds(args) => release_funds(program_id, accounts, args),
+            EscrowInstruction::CancelEscrow { escrow_id } => {
+                cancel_escrow(program_id, accounts, escrow_id)
+            }
+            EscrowInstruction::RefundExpired { escrow_id } => {
+                refund_expired(program_id, accounts, escrow_id)
+            }
+            EscrowInstruction::UpdateEscrow {
                 escrow_id,
-                deposit_amount,
                 receive_amount,
-            } => initialize_escrow(
+                deposit_amount,
+                withdraw_amount,
+            } => update_escrow(
                 program_id,
                 accounts,
                 escrow_id,
-                deposit_amount,
                 receive_amount,
+                deposit_amount,
+                withdraw_amount,
+            ),
+            EscrowInstruction::InitializeConfig {
+                fee_bps,
+                fee_recipient,
+            } => initialize_config(program_id, accounts, fee_bps, fee_recipient),
+            EscrowInstruction::UpdateConfig {
+                new_admin,
+                fee_bps,
+                fee_recipient,
+                paused,
+            } => update_config(
+                program_id,
+                accounts,
+                new_admin,
+                fee_bps,
+                fee_recipient,
+                paused,
             ),
-            EscrowInstruction::ReleaseFunds { escrow_id } => {
-                release_funds(program_id, accounts, escrow_id)
+            EscrowInstruction::MigrateEscrow { escrow_id } => {
+                migrate_escrow(program_id, accounts, escrow_id)
             }
-            EscrowInstruction::CancelEscrow { escrow_id } => {
-                cancel_escrow(program_id, accounts, escrow_id)
+            EscrowInstruction::PlaceBid {
+                escrow_id,
+                bid_amount,
+                expected_offered,
+            } => place_bid(
+                program_id,
+                accounts,
+                escrow_id,
+                bid_amount,
+                expected_offered,
+            ),
+            EscrowInstruction::AcceptBid { escrow_id } => {
+                accept_bid(program_id, accounts, escrow_id)
+            }
+            EscrowInstruction::WithdrawBid => withdraw_bid(program_id, accounts),
+            EscrowInstruction::SetOperator {
+                escrow_id,
+                operator,
+            } => set_operator(program_id, accounts, escrow_id, operator),
+            EscrowInstruction::ClaimVested { escrow_id } => {
+                claim_vested(program_id, accounts, escrow_id)
+            }
+            EscrowInstruction::ConfirmEscrow { escrow_id } => {
+                confirm_escrow(program_id, accounts, escrow_id)
+            }
+            EscrowInstruction::Resolve {
+                escrow_id,
+                release_bps,
+            } => resolve_escrow(program_id, accounts, escrow_id, release_bps),
+            EscrowInstruction::ApproveMilestone {
+                escrow_id,
+                milestone,
+            } => approve_milestone(program_id, accounts, escrow_id, milestone),
+            EscrowInstruction::Approve { escrow_id } => {
+                approve_escrow(program_id, accounts, escrow_id)
             }
         }
     }
@@ -2,6 +2,239 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::io::Error;
+
+use crate::{arbiter::PAYMENT_VAULT_SEED, basket::BASKET_VAULT_SEED, error::EscrowError};
+
+/// The asset the maker wants to receive in exchange for token A.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum ReceiveAsset {
+    /// Tokens of `token_mint_b`, paid from the taker's token account.
+    #[default]
+    Token,
+    /// Native SOL, paid in lamports with a system transfer. `token_mint_b` is the native mint.
+    NativeSol,
+}
+
+/// The kind of asset the maker offers as token A.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum OfferAsset {
+    /// Any amount of a fungible `token_mint_a`, which takers may buy in part.
+    #[default]
+    Token,
+    /// The single token of an NFT mint: no decimals and a supply of one, sold in one fill.
+    Nft,
+}
+
+/// The linear unlock of the token A sold by a vesting escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct VestingSchedule {
+    /// Unix timestamp before which none of token A is unlocked.
+    pub start: i64,
+    /// Unix timestamp from which all of token A is unlocked.
+    pub end: i64,
+}
+impl VestingSchedule {
+    pub const LEN: usize = 8 + 8;
+
+    /// The part of `total` unlocked at unix time `now`: none until `start`, all from `end`,
+    /// and a linear share in between.
+    pub fn unlocked(&self, total: u64, now: i64) -> u64 {
+        if now <= self.start {
+            return 0;
+        }
+        if now >= self.end {
+            return total;
+        }
+        let elapsed = now.abs_diff(self.start) as u128;
+        let duration = self.end.abs_diff(self.start) as u128;
+        // `elapsed < duration`, so the share is below `total` and fits a u64.
+        (total as u128 * elapsed / duration) as u64
+    }
+}
+
+/// A tranche of the token A held by a milestone escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct Milestone {
+    /// The amount of token A the maker releases to the recipient by approving the milestone.
+    pub amount: u64,
+    /// Whether the maker has approved the milestone.
+    pub approved: bool,
+}
+impl Milestone {
+    pub const LEN: usize = 8 + 1;
+}
+
+/// A key whose approval counts towards releasing an approval escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct Approver {
+    /// The approver's public key, which signs `Approve`.
+    pub key: Pubkey,
+    /// Whether the approver has approved the release.
+    pub approved: bool,
+}
+impl Approver {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// The lifecycle status of an escrow.
+///
+/// An escrow that is filled, cancelled or refunded is closed in the same instruction, so only
+/// the states of a live escrow are recorded. The discriminants are those older layouts wrote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+#[borsh(use_discriminant = true)]
+pub enum EscrowStatus {
+    /// The escrow can be taken, cancelled or, once expired, refunded.
+    #[default]
+    Open = 0,
+    /// A vesting escrow was taken; token A stays in the vault until the taker claims it.
+    Vesting = 4,
+    /// An arbiter escrow was taken; both sides stay in their vaults until the maker and taker
+    /// confirm, or the arbiter resolves it.
+    Held = 5,
+    /// A milestone or approval escrow is paying its recipient; token A leaves the vault one
+    /// approved milestone at a time, or once enough approvers have approved.
+    InProgress = 6,
+}
+
+/// The original escrow layout, written before accounts carried a header.
+///
+/// These accounts are exactly `EscrowV0::ACCOUNT_LEN` bytes and must be upgraded
+/// with `MigrateEscrow` before the program will act on them.
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct EscrowV0 {
+    pub escrow_id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub receive: u64,
+    pub bump: u8,
+}
+impl EscrowV0 {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+
+    /// Upgrades a version 0 escrow to the current layout, as an open single-pair escrow.
+    ///
+    /// Version 0 priced the whole vault, so the vault balance becomes the offer.
+    pub fn migrate(self, context: &MigrationContext) -> Escrow {
+        Escrow {
+            escrow_id: self.escrow_id,
+            maker: self.maker,
+            token_mint_a: self.token_mint_a,
+            token_mint_b: self.token_mint_b,
+            offered: context.vault_amount,
+            receive: self.receive,
+            bump: self.bump,
+            vault_bump: context.vault_bump,
+            payment_vault_bump: 0,
+            config_bump: context.config_bump,
+            expires_at: None,
+            allowed_taker: None,
+            operator: None,
+            maker_multisig: None,
+            offer_asset: OfferAsset::Token,
+            vesting: None,
+            beneficiary: None,
+            claimed: 0,
+            arbiter: None,
+            taker: None,
+            maker_confirmed: false,
+            taker_confirmed: false,
+            milestones: Vec::new(),
+            approvers: Vec::new(),
+            approval_threshold: 0,
+            fee_bps: context.fee_bps,
+            receive_asset: ReceiveAsset::Token,
+            status: EscrowStatus::Open,
+            basket_offered: Vec::new(),
+            basket_requested: Vec::new(),
+        }
+    }
+}
+
+/// One extra mint of a basket escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct BasketLeg {
+    /// The mint of this leg.
+    pub mint: Pubkey,
+    /// The amount held in the leg's vault, or owed by the taker.
+    pub amount: u64,
+}
+impl BasketLeg {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// One extra mint a basket escrow offers, held in a vault PDA of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct OfferedLeg {
+    /// The mint of this leg.
+    pub mint: Pubkey,
+    /// The amount held in the leg's vault.
+    pub amount: u64,
+    /// The bump seed used to derive the leg's vault PDA.
+    pub vault_bump: u8,
+}
+impl OfferedLeg {
+    pub const LEN: usize = BasketLeg::LEN + 1;
+}
+
+/// An escrow account decoded from any known layout version.
+#[derive(Debug)]
+pub enum VersionedEscrow {
+    V0(EscrowV0),
+    Current(Box<Escrow>),
+}
+impl VersionedEscrow {
+    /// Decodes an escrow account of any known version.
+    ///
+    /// Accounts with the escrow discriminator are read in the current layout; headerless
+    /// accounts of exactly `EscrowV0::ACCOUNT_LEN` bytes are read as version 0.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.starts_with(&Escrow::DISCRIMINATOR) {
+            return Escrow::unpack_the_slice_data(data)
+                .map(|escrow| Self::Current(Box::new(escrow)));
+        }
+        if data.len() == EscrowV0::ACCOUNT_LEN {
+            return EscrowV0::try_from_slice(data)
+                .map(Self::V0)
+                .map_err(|_| ProgramError::InvalidAccountData);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    /// The layout version the account was decoded from.
+    pub fn version(&self) -> u8 {
+        match self {
+            Self::V0(_) => 0,
+            Self::Current(_) => Escrow::VERSION,
+        }
+    }
+
+    /// Upgrades the escrow to the current layout; a current escrow is returned as is.
+    pub fn into_current(self, context: &MigrationContext) -> Escrow {
+        match self {
+            Self::V0(escrow) => escrow.migrate(context),
+            Self::Current(escrow) => *escrow,
+        }
+    }
+}
+
+/// What upgrading a version 0 escrow needs from outside its own account.
+#[derive(Debug, Clone, Copy)]
+pub struct MigrationContext {
+    /// The Token A balance of the vault, which version 0 offered in full.
+    pub vault_amount: u64,
+    /// The bump of the vault PDA, which version 0 did not store.
+    pub vault_bump: u8,
+    /// The bump of the config PDA, which version 0 did not store.
+    pub config_bump: u8,
+    /// The protocol fee to record on the escrow, as configured when migrated.
+    pub fee_bps: u16,
+}
+
+/// The current escrow layout.
+///
+/// On chain the fields are preceded by a header of `Escrow::DISCRIMINATOR`
+/// and a version byte, written by `pack_the_slice_data`.
 #[derive(Debug, BorshDeserialize, BorshSerialize)]
 pub struct Escrow {
     /// Unique identifier for this escrow account.
@@ -12,22 +245,415 @@ pub struct Escrow {
     pub token_mint_a: Pubkey,
     /// The public key of the token mint for token B, which the maker wants to receive.
     pub token_mint_b: Pubkey,
-    /// The amount of token B the maker expects to receive.
+    /// The amount of token A still on offer in the vault.
+    pub offered: u64,
+    /// The amount of token B the maker still expects to receive for `offered`.
     pub receive: u64,
     /// The bump seed used to derive the PDA for this escrow account.
     pub bump: u8,
+    /// The bump seed used to derive the vault PDA holding token A.
+    pub vault_bump: u8,
+    /// The bump seed used to derive the payment vault PDA of an arbiter escrow, set when it
+    /// is taken. Zero until then, and for escrows without an arbiter.
+    pub payment_vault_bump: u8,
+    /// The bump seed used to derive the config PDA, which need not exist.
+    pub config_bump: u8,
+    /// Unix timestamp after which the escrow can no longer be taken, if any.
+    pub expires_at: Option<i64>,
+    /// The only account allowed to take this escrow, if it is private.
+    pub allowed_taker: Option<Pubkey>,
+    /// A key the maker lets cancel or reprice this escrow for them, if any.
+    /// Refunds still go to the maker.
+    pub operator: Option<Pubkey>,
+    /// The SPL multisig that owns the maker's token accounts, if the maker trades for one.
+    pub maker_multisig: Option<Pubkey>,
+    /// Whether the maker offers fungible token A or a single NFT.
+    pub offer_asset: OfferAsset,
+    /// The schedule on which token A unlocks to the taker, if this is a vesting escrow.
+    /// Once taken, `offered` is the total unlocking to `beneficiary`.
+    pub vesting: Option<VestingSchedule>,
+    /// The owner of the taker's token accounts, which claims token A as it unlocks, or
+    /// receives its share when an arbiter escrow settles. Set when either kind is taken,
+    /// or when a milestone or approval escrow is created, to its recipient.
+    pub beneficiary: Option<Pubkey>,
+    /// The amount of token A the beneficiary has claimed so far.
+    pub claimed: u64,
+    /// The neutral key that may settle a taken escrow by splitting its vaults, if any.
+    /// The taker's payment is then held in a vault until the escrow settles.
+    pub arbiter: Option<Pubkey>,
+    /// The wallet that took an arbiter escrow, which confirms the trade for the taker.
+    pub taker: Option<Pubkey>,
+    /// Whether the maker has confirmed a held arbiter escrow.
+    pub maker_confirmed: bool,
+    /// Whether the taker has confirmed a held arbiter escrow.
+    pub taker_confirmed: bool,
+    /// The tranches token A is released in, if this is a milestone escrow. Such an escrow is
+    /// never taken: it pays `beneficiary` as the maker approves each milestone.
+    pub milestones: Vec<Milestone>,
+    /// The keys that approve releasing token A, if this is an approval escrow. Such an escrow
+    /// is never taken: it pays `beneficiary` once `approval_threshold` of them have approved.
+    pub approvers: Vec<Approver>,
+    /// The number of `approvers` that must approve before token A is released.
+    pub approval_threshold: u8,
+    /// The protocol fee taken from each fill's token B, in basis points, as configured when the
+    /// escrow was created. Later config changes do not apply to it.
+    pub fee_bps: u16,
+    /// Whether the maker receives token B or native SOL.
+    pub receive_asset: ReceiveAsset,
+    /// The lifecycle status of the escrow.
+    pub status: EscrowStatus,
+    /// Extra mints deposited alongside token A, each in its own vault. Empty unless this is a basket.
+    pub basket_offered: Vec<OfferedLeg>,
+    /// Extra mints the maker wants alongside token B. Empty unless this is a basket.
+    pub basket_requested: Vec<BasketLeg>,
 }
 impl Escrow {
-    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+    /// Tag identifying an escrow state account.
+    pub const DISCRIMINATOR: [u8; 8] = *b"ESCROW\0\0";
+    /// The layout version written by this program.
+    pub const VERSION: u8 = 1;
+    /// Size of the discriminator and version header.
+    pub const HEADER_LEN: usize = 8 + 1;
+    /// Size of a single-pair escrow account. Basket legs, milestones and approvers add to this,
+    /// see `account_len`.
+    pub const ACCOUNT_LEN: usize = Self::HEADER_LEN
+        + 8 + 32 + 32 + 32 // escrow_id, maker, token_mint_a, token_mint_b
+        + 8 + 8 + 1 + 1 // offered, receive, bump, vault_bump
+        + 1 + 1 // payment_vault_bump, config_bump
+        + (1 + 8) + (1 + 32) + (1 + 32) + (1 + 32) // expires_at, allowed_taker, operator, maker_multisig
+        + 1 + (1 + VestingSchedule::LEN) + (1 + 32) + 8 // offer_asset, vesting, beneficiary, claimed
+        + (1 + 32) + (1 + 32) + 1 + 1 // arbiter, taker, maker_confirmed, taker_confirmed
+        + 4 + 4 + 1 + 2 // milestones, approvers, approval_threshold, fee_bps
+        + 1 + 1 // receive_asset, status
+        + 4 + 4; // basket_offered, basket_requested
+    /// The most extra legs a basket can have on each side.
+    pub const MAX_BASKET_LEGS: usize = 3;
+    /// The most milestones a milestone escrow can have.
+    pub const MAX_MILESTONES: usize = 8;
+    /// The most approvers an approval escrow can have, as many as an SPL multisig's signers.
+    pub const MAX_APPROVERS: usize = 11;
+    /// Seed prefix of the escrow state PDA.
+    pub const SEED: &'static [u8] = b"escrow_vault";
+    /// Seed prefix of the vault PDA holding token A.
+    pub const VAULT_SEED: &'static [u8] = b"vault";
 
-    /// Serializes the `Escrow` struct.
+    /// Size of an escrow account with the given number of extra offered and requested legs,
+    /// of milestones and of approvers.
+    pub fn account_len(
+        offered_legs: usize,
+        requested_legs: usize,
+        milestones: usize,
+        approvers: usize,
+    ) -> usize {
+        Self::ACCOUNT_LEN
+            + offered_legs * OfferedLeg::LEN
+            + requested_legs * BasketLeg::LEN
+            + milestones * Milestone::LEN
+            + approvers * Approver::LEN
+    }
+    /// Returns `true` if the escrow trades more than a single pair of mints.
+    pub fn is_basket(&self) -> bool {
+        !self.basket_offered.is_empty() || !self.basket_requested.is_empty()
+    }
+    /// Returns `true` if the escrow sells a single NFT rather than fungible token A.
+    pub fn is_nft(&self) -> bool {
+        self.offer_asset == OfferAsset::Nft
+    }
+    /// Returns `true` if the taker receives token A over time rather than on the fill.
+    pub fn is_vesting(&self) -> bool {
+        self.vesting.is_some()
+    }
+    /// Returns `true` if the taker's payment is held until both parties or the arbiter settle.
+    pub fn is_arbitrated(&self) -> bool {
+        self.arbiter.is_some()
+    }
+    /// Returns `true` if token A is paid to the beneficiary in milestones the maker approves.
+    pub fn has_milestones(&self) -> bool {
+        !self.milestones.is_empty()
+    }
+    /// Returns `true` if token A is paid to the beneficiary once enough approvers approve.
+    pub fn requires_approval(&self) -> bool {
+        !self.approvers.is_empty()
+    }
+    /// The number of approvers that have approved so far.
+    pub fn approvals(&self) -> usize {
+        self.approvers
+            .iter()
+            .filter(|approver| approver.approved)
+            .count()
+    }
+    /// Returns the protocol fee on `amount` at the escrow's recorded rate, rounded down.
+    pub fn fee_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(EscrowError::ArithmeticOverflow)?
+            / Config::BPS_DENOMINATOR as u128;
+        Ok(fee as u64)
+    }
+    /// Returns `true` if `key` may cancel or reprice the escrow: its maker or its operator.
+    pub fn can_manage(&self, key: &Pubkey) -> bool {
+        self.maker == *key || self.operator == Some(*key)
+    }
+    /// The owner of the maker's token accounts: the maker's multisig if set, else the maker.
+    pub fn token_owner(&self) -> Pubkey {
+        self.maker_multisig.unwrap_or(self.maker)
+    }
+    /// Recreates this escrow's PDA from the stored `bump`.
+    ///
+    /// `create_program_address` hashes the seeds once, where `find_program_address`
+    /// may try many bumps, so handlers use this instead of deriving the PDA again.
+    pub fn address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                Self::SEED,
+                self.maker.as_ref(),
+                &self.escrow_id.to_le_bytes(),
+                &[self.bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Recreates the PDA of the vault holding token A from the stored `vault_bump`.
+    pub fn vault_address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                Self::VAULT_SEED,
+                self.maker.as_ref(),
+                &self.escrow_id.to_le_bytes(),
+                &[self.vault_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Recreates the payment vault PDA of an arbiter escrow from the stored `payment_vault_bump`.
+    pub fn payment_vault_address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                PAYMENT_VAULT_SEED,
+                self.maker.as_ref(),
+                &self.escrow_id.to_le_bytes(),
+                &[self.payment_vault_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Recreates the config PDA from the stored `config_bump`.
+    pub fn config_address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Config::address(program_id, self.config_bump)
+    }
+    /// Recreates the vault PDA of the offered basket leg at `index` from its stored `vault_bump`.
+    pub fn basket_vault_address(
+        &self,
+        program_id: &Pubkey,
+        index: u8,
+    ) -> Result<Pubkey, ProgramError> {
+        let leg = self
+            .basket_offered
+            .get(index as usize)
+            .ok_or(EscrowError::InvalidBasket)?;
+        Pubkey::create_program_address(
+            &[
+                BASKET_VAULT_SEED,
+                self.maker.as_ref(),
+                &self.escrow_id.to_le_bytes(),
+                &[index],
+                &[leg.vault_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+
+    /// Serializes the `Escrow` struct behind the current header.
     ///
     /// It `expect`s serialization to not fail, as `Escrow` contains only types
     /// that are guaranteed to serialize successfully with Borsh.
     pub fn pack_the_slice_data(&self, data: &mut [u8]) -> Result<(), Error> {
-        self.serialize(&mut &mut data[..])
+        let (header, body) = data.split_at_mut(Self::HEADER_LEN);
+        header[..8].copy_from_slice(&Self::DISCRIMINATOR);
+        header[8] = Self::VERSION;
+        self.serialize(&mut &mut body[..])
     }
+    /// Deserializes the `Escrow` struct, checking the header.
+    ///
+    /// Accounts in an older layout fail with `OutdatedAccountVersion` and need
+    /// `MigrateEscrow`. Optional fields make the serialized size vary, so the
+    /// account is sized for the largest layout and any trailing bytes are ignored.
     pub fn unpack_the_slice_data(data: &[u8]) -> Result<Self, ProgramError> {
-        BorshDeserialize::try_from_slice(data).map_err(|_| ProgramError::InvalidAccountData)
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, body) = data.split_at(Self::HEADER_LEN);
+        if header[..8] != Self::DISCRIMINATOR {
+            if data.len() == EscrowV0::ACCOUNT_LEN {
+                return Err(EscrowError::OutdatedAccountVersion.into());
+            }
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if header[8] < Self::VERSION {
+            return Err(EscrowError::OutdatedAccountVersion.into());
+        }
+        if header[8] != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::deserialize(&mut &body[..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/// The program-wide configuration, stored at the `[b"config"]` PDA.
+///
+/// Like `Escrow`, the fields are preceded by a discriminator and version header.
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct Config {
+    /// The account allowed to update the configuration.
+    pub admin: Pubkey,
+    /// The protocol fee taken from each fill's Token B, in basis points.
+    pub fee_bps: u16,
+    /// The wallet that receives protocol fees.
+    pub fee_recipient: Pubkey,
+    /// The bump seed used to derive the config PDA.
+    pub bump: u8,
+    /// While set, no escrows can be created or taken. Cancels and refunds still work.
+    pub paused: bool,
+}
+impl Config {
+    /// Tag identifying the config account.
+    pub const DISCRIMINATOR: [u8; 8] = *b"ESCCONFG";
+    /// The layout version written by this program.
+    pub const VERSION: u8 = 1;
+    pub const ACCOUNT_LEN: usize = Escrow::HEADER_LEN + 32 + 2 + 32 + 1 + 1;
+    /// Seed of the config PDA.
+    pub const SEED: &'static [u8] = b"config";
+    /// Basis points in a whole amount.
+    pub const BPS_DENOMINATOR: u16 = 10_000;
+    /// The highest fee the admin can set (10%).
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    /// Serializes the `Config` struct behind its header.
+    pub fn pack_the_slice_data(&self, data: &mut [u8]) -> Result<(), Error> {
+        let (header, body) = data.split_at_mut(Escrow::HEADER_LEN);
+        header[..8].copy_from_slice(&Self::DISCRIMINATOR);
+        header[8] = Self::VERSION;
+        self.serialize(&mut &mut body[..])
+    }
+    /// Deserializes the `Config` struct, checking its header.
+    pub fn unpack_the_slice_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Escrow::HEADER_LEN
+            || data[..8] != Self::DISCRIMINATOR
+            || data[8] != Self::VERSION
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::deserialize(&mut &data[Escrow::HEADER_LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Recreates the config PDA from `bump`, as stored on the config and on each escrow.
+    pub fn address(program_id: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(&[Self::SEED, &[bump]], program_id)
+            .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Derives the config PDA.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+/// A taker's counter-offer on an escrow, stored at the `[b"bid", escrow, bidder]` PDA.
+///
+/// The bid's Token B sits in a vault PDA of its own until the maker accepts it or the
+/// bidder withdraws it. Like `Escrow`, the fields are preceded by a discriminator and version header.
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct Bid {
+    /// The escrow state account the bid was placed on.
+    pub escrow: Pubkey,
+    /// The account that placed the bid and receives its rent back.
+    pub bidder: Pubkey,
+    /// The mint of the token B held in the bid vault.
+    pub token_mint_b: Pubkey,
+    /// The amount of token A the bid is for: everything the escrow offered when it was placed.
+    pub offered: u64,
+    /// The amount of token B that reached the bid vault.
+    pub amount: u64,
+    /// The bump seed used to derive the bid PDA.
+    pub bump: u8,
+    /// The bump seed used to derive the bid vault PDA.
+    pub vault_bump: u8,
+    /// Unix timestamp at which the bid was placed.
+    pub created_at: i64,
+}
+impl Bid {
+    /// Tag identifying a bid account.
+    pub const DISCRIMINATOR: [u8; 8] = *b"ESCRWBID";
+    /// The layout version written by this program.
+    pub const VERSION: u8 = 1;
+    pub const ACCOUNT_LEN: usize = Escrow::HEADER_LEN + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 8;
+    /// Seed prefix of the bid PDA.
+    pub const SEED: &'static [u8] = b"bid";
+    /// Seed prefix of the bid vault PDA, which holds the bid's token B.
+    pub const VAULT_SEED: &'static [u8] = b"bid_vault";
+
+    /// Serializes the `Bid` struct behind its header.
+    pub fn pack_the_slice_data(&self, data: &mut [u8]) -> Result<(), Error> {
+        let (header, body) = data.split_at_mut(Escrow::HEADER_LEN);
+        header[..8].copy_from_slice(&Self::DISCRIMINATOR);
+        header[8] = Self::VERSION;
+        self.serialize(&mut &mut body[..])
+    }
+    /// Deserializes the `Bid` struct, checking its header.
+    pub fn unpack_the_slice_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Escrow::HEADER_LEN
+            || data[..8] != Self::DISCRIMINATOR
+            || data[8] != Self::VERSION
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::deserialize(&mut &data[Escrow::HEADER_LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Recreates the bid PDA from the stored `bump`.
+    pub fn address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                Self::SEED,
+                self.escrow.as_ref(),
+                self.bidder.as_ref(),
+                &[self.bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Recreates the bid vault PDA from the stored `vault_bump`.
+    pub fn vault_address(&self, program_id: &Pubkey) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                Self::VAULT_SEED,
+                self.escrow.as_ref(),
+                self.bidder.as_ref(),
+                &[self.vault_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| EscrowError::PDADerivationMismatch.into())
+    }
+    /// Derives the bid PDA of `bidder` on `escrow`.
+    pub fn find_address(program_id: &Pubkey, escrow: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, escrow.as_ref(), bidder.as_ref()], program_id)
+    }
+    /// Derives the vault PDA holding the token B of `bidder`'s bid on `escrow`.
+    pub fn find_vault_address(
+        program_id: &Pubkey,
+        escrow: &Pubkey,
+        bidder: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::VAULT_SEED, escrow.as_ref(), bidder.as_ref()],
+            program_id,
+        )
     }
 }
@@ -0,0 +1,352 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{
+            instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig,
+        },
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    instruction::transfer_checked,
+    state::{Account, Mint, Multisig},
+};
+
+use crate::error::EscrowError;
+
+/// Returns `true` if `program_id` is the SPL Token or the Token-2022 program.
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::ID || *program_id == spl_token_2022::ID
+}
+
+/// Verifies that `token_program_info` is a supported token program and that it owns `mint_info`.
+pub fn check_token_program(
+    mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+) -> ProgramResult {
+    if !is_token_program(token_program_info.key) {
+        msg!(
+            "Error: {} is not the SPL Token or Token-2022 program.",
+            token_program_info.key
+        );
+        return Err(EscrowError::IncorrectProgramId.into());
+    }
+    if mint_info.owner != token_program_info.key {
+        msg!(
+            "Error: Mint {} is not owned by token program {}.",
+            mint_info.key,
+            token_program_info.key
+        );
+        return Err(EscrowError::IncorrectProgramId.into());
+    }
+    Ok(())
+}
+
+/// Unpacks a token account, checking that it is owned by `token_program_id`.
+///
+/// Works for both token programs, ignoring any Token-2022 account extensions.
+pub fn unpack_token_account(
+    account_info: &AccountInfo,
+    token_program_id: &Pubkey,
+) -> Result<Account, ProgramError> {
+    if account_info.owner != token_program_id {
+        return Err(EscrowError::InvalidAccountOwner.into());
+    }
+    let data = account_info.data.borrow();
+    Ok(StateWithExtensions::<Account>::unpack(&data)?.base)
+}
+
+/// Unpacks a mint owned by either token program, ignoring any extensions.
+pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    let data = mint_info.data.borrow();
+    Ok(StateWithExtensions::<Mint>::unpack(&data)?.base)
+}
+
+/// Rejects Token-2022 mints with extensions the escrow cannot honour.
+///
+/// Transfer hooks need extra accounts the escrow does not forward, and
+/// non-transferable tokens can never leave the vault.
+pub fn check_mint_extensions(mint_info: &AccountInfo) -> ProgramResult {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    for extension in mint.get_extension_types()? {
+        if matches!(
+            extension,
+            ExtensionType::TransferHook | ExtensionType::NonTransferable
+        ) {
+            msg!(
+                "Error: Mint {} uses unsupported extension {:?}.",
+                mint_info.key,
+                extension
+            );
+            return Err(EscrowError::UnsupportedMintExtension.into());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the space needed for a vault token account of `mint_info`,
+/// including any account extensions the mint requires.
+pub fn vault_account_len(mint_info: &AccountInfo) -> Result<usize, ProgramError> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(Account::LEN);
+    }
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let required_extensions =
+        ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+    ExtensionType::try_calculate_account_len::<Account>(&required_extensions)
+}
+
+/// Returns the amount to send so that the recipient receives `net_amount`
+/// after the mint's current transfer fee, if it has one.
+pub fn gross_amount_for_net(mint_info: &AccountInfo, net_amount: u64) -> Result<u64, ProgramError> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(net_amount);
+    }
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(net_amount);
+    };
+    let epoch = Clock::get()?.epoch;
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    Ok(net_amount
+        .checked_add(fee)
+        .ok_or(EscrowError::ArithmeticOverflow)?)
+}
+
+/// Returns what the recipient receives when `gross_amount` is sent,
+/// after the mint's current transfer fee, if it has one.
+pub fn net_amount_after_fee(
+    mint_info: &AccountInfo,
+    gross_amount: u64,
+) -> Result<u64, ProgramError> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(gross_amount);
+    }
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(gross_amount);
+    };
+    let epoch = Clock::get()?.epoch;
+    let fee = fee_config
+        .calculate_epoch_fee(epoch, gross_amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    Ok(gross_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::ArithmeticOverflow)?)
+}
+
+/// Creates `wallet_info`'s associated token account for `mint_info` if `account_info`
+/// does not exist yet, funded by `payer_info`.
+///
+/// The associated token account program fails unless `account_info` is that address,
+/// so a missing account cannot be swapped for another. Existing accounts are left alone.
+pub fn create_associated_account_if_missing<'a>(
+    payer_info: &AccountInfo<'a>,
+    wallet_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    associated_token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    if !account_info.data_is_empty() {
+        return Ok(());
+    }
+    msg!(
+        "Creating associated token account of {} for mint {}...",
+        wallet_info.key,
+        mint_info.key
+    );
+    invoke(
+        &create_associated_token_account_idempotent(
+            payer_info.key,
+            wallet_info.key,
+            mint_info.key,
+            token_program_info.key,
+        ),
+        &[
+            payer_info.clone(),
+            account_info.clone(),
+            wallet_info.clone(),
+            mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )
+}
+
+/// The authority over someone's token accounts: their own wallet, or an SPL multisig
+/// together with the members that signed for it.
+pub struct TokenAuthority<'a, 'b> {
+    /// The owner of the token accounts: the wallet or the multisig account.
+    pub owner_info: &'b AccountInfo<'a>,
+    /// The multisig members that signed. Empty when the wallet owns the accounts.
+    pub signer_infos: &'b [AccountInfo<'a>],
+}
+impl<'a, 'b> TokenAuthority<'a, 'b> {
+    /// Resolves who owns `wallet_info`'s token accounts.
+    ///
+    /// With no `multisig_accounts` the signing wallet owns them. Otherwise the first account
+    /// is an SPL multisig of `token_program_id` and the rest are at least `m` of its members,
+    /// each signing. The wallet must be a member itself, so nobody can act for a multisig
+    /// they do not belong to.
+    pub fn resolve(
+        wallet_info: &'b AccountInfo<'a>,
+        multisig_accounts: &'b [AccountInfo<'a>],
+        token_program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let Some((multisig_info, signer_infos)) = multisig_accounts.split_first() else {
+            return Ok(Self {
+                owner_info: wallet_info,
+                signer_infos: &[],
+            });
+        };
+        if multisig_info.owner != token_program_id {
+            msg!(
+                "Error: Multisig {} is not owned by token program {}.",
+                multisig_info.key,
+                token_program_id
+            );
+            return Err(EscrowError::InvalidAccountOwner.into());
+        }
+        let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+        let members = &multisig.signers[..multisig.n as usize];
+        if !members.contains(wallet_info.key) {
+            msg!(
+                "Error: {} is not a member of multisig {}.",
+                wallet_info.key,
+                multisig_info.key
+            );
+            return Err(EscrowError::Unauthorized.into());
+        }
+        for (index, signer_info) in signer_infos.iter().enumerate() {
+            if !signer_info.is_signer
+                || !members.contains(signer_info.key)
+                || signer_infos[..index]
+                    .iter()
+                    .any(|other| other.key == signer_info.key)
+            {
+                msg!(
+                    "Error: {} is not a signing member of multisig {}.",
+                    signer_info.key,
+                    multisig_info.key
+                );
+                return Err(EscrowError::MissingRequiredSignature.into());
+            }
+        }
+        if signer_infos.len() < multisig.m as usize {
+            msg!(
+                "Error: Multisig {} needs {} signers, got {}.",
+                multisig_info.key,
+                multisig.m,
+                signer_infos.len()
+            );
+            return Err(EscrowError::MissingRequiredSignature.into());
+        }
+        Ok(Self {
+            owner_info: multisig_info,
+            signer_infos,
+        })
+    }
+
+    /// The owner the token accounts must have.
+    pub fn key(&self) -> &Pubkey {
+        self.owner_info.key
+    }
+
+    /// The multisig account, if the token accounts belong to one.
+    pub fn multisig(&self) -> Option<Pubkey> {
+        (!self.signer_infos.is_empty()).then_some(*self.owner_info.key)
+    }
+
+    /// Transfers `amount` out of `source_info` with this authority's signatures.
+    pub fn transfer_checked(
+        &self,
+        token_program_info: &AccountInfo<'a>,
+        source_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        amount: u64,
+        decimals: u8,
+    ) -> ProgramResult {
+        let signer_keys: Vec<&Pubkey> = self.signer_infos.iter().map(|info| info.key).collect();
+        let mut account_infos = vec![
+            token_program_info.clone(),
+            source_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            self.owner_info.clone(),
+        ];
+        account_infos.extend(self.signer_infos.iter().cloned());
+        invoke(
+            &transfer_checked(
+                token_program_info.key,
+                source_info.key,
+                mint_info.key,
+                destination_info.key,
+                self.owner_info.key,
+                &signer_keys,
+                amount,
+                decimals,
+            )?,
+            &account_infos,
+        )
+    }
+}
+
+/// Harvests transfer fees withheld in a Token-2022 vault back to its mint.
+///
+/// A vault with withheld fees cannot be closed, so this must run before `close_account`.
+/// It is a no-op for SPL Token vaults and for vaults with nothing withheld.
+pub fn harvest_vault_fees<'a>(
+    token_program_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    if *token_program_info.key != spl_token_2022::ID {
+        return Ok(());
+    }
+    let withheld_amount = {
+        let data = vault_info.data.borrow();
+        let vault = StateWithExtensions::<Account>::unpack(&data)?;
+        match vault.get_extension::<TransferFeeAmount>() {
+            Ok(fee_amount) => u64::from(fee_amount.withheld_amount),
+            Err(_) => 0,
+        }
+    };
+    if withheld_amount == 0 {
+        return Ok(());
+    }
+    msg!(
+        "Harvesting {} withheld Token A fees to the mint...",
+        withheld_amount
+    );
+    let harvest_instruction =
+        harvest_withheld_tokens_to_mint(token_program_info.key, mint_info.key, &[vault_info.key])?;
+    invoke(
+        &harvest_instruction,
+        &[
+            mint_info.clone(),
+            vault_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
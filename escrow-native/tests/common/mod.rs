@@ -1,7 +1,18 @@
+#![allow(dead_code)]
+
 use std::str::FromStr;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use escrow_native::{
+    arbiter::find_payment_vault,
+    basket::find_basket_vault,
+    events::EscrowEvent,
+    instructions::instruction::{EscrowInstruction, InitializeEscrowArgs, ReleaseFundsArgs},
+    state::{Bid, Config, Escrow, OfferAsset, ReceiveAsset, VestingSchedule},
+};
 use litesvm::LiteSVM;
 use solana_sdk::{
+    bpf_loader_upgradeable,
     instruction::{AccountMeta, Instruction},
     program_pack::Pack,
     pubkey::Pubkey,
@@ -12,7 +23,7 @@ use solana_sdk::{
     transaction::Transaction,
 };
 use spl_associated_token_account::{
-    get_associated_token_address, instruction::create_associated_token_account,
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
 };
 const PROGRAM_ID: &str = "YOUR_PROGRAM_ID";
 const MAKER: &str = "YOUR_KEYPAIR";
@@ -21,9 +32,15 @@ const TOKEN_MINT_A: &str = "TOKEN_MINT_A_KEYPAIR";
 const TOKEN_MINT_B: &str = "TOKEN_MINT_B_KEYPAIR";
 
 use spl_token::{
+    instruction::initialize_multisig, native_mint, state::Multisig, ID as TOKEN_PROGRAM_ID,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType,
+        StateWithExtensions,
+    },
     instruction::{initialize_mint, mint_to},
-    state::Mint,
-    ID as TOKEN_PROGRAM_ID,
+    state::{Account, Mint},
 };
 
 // Holds all setup data needed for an escrow test
@@ -44,12 +61,27 @@ pub struct EscrowTestSetup {
     pub escrow_bump: u8,
     pub token_a_decimals: u8,
     pub token_b_decimals: u8,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
 }
 /// Holds escrow transaction parameters
+#[derive(Default)]
 pub struct EscrowParams {
     pub escrow_id: u64,
     pub deposit_amount: u64,
     pub receive_amount: u64,
+    pub expires_at: Option<i64>,
+    pub allowed_taker: Option<Pubkey>,
+    pub receive_asset: ReceiveAsset,
+    pub basket_offered: Vec<(Pubkey, u64)>,
+    pub basket_requested: Vec<(Pubkey, u64)>,
+    pub operator: Option<Pubkey>,
+    pub offer_asset: OfferAsset,
+    pub vesting: Option<VestingSchedule>,
+    pub arbiter: Option<Pubkey>,
+    pub milestones: Vec<u64>,
+    pub approvers: Vec<Pubkey>,
+    pub approval_threshold: u8,
 }
 //helper fns
 /// Creates and initializes a token mint in LiteSVM
@@ -58,26 +90,54 @@ pub fn create_token_mint(
     mint: &Keypair,
     decimals: u8,
     auth_payer: &Keypair,
+) -> Result<(), Box<dyn std::error::Error>> {
+    create_token_mint_with_program(svm, mint, decimals, auth_payer, &TOKEN_PROGRAM_ID, None)
+}
+/// Creates and initializes a token mint owned by `token_program`.
+/// A Token-2022 mint can be given a transfer fee in basis points.
+pub fn create_token_mint_with_program(
+    svm: &mut LiteSVM,
+    mint: &Keypair,
+    decimals: u8,
+    auth_payer: &Keypair,
+    token_program: &Pubkey,
+    transfer_fee_basis_points: Option<u16>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("started create token mint");
-    let rent = svm.minimum_balance_for_rent_exemption(Mint::LEN);
+    let mut instructions = vec![];
+    let extensions = match transfer_fee_basis_points {
+        Some(_) => vec![ExtensionType::TransferFeeConfig],
+        None => vec![],
+    };
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
+    let rent = svm.minimum_balance_for_rent_exemption(space);
 
-    let mint_instruction = create_account(
+    instructions.push(create_account(
         &auth_payer.pubkey(),
         &mint.pubkey(),
         rent,
-        Mint::LEN as u64,
-        &TOKEN_PROGRAM_ID,
-    );
-    let initialize_mint_instruction = initialize_mint(
-        &TOKEN_PROGRAM_ID,
+        space as u64,
+        token_program,
+    ));
+    if let Some(basis_points) = transfer_fee_basis_points {
+        instructions.push(initialize_transfer_fee_config(
+            token_program,
+            &mint.pubkey(),
+            Some(&auth_payer.pubkey()),
+            Some(&auth_payer.pubkey()),
+            basis_points,
+            u64::MAX,
+        )?);
+    }
+    instructions.push(initialize_mint(
+        token_program,
         &mint.pubkey(),
         &auth_payer.pubkey(),
         Some(&auth_payer.pubkey()),
         decimals,
-    )?;
+    )?);
     let tx = Transaction::new_signed_with_payer(
-        &[mint_instruction, initialize_mint_instruction],
+        &instructions,
         Some(&auth_payer.pubkey()),
         &[&auth_payer, &mint],
         svm.latest_blockhash(),
@@ -99,20 +159,31 @@ pub fn create_token_account(
     payer: &Keypair,
     mint: &Pubkey,
     owner: &Pubkey,
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    create_token_account_with_program(svm, payer, mint, owner, &TOKEN_PROGRAM_ID)
+}
+/// Creates and initializes a token ata account for a mint owned by `token_program`
+pub fn create_token_account_with_program(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    token_program: &Pubkey,
 ) -> Result<Pubkey, Box<dyn std::error::Error>> {
     println!("started creating token account...");
 
-    // Derive the associated token account address for fee_payer
-    let associated_token_account = get_associated_token_address(
-        &payer.pubkey(), // owner
-        &mint,           // mint
+    // Derive the associated token account address for the owner
+    let associated_token_account = get_associated_token_address_with_program_id(
+        owner,         // owner
+        mint,          // mint
+        token_program, // program id
     );
     // Instruction to create associated token account
     let create_ata_instruction = create_associated_token_account(
-        &payer.pubkey(),   // funding address
-        &owner,            // wallet address (owner)
-        &mint,             // mint address
-        &TOKEN_PROGRAM_ID, // program id
+        &payer.pubkey(), // funding address
+        owner,           // wallet address (owner)
+        mint,            // mint address
+        token_program,   // program id
     );
     // Create transaction for associated token account creation
     let transaction = Transaction::new_signed_with_payer(
@@ -140,12 +211,14 @@ pub fn mint_tokens_to(
     ata: &Pubkey,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("started mint to");
+    // Mint with whichever token program owns the mint
+    let token_program = svm.get_account(mint).ok_or("mint account not found")?.owner;
 
     // Create mint_to instruction to mint tokens to the associated token account
     let mint_to_instruction = mint_to(
-        &TOKEN_PROGRAM_ID,
-        &mint,              // mint
-        &ata,               // destination
+        &token_program,
+        mint,               // mint
+        ata,                // destination
         &payer.pubkey(),    // authority
         &[&payer.pubkey()], // signer
         amount,             // amount
@@ -168,17 +241,32 @@ pub fn setup_token_with_account(
     mint_keypair: &Keypair,
     owner: &Keypair,
     initial_amount: u64,
+    token_program: &Pubkey,
+    transfer_fee_basis_points: Option<u16>,
 ) -> Result<(Pubkey, Pubkey), Box<dyn std::error::Error>> {
     println!("started setup token with account");
     // Create mint
-    create_token_mint(svm, mint_keypair, 9, &owner)?;
+    create_token_mint_with_program(
+        svm,
+        mint_keypair,
+        9,
+        owner,
+        token_program,
+        transfer_fee_basis_points,
+    )?;
 
     // Create token account
-    let ata = create_token_account(svm, &owner, &mint_keypair.pubkey(), &owner.pubkey())?;
+    let ata = create_token_account_with_program(
+        svm,
+        owner,
+        &mint_keypair.pubkey(),
+        &owner.pubkey(),
+        token_program,
+    )?;
 
     // Mint tokens if requested
     if initial_amount > 0 {
-        mint_tokens_to(svm, &owner, &mint_keypair.pubkey(), initial_amount, &ata)?;
+        mint_tokens_to(svm, owner, &mint_keypair.pubkey(), initial_amount, &ata)?;
     }
     println!("done setup token with account");
 
@@ -250,8 +338,23 @@ pub fn load_keypairs(
 
     Ok((maker, mint_a, mint_b, taker))
 }
+/// Unpacks a token account of either token program, ignoring extensions
+pub fn unpack_token_account(data: &[u8]) -> Account {
+    StateWithExtensions::<Account>::unpack(data)
+        .expect("Failed to unpack token account")
+        .base
+}
 /// Perform complete test setup
 pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std::error::Error>> {
+    setup_escrow_test_with_token_program(escrow_id, &TOKEN_PROGRAM_ID, None)
+}
+/// Perform complete test setup with both mints owned by `token_program`,
+/// optionally charging a Token-2022 transfer fee in basis points
+pub fn setup_escrow_test_with_token_program(
+    escrow_id: u64,
+    token_program: &Pubkey,
+    transfer_fee_basis_points: Option<u16>,
+) -> Result<EscrowTestSetup, Box<dyn std::error::Error>> {
     // Setup SVM and program
     let (mut svm, program_id) = setup_svm_and_program()?;
 
@@ -269,21 +372,27 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
 
     // Setup tokens
     let initial_amount = 1_000_000_000;
-    let (mint_a_pubkey, maker_token_acc_a) =
-        setup_token_with_account(&mut svm, &token_mint_a, &maker, initial_amount)?;
+    let (mint_a_pubkey, maker_token_acc_a) = setup_token_with_account(
+        &mut svm,
+        &token_mint_a,
+        &maker,
+        initial_amount,
+        token_program,
+        transfer_fee_basis_points,
+    )?;
     // Verify maker has tokens in account A
     let maker_acc_a_info = svm
         .get_account(&maker_token_acc_a)
         .expect("Maker token account A not found");
     // Deserialize token account data
-    let token_acc_data = spl_token::state::Account::unpack(&maker_acc_a_info.data)
-        .expect("Failed to unpack token account");
+    let token_acc_data = unpack_token_account(&maker_acc_a_info.data);
     // Get decimals for Token A
     let mint_a_account = svm
         .get_account(&mint_a_pubkey)
         .expect("failed to get mint A");
-    let mint_a_data =
-        spl_token::state::Mint::unpack(&mint_a_account.data).expect("failed to unpack mint a");
+    let mint_a_data = StateWithExtensions::<Mint>::unpack(&mint_a_account.data)
+        .expect("failed to unpack mint a")
+        .base;
     let token_a_decimals = mint_a_data.decimals;
     println!(
         "Maker Token Account A:\n  Mint: {}\n  ATA:  {}\n  Amount: {} ({})\n",
@@ -292,21 +401,27 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
         token_acc_data.amount,
         to_ui_amount(token_acc_data.amount, token_a_decimals)
     );
-    let (mint_b_pubkey, maker_token_acc_b) =
-        setup_token_with_account(&mut svm, &token_mint_b, &maker, 0)?;
+    let (mint_b_pubkey, maker_token_acc_b) = setup_token_with_account(
+        &mut svm,
+        &token_mint_b,
+        &maker,
+        0,
+        token_program,
+        transfer_fee_basis_points,
+    )?;
     // Verify maker has tokens in account A
     let maker_acc_b_info = svm
         .get_account(&maker_token_acc_b)
         .expect("Maker token account B not found");
     // Deserialize token account data
-    let token_acc_data = spl_token::state::Account::unpack(&maker_acc_b_info.data)
-        .expect("Failed to unpack token account");
+    let token_acc_data = unpack_token_account(&maker_acc_b_info.data);
     // Get decimals for Token B
     let mint_b_account = svm
         .get_account(&mint_b_pubkey)
         .expect("failed to get mint B");
-    let mint_b_data =
-        spl_token::state::Mint::unpack(&mint_b_account.data).expect("failed to unpack mint b");
+    let mint_b_data = StateWithExtensions::<Mint>::unpack(&mint_b_account.data)
+        .expect("failed to unpack mint b")
+        .base;
     let token_b_decimals = mint_b_data.decimals;
     println!(
         "Maker Token Account B:\n  Mint: {}\n  ATA:  {}\n  Amount: {} ({})\n",
@@ -317,11 +432,12 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
     );
 
     // STEP 3: Create taker's Token B account (using SAME mint_b_pubkey, just different owner)
-    let taker_token_acc_b = create_token_account(
+    let taker_token_acc_b = create_token_account_with_program(
         &mut svm,
         &taker,
         &mint_b_pubkey, // Use the SAME Token B mint as maker
         &taker.pubkey(),
+        token_program,
     )?;
 
     // Mint tokens to taker's Token B account
@@ -338,8 +454,7 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
         .get_account(&taker_token_acc_b)
         .expect("Taker token account B not found");
     // Deserialize token account data
-    let token_acc_data = spl_token::state::Account::unpack(&taker_acc_b_info.data)
-        .expect("Failed to unpack token account");
+    let token_acc_data = unpack_token_account(&taker_acc_b_info.data);
     println!(
         "Taker Token Account B:\n  Mint: {}\n  ATA:  {}\n  Amount: {} ({})\n",
         mint_b_pubkey,
@@ -349,11 +464,12 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
     );
 
     // STEP 4: Create taker's Token A account (using SAME mint_a_pubkey, just different owner)
-    let taker_token_acc_a = create_token_account(
+    let taker_token_acc_a = create_token_account_with_program(
         &mut svm,
         &taker,
         &mint_a_pubkey, // Use the SAME Token A mint as maker
         &taker.pubkey(),
+        token_program,
     )?;
 
     // Verify taker has tokens in account A
@@ -361,8 +477,7 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
         .get_account(&taker_token_acc_a)
         .expect("Taker token account A not found");
     // Deserialize token account data
-    let token_acc_data = spl_token::state::Account::unpack(&taker_acc_a_info.data)
-        .expect("Failed to unpack token account");
+    let token_acc_data = unpack_token_account(&taker_acc_a_info.data);
 
     println!(
         "Taker Token Account A:\n  Mint: {}\n  ATA:  {}\n  Amount: {} ({})\n",
@@ -393,6 +508,8 @@ pub fn setup_escrow_test(escrow_id: u64) -> Result<EscrowTestSetup, Box<dyn std:
         escrow_bump,
         token_a_decimals,
         token_b_decimals,
+        token_program_a: *token_program,
+        token_program_b: *token_program,
     })
 }
 /// Send and verify transaction
@@ -414,6 +531,47 @@ pub fn send_transaction(
     println!("Transaction sent successfully");
     Ok(())
 }
+/// Send a transaction and decode the escrow events it logged
+pub fn send_transaction_with_events(
+    svm: &mut LiteSVM,
+    instruction: Instruction,
+    payer: &Keypair,
+) -> Result<Vec<EscrowEvent>, Box<dyn std::error::Error>> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        svm.latest_blockhash(),
+    );
+    let metadata = svm
+        .send_transaction(tx)
+        .map_err(|e| format!("Transaction failed: {:?}", e))?;
+    let events = metadata
+        .logs
+        .iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|bytes| EscrowEvent::unpack(&bytes).ok())
+        .collect();
+    Ok(events)
+}
+/// Send a transaction and return the compute units it consumed
+pub fn send_transaction_with_compute_units(
+    svm: &mut LiteSVM,
+    instruction: Instruction,
+    payer: &Keypair,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        svm.latest_blockhash(),
+    );
+    let metadata = svm
+        .send_transaction(tx)
+        .map_err(|e| format!("Transaction failed: {:?}", e))?;
+    Ok(metadata.compute_units_consumed)
+}
 fn to_ui_amount(amount: u64, decimals: u8) -> f64 {
     amount as f64 / 10f64.powi(decimals as i32)
 }
@@ -422,72 +580,784 @@ pub fn create_initialize_escrow_instruction(
     set_up: &EscrowTestSetup,
     params: &EscrowParams,
 ) -> Instruction {
-    let mut instruction_data = vec![0u8];
-    instruction_data.extend_from_slice(&params.escrow_id.to_le_bytes());
-    instruction_data.extend_from_slice(&params.deposit_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&params.receive_amount.to_le_bytes());
+    let instruction_data = EscrowInstruction::InitializeEscrow(InitializeEscrowArgs {
+        escrow_id: params.escrow_id,
+        deposit_amount: params.deposit_amount,
+        receive_amount: params.receive_amount,
+        expires_at: params.expires_at,
+        allowed_taker: params.allowed_taker,
+        receive_asset: params.receive_asset,
+        basket_offered: params.basket_offered.iter().map(|leg| leg.1).collect(),
+        basket_requested: params.basket_requested.iter().map(|leg| leg.1).collect(),
+        operator: params.operator,
+        offer_asset: params.offer_asset,
+        vesting: params.vesting,
+        arbiter: params.arbiter,
+        milestones: params.milestones.clone(),
+        approvers: params.approvers.clone(),
+        approval_threshold: params.approval_threshold,
+    })
+    .pack();
+    // A native SOL escrow takes the native mint and the maker's wallet in place of Token B
+    let (mint_b, maker_token_acc_b) = match params.receive_asset {
+        ReceiveAsset::Token => (set_up.mint_b_pubkey, set_up.maker_token_acc_b),
+        ReceiveAsset::NativeSol => (native_mint::ID, set_up.maker.pubkey()),
+    };
 
-    let instruction = Instruction {
+    let mut instruction = Instruction {
         program_id: set_up.program_id, // The program to call
         accounts: vec![
             // Accounts the program needs
             AccountMeta::new(set_up.maker.pubkey(), true),
             AccountMeta::new_readonly(set_up.mint_a_pubkey, false),
-            AccountMeta::new_readonly(set_up.mint_b_pubkey, false),
+            AccountMeta::new_readonly(mint_b, false),
             AccountMeta::new(set_up.maker_token_acc_a, false),
             AccountMeta::new(set_up.vault_pda, false),
             AccountMeta::new(set_up.escrow_pda, false),
-            AccountMeta::new(set_up.maker_token_acc_b, false),
+            AccountMeta::new(maker_token_acc_b, false),
             AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(&set_up.program_id), false),
         ],
         data: instruction_data, // Instruction data
     };
+    // Basket legs follow: mint, maker account and vault per offered leg, then mint and
+    // maker account per requested leg
+    for (index, (mint, _)) in params.basket_offered.iter().enumerate() {
+        let (vault, _) = find_basket_vault(
+            &set_up.program_id,
+            &set_up.maker.pubkey(),
+            params.escrow_id,
+            index as u8,
+        );
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    &set_up.maker.pubkey(),
+                    mint,
+                    &set_up.token_program_a,
+                ),
+                false,
+            ),
+            AccountMeta::new(vault, false),
+        ]);
+    }
+    for (mint, _) in &params.basket_requested {
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(
+                get_associated_token_address_with_program_id(
+                    &set_up.maker.pubkey(),
+                    mint,
+                    &set_up.token_program_b,
+                ),
+                false,
+            ),
+        ]);
+    }
     instruction
 }
 
+/// Account metas refunding each offered basket leg to `owner`: mint, vault and owner's account
+pub fn basket_release_accounts(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    owner: &Pubkey,
+) -> Vec<AccountMeta> {
+    let mut accounts = Vec::new();
+    for (index, (mint, _)) in params.basket_offered.iter().enumerate() {
+        let (vault, _) = find_basket_vault(
+            &set_up.program_id,
+            &set_up.maker.pubkey(),
+            params.escrow_id,
+            index as u8,
+        );
+        accounts.extend([
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(owner, mint, &set_up.token_program_a),
+                false,
+            ),
+        ]);
+    }
+    accounts
+}
+
 pub fn create_refund_escrow_instruction(
     set_up: &mut EscrowTestSetup,
     params: &EscrowParams,
 ) -> Instruction {
-    let mut instruction_data = vec![2u8];
-    instruction_data.extend_from_slice(&params.escrow_id.to_le_bytes());
+    let instruction_data = EscrowInstruction::CancelEscrow {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
     Instruction {
         program_id: set_up.program_id,
         accounts: vec![
             AccountMeta::new(set_up.maker.pubkey(), true),
-            AccountMeta::new_readonly(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.mint_a_pubkey, false),
             AccountMeta::new(set_up.maker_token_acc_a, false),
             AccountMeta::new(set_up.escrow_pda, false),
             AccountMeta::new(set_up.vault_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
-        ],
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ]
+        .into_iter()
+        .chain(basket_release_accounts(
+            set_up,
+            params,
+            &set_up.maker.pubkey(),
+        ))
+        .collect(),
         data: instruction_data,
     }
 }
 
+/// Builds a take that quotes the escrow's current on-chain terms, as the CLI does
 pub fn create_release_funds_instruction(
     set_up: &mut EscrowTestSetup,
     params: &EscrowParams,
+    fill_amount: u64,
+) -> Instruction {
+    let (expected_offered, expected_receive) = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .and_then(|account| Escrow::unpack_the_slice_data(&account.data).ok())
+        .map(|escrow| (escrow.offered, escrow.receive))
+        .unwrap_or((params.deposit_amount, params.receive_amount));
+    create_release_funds_instruction_with_terms(
+        set_up,
+        params,
+        fill_amount,
+        expected_offered,
+        expected_receive,
+    )
+}
+
+/// Builds a take against the given terms, without slippage limits
+pub fn create_release_funds_instruction_with_terms(
+    set_up: &mut EscrowTestSetup,
+    params: &EscrowParams,
+    fill_amount: u64,
+    expected_offered: u64,
+    expected_receive: u64,
+) -> Instruction {
+    create_release_funds_instruction_with_limits(
+        set_up,
+        params,
+        fill_amount,
+        expected_offered,
+        expected_receive,
+        0,
+        u64::MAX,
+    )
+}
+
+/// Builds a take against the given terms that receives at least `min_token_a_out`
+/// and pays at most `max_token_b_in`
+pub fn create_release_funds_instruction_with_limits(
+    set_up: &mut EscrowTestSetup,
+    params: &EscrowParams,
+    fill_amount: u64,
+    expected_offered: u64,
+    expected_receive: u64,
+    min_token_a_out: u64,
+    max_token_b_in: u64,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ReleaseFunds(ReleaseFundsArgs {
+        escrow_id: params.escrow_id,
+        fill_amount,
+        expected_offered,
+        expected_receive,
+        min_token_a_out,
+        max_token_b_in,
+    })
+    .pack();
+    // A native SOL escrow is paid from the taker's wallet to the maker's via the System Program
+    let (mint_b, maker_token_acc_b, taker_token_acc_b, token_program_b) = match params.receive_asset
+    {
+        // An arbiter escrow is paid into its payment vault instead of the maker's account
+        ReceiveAsset::Token if params.arbiter.is_some() => (
+            set_up.mint_b_pubkey,
+            find_payment_vault(&set_up.program_id, &set_up.maker.pubkey(), params.escrow_id).0,
+            set_up.taker_token_acc_b,
+            set_up.token_program_b,
+        ),
+        ReceiveAsset::Token => (
+            set_up.mint_b_pubkey,
+            set_up.maker_token_acc_b,
+            set_up.taker_token_acc_b,
+            set_up.token_program_b,
+        ),
+        ReceiveAsset::NativeSol => (
+            native_mint::ID,
+            set_up.maker.pubkey(),
+            set_up.taker.pubkey(),
+            system_program::id(),
+        ),
+    };
+    let mut accounts = vec![
+        AccountMeta::new(set_up.taker.pubkey(), true),
+        AccountMeta::new(set_up.maker.pubkey(), false),
+        AccountMeta::new(set_up.mint_a_pubkey, false),
+        AccountMeta::new(mint_b, false),
+        AccountMeta::new(maker_token_acc_b, false),
+        AccountMeta::new(set_up.taker_token_acc_a, false),
+        AccountMeta::new(taker_token_acc_b, false),
+        AccountMeta::new(set_up.vault_pda, false),
+        AccountMeta::new(set_up.escrow_pda, false),
+        AccountMeta::new_readonly(set_up.token_program_a, false),
+        AccountMeta::new_readonly(token_program_b, false),
+        AccountMeta::new_readonly(derive_config_pda(&set_up.program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+    ];
+    // Basket legs follow: offered legs go to the taker, requested legs are paid to the maker
+    accounts.extend(basket_release_accounts(
+        set_up,
+        params,
+        &set_up.taker.pubkey(),
+    ));
+    for (mint, _) in &params.basket_requested {
+        accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    &set_up.taker.pubkey(),
+                    mint,
+                    &set_up.token_program_b,
+                ),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    &set_up.maker.pubkey(),
+                    mint,
+                    &set_up.token_program_b,
+                ),
+                false,
+            ),
+        ]);
+    }
+    Instruction {
+        program_id: set_up.program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+pub fn create_refund_expired_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
 ) -> Instruction {
-    let mut instruction_data = vec![1u8];
-    instruction_data.extend_from_slice(&params.escrow_id.to_le_bytes());
+    let instruction_data = EscrowInstruction::RefundExpired {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
     Instruction {
         program_id: set_up.program_id,
         accounts: vec![
-            AccountMeta::new(set_up.taker.pubkey(), true),
             AccountMeta::new(set_up.maker.pubkey(), false),
             AccountMeta::new(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.maker_token_acc_a, false),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new(set_up.vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ]
+        .into_iter()
+        .chain(basket_release_accounts(
+            set_up,
+            params,
+            &set_up.maker.pubkey(),
+        ))
+        .collect(),
+        data: instruction_data,
+    }
+}
+
+pub fn create_claim_vested_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ClaimVested {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.maker.pubkey(), false),
+            AccountMeta::new(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.taker_token_acc_a, false),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new(set_up.vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_approve_milestone_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    milestone: u8,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ApproveMilestone {
+        escrow_id: params.escrow_id,
+        milestone,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.maker.pubkey(), true),
+            AccountMeta::new(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.taker_token_acc_a, false),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new(set_up.vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_approve_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    approver: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::Approve {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new(set_up.maker.pubkey(), false),
+            AccountMeta::new(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.taker_token_acc_a, false),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new(set_up.vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Lists the accounts shared by `ConfirmEscrow` and `Resolve`, with `signer` signing if it
+/// is the maker or the taker
+fn settle_accounts(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    signer: &Pubkey,
+) -> Vec<AccountMeta> {
+    let maker = set_up.maker.pubkey();
+    let taker = set_up.taker.pubkey();
+    vec![
+        AccountMeta::new(maker, maker == *signer),
+        AccountMeta::new(taker, taker == *signer),
+        AccountMeta::new(set_up.mint_a_pubkey, false),
+        AccountMeta::new(set_up.mint_b_pubkey, false),
+        AccountMeta::new(set_up.maker_token_acc_a, false),
+        AccountMeta::new(set_up.maker_token_acc_b, false),
+        AccountMeta::new(set_up.taker_token_acc_a, false),
+        AccountMeta::new(set_up.taker_token_acc_b, false),
+        AccountMeta::new(set_up.vault_pda, false),
+        AccountMeta::new(
+            find_payment_vault(&set_up.program_id, &maker, params.escrow_id).0,
+            false,
+        ),
+        AccountMeta::new(set_up.escrow_pda, false),
+        AccountMeta::new_readonly(set_up.token_program_a, false),
+        AccountMeta::new_readonly(set_up.token_program_b, false),
+    ]
+}
+
+pub fn create_confirm_escrow_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    signer: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::ConfirmEscrow {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: settle_accounts(set_up, params, signer),
+        data: instruction_data,
+    }
+}
+
+pub fn create_resolve_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    arbiter: &Pubkey,
+    release_bps: u16,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::Resolve {
+        escrow_id: params.escrow_id,
+        release_bps,
+    }
+    .pack();
+    let mut accounts = settle_accounts(set_up, params, arbiter);
+    accounts.push(AccountMeta::new_readonly(*arbiter, true));
+    Instruction {
+        program_id: set_up.program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+pub fn create_migrate_escrow_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::MigrateEscrow {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.maker.pubkey(), true),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new_readonly(set_up.vault_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(&set_up.program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_update_escrow_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    receive_amount: Option<u64>,
+    deposit_amount: u64,
+    withdraw_amount: u64,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::UpdateEscrow {
+        escrow_id: params.escrow_id,
+        receive_amount,
+        deposit_amount,
+        withdraw_amount,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(set_up.maker.pubkey(), true),
+            AccountMeta::new_readonly(set_up.mint_a_pubkey, false),
+            AccountMeta::new(set_up.maker_token_acc_a, false),
+            AccountMeta::new(set_up.escrow_pda, false),
+            AccountMeta::new(set_up.vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Builds a cancel signed by `operator` on the maker's behalf
+pub fn create_operator_cancel_instruction(
+    set_up: &mut EscrowTestSetup,
+    params: &EscrowParams,
+    operator: &Pubkey,
+) -> Instruction {
+    let mut instruction = create_refund_escrow_instruction(set_up, params);
+    instruction.accounts[0].is_signer = false;
+    instruction
+        .accounts
+        .insert(7, AccountMeta::new_readonly(*operator, true));
+    instruction
+}
+
+/// Builds an update signed by `operator` on the maker's behalf
+pub fn create_operator_update_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    operator: &Pubkey,
+    receive_amount: Option<u64>,
+    deposit_amount: u64,
+    withdraw_amount: u64,
+) -> Instruction {
+    let mut instruction = create_update_escrow_instruction(
+        set_up,
+        params,
+        receive_amount,
+        deposit_amount,
+        withdraw_amount,
+    );
+    instruction.accounts[0].is_signer = false;
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*operator, true));
+    instruction
+}
+
+pub fn create_set_operator_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    signer: &Pubkey,
+    operator: Option<Pubkey>,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::SetOperator {
+        escrow_id: params.escrow_id,
+        operator,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(set_up.escrow_pda, false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Derive the program-wide config PDA
+pub fn derive_config_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[Config::SEED], program_id).0
+}
+
+/// Derive the program's ProgramData account under the upgradeable BPF loader
+pub fn derive_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Records `authority` as the program's upgrade authority in a ProgramData account, which
+/// litesvm does not create for programs it loads directly
+pub fn set_upgrade_authority(set_up: &mut EscrowTestSetup, authority: &Pubkey) {
+    let mut data = Vec::new();
+    data.extend_from_slice(&3u32.to_le_bytes()); // The loader's ProgramData state
+    data.extend_from_slice(&0u64.to_le_bytes()); // Slot the program was deployed in
+    data.push(1); // The authority is set
+    data.extend_from_slice(authority.as_ref());
+    let lamports = set_up.svm.minimum_balance_for_rent_exemption(data.len());
+    set_up
+        .svm
+        .set_account(
+            derive_program_data_address(&set_up.program_id),
+            solana_sdk::account::Account {
+                lamports,
+                data,
+                owner: bpf_loader_upgradeable::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .expect("failed to set the ProgramData account");
+}
+
+pub fn create_initialize_config_instruction(
+    set_up: &EscrowTestSetup,
+    admin: &Pubkey,
+    fee_bps: u16,
+    fee_recipient: &Pubkey,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::InitializeConfig {
+        fee_bps,
+        fee_recipient: *fee_recipient,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(derive_config_pda(&set_up.program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(derive_program_data_address(&set_up.program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+pub fn create_update_config_instruction(
+    set_up: &EscrowTestSetup,
+    admin: &Pubkey,
+    fee_bps: Option<u16>,
+    fee_recipient: Option<Pubkey>,
+    paused: Option<bool>,
+) -> Instruction {
+    let instruction_data = EscrowInstruction::UpdateConfig {
+        new_admin: None,
+        fee_bps,
+        fee_recipient,
+        paused,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(derive_config_pda(&set_up.program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Derives the bid and bid vault PDAs of the taker on the test escrow
+pub fn derive_bid_pdas(set_up: &EscrowTestSetup) -> (Pubkey, Pubkey) {
+    let (bid_pda, _) = Bid::find_address(
+        &set_up.program_id,
+        &set_up.escrow_pda,
+        &set_up.taker.pubkey(),
+    );
+    let (bid_vault_pda, _) = Bid::find_vault_address(
+        &set_up.program_id,
+        &set_up.escrow_pda,
+        &set_up.taker.pubkey(),
+    );
+    (bid_pda, bid_vault_pda)
+}
+
+/// Builds a bid by the taker for everything the escrow offers
+pub fn create_place_bid_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+    bid_amount: u64,
+    expected_offered: u64,
+) -> Instruction {
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(set_up);
+    let instruction_data = EscrowInstruction::PlaceBid {
+        escrow_id: params.escrow_id,
+        bid_amount,
+        expected_offered,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.taker.pubkey(), true),
+            AccountMeta::new_readonly(set_up.mint_b_pubkey, false),
+            AccountMeta::new(set_up.taker_token_acc_b, false),
+            AccountMeta::new_readonly(set_up.escrow_pda, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(set_up.token_program_b, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(&set_up.program_id), false),
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Builds the maker's acceptance of the taker's bid
+pub fn create_accept_bid_instruction(
+    set_up: &EscrowTestSetup,
+    params: &EscrowParams,
+) -> Instruction {
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(set_up);
+    let instruction_data = EscrowInstruction::AcceptBid {
+        escrow_id: params.escrow_id,
+    }
+    .pack();
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.maker.pubkey(), true),
+            AccountMeta::new(set_up.taker.pubkey(), false),
+            AccountMeta::new(set_up.mint_a_pubkey, false),
             AccountMeta::new(set_up.mint_b_pubkey, false),
             AccountMeta::new(set_up.maker_token_acc_b, false),
             AccountMeta::new(set_up.taker_token_acc_a, false),
-            AccountMeta::new(set_up.taker_token_acc_b, false),
             AccountMeta::new(set_up.vault_pda, false),
             AccountMeta::new(set_up.escrow_pda, false),
-            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_a, false),
+            AccountMeta::new_readonly(set_up.token_program_b, false),
+            AccountMeta::new_readonly(derive_config_pda(&set_up.program_id), false),
         ],
         data: instruction_data,
     }
 }
+
+/// Builds the taker's withdrawal of their bid
+pub fn create_withdraw_bid_instruction(set_up: &EscrowTestSetup) -> Instruction {
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(set_up);
+    Instruction {
+        program_id: set_up.program_id,
+        accounts: vec![
+            AccountMeta::new(set_up.taker.pubkey(), true),
+            AccountMeta::new(set_up.mint_b_pubkey, false),
+            AccountMeta::new(set_up.taker_token_acc_b, false),
+            AccountMeta::new(bid_pda, false),
+            AccountMeta::new(bid_vault_pda, false),
+            AccountMeta::new_readonly(set_up.token_program_b, false),
+        ],
+        data: EscrowInstruction::WithdrawBid.pack(),
+    }
+}
+
+/// Creates an `m`-of-n SPL Token multisig over `members`, paid for by `payer`
+pub fn create_multisig(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    members: &[&Pubkey],
+    m: u8,
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let multisig = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(Multisig::LEN);
+    let instructions = [
+        create_account(
+            &payer.pubkey(),
+            &multisig.pubkey(),
+            rent,
+            Multisig::LEN as u64,
+            &TOKEN_PROGRAM_ID,
+        ),
+        initialize_multisig(&TOKEN_PROGRAM_ID, &multisig.pubkey(), members, m)?,
+    ];
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[payer, &multisig],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| format!("Failed to create multisig: {:?}", e))?;
+    println!(
+        "Created {}-of-{} multisig: {}",
+        m,
+        members.len(),
+        multisig.pubkey()
+    );
+    Ok(multisig.pubkey())
+}
+/// Account metas handing an SPL multisig and its signing members to the program
+pub fn multisig_accounts(multisig: &Pubkey, signers: &[&Pubkey]) -> Vec<AccountMeta> {
+    std::iter::once(AccountMeta::new_readonly(*multisig, false))
+        .chain(
+            signers
+                .iter()
+                .map(|signer| AccountMeta::new_readonly(**signer, true)),
+        )
+        .collect()
+}
+/// Send a transaction paid for by `payer` and co-signed by `signers`
+pub fn send_transaction_with_signers(
+    svm: &mut LiteSVM,
+    instruction: Instruction,
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &all_signers,
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| format!("Transaction failed: {:?}", e))?;
+    println!("Transaction sent successfully");
+    Ok(())
+}
@@ -0,0 +1,147 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Reads the balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("token account not found");
+    unpack_token_account(&account.data).amount
+}
+
+/// Opens an escrow paying the taker 100 Token A once two of three new approvers approve
+fn open_approval_escrow(
+    set_up: &mut EscrowTestSetup,
+    escrow_id: u64,
+) -> (EscrowParams, Vec<Keypair>) {
+    let approvers: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for approver in &approvers {
+        set_up
+            .svm
+            .airdrop(&approver.pubkey(), 1_000_000_000)
+            .expect("failed to airdrop to approver");
+    }
+    let params = EscrowParams {
+        escrow_id,
+        deposit_amount: 100_000_000,
+        receive_amount: 0,
+        allowed_taker: Some(set_up.taker.pubkey()),
+        approvers: approvers.iter().map(|approver| approver.pubkey()).collect(),
+        approval_threshold: 2,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    (params, approvers)
+}
+
+// TEST 1: Record One Approval
+// Test that an approval escrow cannot be taken, and that an approval below the threshold is only
+// recorded
+#[test]
+fn test_approval_below_threshold_is_recorded() {
+    println!("\n========== TEST: Approval Below Threshold Is Recorded ==========\n");
+    let mut set_up = setup_escrow_test(240).expect("Setup failed");
+    let (params, approvers) = open_approval_escrow(&mut set_up, 240);
+
+    let release_instruction = create_release_funds_instruction(&mut set_up, &params, 1);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "An approval escrow should not be taken");
+
+    let approve_instruction = create_approve_instruction(&set_up, &params, &approvers[1].pubkey());
+    send_transaction(&mut set_up.svm, approve_instruction, &approvers[1]).expect("Approve failed");
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::InProgress);
+    assert_eq!(escrow.approvals(), 1);
+    assert!(escrow.approvers[1].approved);
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_a), 0);
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    println!("\nApproval Record Test PASSED!\n");
+}
+
+// TEST 2: Reach The Threshold
+// Test that the approval reaching the threshold pays the recipient and closes the escrow
+#[test]
+fn test_threshold_approval_releases_and_closes() {
+    println!("\n========== TEST: Threshold Approval Releases ==========\n");
+    let mut set_up = setup_escrow_test(241).expect("Setup failed");
+    let (params, approvers) = open_approval_escrow(&mut set_up, 241);
+
+    for approver in [&approvers[2], &approvers[0]] {
+        let approve_instruction = create_approve_instruction(&set_up, &params, &approver.pubkey());
+        send_transaction(&mut set_up.svm, approve_instruction, approver).expect("Approve failed");
+    }
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none(),
+        "Vault should be closed"
+    );
+    println!("\nThreshold Release Test PASSED!\n");
+}
+
+// TEST 3: Only Approvers, Only Once
+// Test that a key outside the committee cannot approve, and that an approver cannot approve twice
+#[test]
+fn test_approvals_need_distinct_approvers() {
+    println!("\n========== TEST: Approvals Need Distinct Approvers ==========\n");
+    let mut set_up = setup_escrow_test(242).expect("Setup failed");
+    let (params, approvers) = open_approval_escrow(&mut set_up, 242);
+
+    let approve_instruction = create_approve_instruction(&set_up, &params, &set_up.taker.pubkey());
+    let result = send_transaction(&mut set_up.svm, approve_instruction, &set_up.taker);
+    assert!(result.is_err(), "The recipient is not an approver");
+
+    let approve_instruction = create_approve_instruction(&set_up, &params, &approvers[0].pubkey());
+    send_transaction(&mut set_up.svm, approve_instruction, &approvers[0]).expect("Approve failed");
+    set_up.svm.expire_blockhash();
+    let approve_instruction = create_approve_instruction(&set_up, &params, &approvers[0].pubkey());
+    let result = send_transaction(&mut set_up.svm, approve_instruction, &approvers[0]);
+    assert!(result.is_err(), "An approver should only approve once");
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    println!("\nDistinct Approvers Test PASSED!\n");
+}
+
+// TEST 4: Threshold Must Be Reachable
+// Test that a threshold above the number of approvers is rejected
+#[test]
+fn test_threshold_above_approvers_rejected() {
+    println!("\n========== TEST: Threshold Above Approvers ==========\n");
+    let mut set_up = setup_escrow_test(243).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 243,
+        deposit_amount: 100_000_000,
+        receive_amount: 0,
+        allowed_taker: Some(set_up.taker.pubkey()),
+        approvers: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        approval_threshold: 3,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "Two approvers cannot reach three approvals"
+    );
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nThreshold Test PASSED!\n");
+}
@@ -0,0 +1,236 @@
+mod common;
+use common::*;
+use escrow_native::{
+    arbiter::find_payment_vault,
+    state::{Escrow, EscrowStatus},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Reads the balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("token account not found");
+    unpack_token_account(&account.data).amount
+}
+
+/// Opens an escrow settled by a new arbiter and has the taker take it in full
+fn take_arbiter_escrow(set_up: &mut EscrowTestSetup, escrow_id: u64) -> (EscrowParams, Keypair) {
+    let arbiter = Keypair::new();
+    set_up
+        .svm
+        .airdrop(&arbiter.pubkey(), 1_000_000_000)
+        .expect("failed to airdrop to arbiter");
+    let params = EscrowParams {
+        escrow_id,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        arbiter: Some(arbiter.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let release_instruction =
+        create_release_funds_instruction(set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+    (params, arbiter)
+}
+
+// TEST 1: Take Holds Both Sides
+// Test that taking an arbiter escrow holds the payment in its own vault, next to Token A
+#[test]
+fn test_arbiter_take_holds_payment() {
+    println!("\n========== TEST: Arbiter Take Holds Payment ==========\n");
+    let mut set_up = setup_escrow_test(220).expect("Setup failed");
+    let maker_token_b_before = token_balance(&set_up, &set_up.maker_token_acc_b);
+    let (params, _arbiter) = take_arbiter_escrow(&mut set_up, 220);
+
+    let (payment_vault, _) = find_payment_vault(&set_up.program_id, &set_up.maker.pubkey(), 220);
+    assert_eq!(
+        token_balance(&set_up, &payment_vault),
+        params.receive_amount
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        maker_token_b_before
+    );
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::Held);
+    assert_eq!(escrow.taker, Some(set_up.taker.pubkey()));
+    assert_eq!(
+        escrow.payment_vault_address(&set_up.program_id).unwrap(),
+        payment_vault
+    );
+    println!("\nArbiter Take Test PASSED!\n");
+}
+
+// TEST 2: Both Parties Confirm
+// Test that one confirmation only records it, and the second completes the trade and closes
+// the escrow
+#[test]
+fn test_arbiter_both_confirm_completes_trade() {
+    println!("\n========== TEST: Arbiter Both Confirm ==========\n");
+    let mut set_up = setup_escrow_test(221).expect("Setup failed");
+    let maker_token_b_before = token_balance(&set_up, &set_up.maker_token_acc_b);
+    let (params, _arbiter) = take_arbiter_escrow(&mut set_up, 221);
+
+    let confirm_instruction =
+        create_confirm_escrow_instruction(&set_up, &params, &set_up.maker.pubkey());
+    send_transaction(&mut set_up.svm, confirm_instruction, &set_up.maker)
+        .expect("Maker confirm failed");
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("escrow should stay open until both confirm");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert!(escrow.maker_confirmed && !escrow.taker_confirmed);
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_a), 0);
+
+    let confirm_instruction =
+        create_confirm_escrow_instruction(&set_up, &params, &set_up.taker.pubkey());
+    send_transaction(&mut set_up.svm, confirm_instruction, &set_up.taker)
+        .expect("Taker confirm failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        maker_token_b_before + params.receive_amount
+    );
+    let (payment_vault, _) = find_payment_vault(&set_up.program_id, &set_up.maker.pubkey(), 221);
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    assert!(
+        set_up.svm.get_account(&payment_vault).is_none(),
+        "Payment vault should be closed"
+    );
+    println!("\nArbiter Confirm Test PASSED!\n");
+}
+
+// TEST 3: Arbiter Resolves
+// Test that only the arbiter can resolve, and that its ratio splits both vaults
+#[test]
+fn test_arbiter_resolve_splits_vaults() {
+    println!("\n========== TEST: Arbiter Resolve Splits Vaults ==========\n");
+    let mut set_up = setup_escrow_test(222).expect("Setup failed");
+    let maker_token_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+    let maker_token_b_before = token_balance(&set_up, &set_up.maker_token_acc_b);
+    let taker_token_b_before = token_balance(&set_up, &set_up.taker_token_acc_b);
+    let (params, arbiter) = take_arbiter_escrow(&mut set_up, 222);
+
+    let resolve_instruction =
+        create_resolve_instruction(&set_up, &params, &set_up.taker.pubkey(), 10_000);
+    let result = send_transaction(&mut set_up.svm, resolve_instruction, &set_up.taker);
+    assert!(result.is_err(), "Only the arbiter should resolve");
+
+    // A quarter of the trade goes ahead; the rest goes back to whoever funded it.
+    let resolve_instruction =
+        create_resolve_instruction(&set_up, &params, &arbiter.pubkey(), 2_500);
+    send_transaction(&mut set_up.svm, resolve_instruction, &arbiter).expect("Resolve failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount / 4
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        maker_token_a_before - params.deposit_amount / 4
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        maker_token_b_before + params.receive_amount / 4
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_b),
+        taker_token_b_before - params.receive_amount / 4
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    println!("\nArbiter Resolve Test PASSED!\n");
+}
+
+// TEST 4: Maker As Arbiter
+// Test that the maker cannot arbitrate their own escrow
+#[test]
+fn test_arbiter_rejects_maker_as_arbiter() {
+    println!("\n========== TEST: Arbiter Rejects Maker ==========\n");
+    let mut set_up = setup_escrow_test(223).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 223,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        arbiter: Some(set_up.maker.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(result.is_err(), "The maker should not be the arbiter");
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nArbiter Maker Test PASSED!\n");
+}
+
+// TEST 5: Re-initialize A Held Escrow
+// Test that the maker cannot reset a held arbiter escrow to Open by initializing it again
+#[test]
+fn test_arbiter_reinitialize_fails() {
+    println!("\n========== TEST: Arbiter Re-initialize Fails ==========\n");
+    let mut set_up = setup_escrow_test(224).expect("Setup failed");
+    let (params, arbiter) = take_arbiter_escrow(&mut set_up, 224);
+
+    set_up.svm.expire_blockhash();
+    let reinit_params = EscrowParams {
+        escrow_id: 224,
+        deposit_amount: params.deposit_amount,
+        receive_amount: params.receive_amount,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &reinit_params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "An existing escrow should not be re-initialized"
+    );
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::Held);
+    assert_eq!(escrow.arbiter, Some(arbiter.pubkey()));
+    assert_eq!(escrow.beneficiary, Some(set_up.taker.pubkey()));
+    println!("\nArbiter Re-initialize Test PASSED!\n");
+}
+
+// TEST 6: Pre-funded Payment Vault
+// Test that sending lamports to the payment vault PDA before the take does not block it
+#[test]
+fn test_arbiter_take_with_prefunded_payment_vault() {
+    println!("\n========== TEST: Arbiter Take With Pre-funded Payment Vault ==========\n");
+    let mut set_up = setup_escrow_test(225).expect("Setup failed");
+    let (payment_vault, _) = find_payment_vault(&set_up.program_id, &set_up.maker.pubkey(), 225);
+    set_up
+        .svm
+        .airdrop(&payment_vault, 1_000_000)
+        .expect("failed to fund payment vault PDA");
+
+    let (params, _arbiter) = take_arbiter_escrow(&mut set_up, 225);
+    assert_eq!(
+        token_balance(&set_up, &payment_vault),
+        params.receive_amount
+    );
+    println!("\nPre-funded Payment Vault Test PASSED!\n");
+}
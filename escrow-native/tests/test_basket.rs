@@ -0,0 +1,251 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+/// Adds an offered mint C funded by the maker and a requested mint D funded by the taker,
+/// with empty accounts for the other side of each leg.
+/// Returns (mint C, maker C account, taker C account, mint D, taker D account, maker D account)
+fn setup_basket_mints(
+    set_up: &mut EscrowTestSetup,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey, Pubkey, Pubkey) {
+    let token_program = set_up.token_program_a;
+    let (mint_c, maker_token_acc_c) = setup_token_with_account(
+        &mut set_up.svm,
+        &Keypair::new(),
+        &set_up.maker,
+        1_000_000_000,
+        &token_program,
+        None,
+    )
+    .expect("failed to set up mint C");
+    let taker_token_acc_c = create_token_account(
+        &mut set_up.svm,
+        &set_up.taker,
+        &mint_c,
+        &set_up.taker.pubkey(),
+    )
+    .expect("failed to create taker C account");
+    let (mint_d, taker_token_acc_d) = setup_token_with_account(
+        &mut set_up.svm,
+        &Keypair::new(),
+        &set_up.taker,
+        1_000_000_000,
+        &token_program,
+        None,
+    )
+    .expect("failed to set up mint D");
+    let maker_token_acc_d = create_token_account(
+        &mut set_up.svm,
+        &set_up.maker,
+        &mint_d,
+        &set_up.maker.pubkey(),
+    )
+    .expect("failed to create maker D account");
+    (
+        mint_c,
+        maker_token_acc_c,
+        taker_token_acc_c,
+        mint_d,
+        taker_token_acc_d,
+        maker_token_acc_d,
+    )
+}
+
+// TEST 1: Basket Take
+// Test that taking a basket escrow moves every offered and requested leg in one transaction
+#[test]
+fn test_basket_take_settles_every_leg() {
+    println!("\n========== TEST: Basket Take Settles Every Leg ==========\n");
+    let mut set_up = setup_escrow_test(110).expect("Setup failed");
+    let (
+        mint_c,
+        maker_token_acc_c,
+        taker_token_acc_c,
+        mint_d,
+        taker_token_acc_d,
+        maker_token_acc_d,
+    ) = setup_basket_mints(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 110,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        basket_offered: vec![(mint_c, 30_000_000)],
+        basket_requested: vec![(mint_d, 20_000_000)],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("escrow account not found");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.basket_offered.len(), 1);
+    assert_eq!(escrow.basket_offered[0].mint, mint_c);
+    assert_eq!(escrow.basket_offered[0].amount, 30_000_000);
+    assert_eq!(escrow.basket_requested[0].mint, mint_d);
+    assert_eq!(token_balance(&set_up, &maker_token_acc_c), 970_000_000);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount
+    );
+    assert_eq!(token_balance(&set_up, &taker_token_acc_c), 30_000_000);
+    assert_eq!(token_balance(&set_up, &maker_token_acc_d), 20_000_000);
+    assert_eq!(token_balance(&set_up, &taker_token_acc_d), 980_000_000);
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    println!("\nBasket Take Test PASSED!\n");
+}
+
+// TEST 2: Basket Partial Fill
+// Test that a basket escrow cannot be partially filled
+#[test]
+fn test_basket_partial_fill_fails() {
+    println!("\n========== TEST: Basket Partial Fill Fails ==========\n");
+    let mut set_up = setup_escrow_test(111).expect("Setup failed");
+    let (mint_c, _, _, mint_d, _, _) = setup_basket_mints(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 111,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        basket_offered: vec![(mint_c, 30_000_000)],
+        basket_requested: vec![(mint_d, 20_000_000)],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "A partial fill of a basket should fail");
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_a), 0);
+    println!("\nBasket Partial Fill Test PASSED!\n");
+}
+
+// TEST 3: Basket Cancel
+// Test that cancelling a basket escrow refunds every offered leg and closes their vaults
+#[test]
+fn test_basket_cancel_refunds_every_leg() {
+    println!("\n========== TEST: Basket Cancel Refunds Every Leg ==========\n");
+    let mut set_up = setup_escrow_test(112).expect("Setup failed");
+    let (mint_c, maker_token_acc_c, _, _, _, _) = setup_basket_mints(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 112,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        basket_offered: vec![(mint_c, 30_000_000)],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    assert_eq!(token_balance(&set_up, &maker_token_acc_c), 970_000_000);
+
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    send_transaction(&mut set_up.svm, cancel_instruction, &set_up.maker).expect("Cancel failed");
+
+    assert_eq!(token_balance(&set_up, &maker_token_acc_c), 1_000_000_000);
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        1_000_000_000
+    );
+    let (basket_vault, _) = escrow_native::basket::find_basket_vault(
+        &set_up.program_id,
+        &set_up.maker.pubkey(),
+        params.escrow_id,
+        0,
+    );
+    assert!(
+        set_up.svm.get_account(&basket_vault).is_none(),
+        "Basket vault should be closed"
+    );
+    println!("\nBasket Cancel Test PASSED!\n");
+}
+
+// TEST 4: Stored Leg Bumps
+// Test that a basket escrow stores the vault bump of each offered leg
+#[test]
+fn test_basket_stores_leg_vault_bumps() {
+    println!("\n========== TEST: Basket Stores Leg Vault Bumps ==========\n");
+    let mut set_up = setup_escrow_test(113).expect("Setup failed");
+    let (mint_c, _, _, _, _, _) = setup_basket_mints(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 113,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        basket_offered: vec![(mint_c, 30_000_000)],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let escrow_account = set_up.svm.get_account(&set_up.escrow_pda).unwrap();
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    let (basket_vault, basket_vault_bump) = escrow_native::basket::find_basket_vault(
+        &set_up.program_id,
+        &set_up.maker.pubkey(),
+        params.escrow_id,
+        0,
+    );
+    assert_eq!(escrow.basket_offered[0].vault_bump, basket_vault_bump);
+    assert_eq!(
+        escrow.basket_vault_address(&set_up.program_id, 0).unwrap(),
+        basket_vault
+    );
+    println!("\nStored Leg Bumps Test PASSED!\n");
+}
+
+// TEST 5: Pre-funded Leg Vault
+// Test that sending lamports to a leg's vault PDA before the escrow is made does not block it
+#[test]
+fn test_basket_with_prefunded_leg_vault() {
+    println!("\n========== TEST: Basket With Pre-funded Leg Vault ==========\n");
+    let mut set_up = setup_escrow_test(114).expect("Setup failed");
+    let (mint_c, maker_token_acc_c, _, _, _, _) = setup_basket_mints(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 114,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        basket_offered: vec![(mint_c, 30_000_000)],
+        ..Default::default()
+    };
+    let (basket_vault, _) = escrow_native::basket::find_basket_vault(
+        &set_up.program_id,
+        &set_up.maker.pubkey(),
+        params.escrow_id,
+        0,
+    );
+    set_up
+        .svm
+        .airdrop(&basket_vault, 1_000_000)
+        .expect("failed to fund basket vault PDA");
+
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    assert_eq!(token_balance(&set_up, &basket_vault), 30_000_000);
+    assert_eq!(token_balance(&set_up, &maker_token_acc_c), 970_000_000);
+    println!("\nPre-funded Leg Vault Test PASSED!\n");
+}
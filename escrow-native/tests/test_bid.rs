@@ -0,0 +1,158 @@
+mod common;
+use common::*;
+use escrow_native::state::Bid;
+use solana_sdk::pubkey::Pubkey;
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+/// Creates a 100 Token A for 50 Token B escrow with the given id
+fn setup_open_escrow(escrow_id: u64) -> (EscrowTestSetup, EscrowParams) {
+    let mut set_up = setup_escrow_test(escrow_id).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    (set_up, params)
+}
+
+// TEST 1: Accept Bid
+// Test that the maker can accept a lower bid, settling the whole escrow at the bid's price
+#[test]
+fn test_accept_bid_settles_escrow() {
+    println!("\n========== TEST: Accept Bid Settles Escrow ==========\n");
+    let (mut set_up, params) = setup_open_escrow(120);
+    let bid_amount = 40_000_000;
+    let taker_b_before = token_balance(&set_up, &set_up.taker_token_acc_b);
+
+    let bid_instruction =
+        create_place_bid_instruction(&set_up, &params, bid_amount, params.deposit_amount);
+    send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker).expect("Place bid failed");
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(&set_up);
+    assert_eq!(token_balance(&set_up, &bid_vault_pda), bid_amount);
+    assert_eq!(
+        taker_b_before - token_balance(&set_up, &set_up.taker_token_acc_b),
+        bid_amount
+    );
+
+    let accept_instruction = create_accept_bid_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, accept_instruction, &set_up.maker)
+        .expect("Accept bid failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        bid_amount
+    );
+    for closed in [set_up.escrow_pda, set_up.vault_pda, bid_pda, bid_vault_pda] {
+        assert!(
+            set_up.svm.get_account(&closed).is_none(),
+            "Account {} should be closed",
+            closed
+        );
+    }
+    println!("\nAccept Bid Test PASSED!\n");
+}
+
+// TEST 2: Withdraw Bid
+// Test that the bidder can withdraw a bid and get their Token B back
+#[test]
+fn test_withdraw_bid_refunds_bidder() {
+    println!("\n========== TEST: Withdraw Bid Refunds Bidder ==========\n");
+    let (mut set_up, params) = setup_open_escrow(121);
+    let taker_b_before = token_balance(&set_up, &set_up.taker_token_acc_b);
+
+    let bid_instruction =
+        create_place_bid_instruction(&set_up, &params, 40_000_000, params.deposit_amount);
+    send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker).expect("Place bid failed");
+    let withdraw_instruction = create_withdraw_bid_instruction(&set_up);
+    send_transaction(&mut set_up.svm, withdraw_instruction, &set_up.taker)
+        .expect("Withdraw bid failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_b),
+        taker_b_before
+    );
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(&set_up);
+    assert!(set_up.svm.get_account(&bid_pda).is_none());
+    assert!(set_up.svm.get_account(&bid_vault_pda).is_none());
+    // The escrow itself is untouched.
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_some());
+    println!("\nWithdraw Bid Test PASSED!\n");
+}
+
+// TEST 3: Stale Bid
+// Test that a bid cannot be accepted once a partial fill has changed the offer
+#[test]
+fn test_accept_stale_bid_fails() {
+    println!("\n========== TEST: Accept Stale Bid Fails ==========\n");
+    let (mut set_up, params) = setup_open_escrow(122);
+    let bid_instruction =
+        create_place_bid_instruction(&set_up, &params, 40_000_000, params.deposit_amount);
+    send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker).expect("Place bid failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Partial fill failed");
+
+    let accept_instruction = create_accept_bid_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, accept_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "A bid for the old offer should not be accepted"
+    );
+
+    println!("\nStale Bid Test PASSED!\n");
+}
+
+// TEST 4: Pre-funded Bid
+// Test that sending lamports to the bid PDAs before the bid is placed does not block it
+#[test]
+fn test_place_bid_with_prefunded_pdas() {
+    println!("\n========== TEST: Place Bid With Pre-funded PDAs ==========\n");
+    let (mut set_up, params) = setup_open_escrow(124);
+    let (bid_pda, bid_vault_pda) = derive_bid_pdas(&set_up);
+    for pda in [bid_pda, bid_vault_pda] {
+        set_up
+            .svm
+            .airdrop(&pda, 1_000_000)
+            .expect("failed to fund bid PDA");
+    }
+
+    let bid_instruction =
+        create_place_bid_instruction(&set_up, &params, 40_000_000, params.deposit_amount);
+    send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker).expect("Place bid failed");
+    assert_eq!(token_balance(&set_up, &bid_vault_pda), 40_000_000);
+    let bid_account = set_up.svm.get_account(&bid_pda).unwrap();
+    assert_eq!(bid_account.owner, set_up.program_id);
+    assert_eq!(
+        Bid::unpack_the_slice_data(&bid_account.data)
+            .expect("failed to unpack bid")
+            .amount,
+        40_000_000
+    );
+
+    let bid_instruction =
+        create_place_bid_instruction(&set_up, &params, 30_000_000, params.deposit_amount);
+    set_up.svm.expire_blockhash();
+    let result = send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A bidder should only have one bid per escrow"
+    );
+    println!("\nPre-funded Bid Test PASSED!\n");
+}
@@ -0,0 +1,130 @@
+mod common;
+use common::*;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Set to rewrite the baseline with the measured compute units instead of checking them
+const UPDATE_ENV: &str = "UPDATE_CU_BASELINE";
+/// Headroom added to the measured units when the baseline is rewritten
+const HEADROOM_PERCENT: u64 = 10;
+
+fn baseline_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/compute_budget_baseline.txt")
+}
+
+/// Reads the `<instruction> <units>` lines of the baseline, skipping comments
+fn read_baseline() -> BTreeMap<String, u64> {
+    let contents = fs::read_to_string(baseline_path()).expect("failed to read CU baseline");
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, units) = line
+                .split_once(char::is_whitespace)
+                .unwrap_or_else(|| panic!("malformed baseline line: {}", line));
+            let units = units
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("malformed baseline units: {}", line));
+            (name.to_string(), units)
+        })
+        .collect()
+}
+
+/// Comment header of a baseline written from measured units
+const MEASURED_HEADER: &str = "\
+# Most compute units each instruction may use, checked by tests/test_compute_budget.rs.
+# Clients size their compute-budget instructions from these numbers.
+# The numbers are the measured units plus 10% headroom. After an intended change in cost,
+# build the program and regenerate them with:
+#   UPDATE_CU_BASELINE=1 cargo test --test test_compute_budget
+";
+
+/// Rewrites the baseline with `measured` plus the headroom, under the measured header
+fn write_baseline(measured: &[(&str, u64)]) {
+    let mut contents = MEASURED_HEADER.to_string();
+    for (name, units) in measured {
+        contents += &format!("{} {}\n", name, units + units * HEADROOM_PERCENT / 100);
+    }
+    fs::write(baseline_path(), contents).expect("failed to write CU baseline");
+}
+
+/// Measures a single-pair escrow being created and then fully taken or cancelled
+fn measure_compute_units() -> Vec<(&'static str, u64)> {
+    let mut set_up = setup_escrow_test(150).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 150,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let initialize_units =
+        send_transaction_with_compute_units(&mut set_up.svm, init_instruction, &set_up.maker)
+            .expect("Initialize failed");
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let release_units =
+        send_transaction_with_compute_units(&mut set_up.svm, release_instruction, &set_up.taker)
+            .expect("Release funds failed");
+
+    let mut set_up = setup_escrow_test(151).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 151,
+        ..params
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    let cancel_units =
+        send_transaction_with_compute_units(&mut set_up.svm, cancel_instruction, &set_up.maker)
+            .expect("Cancel failed");
+
+    vec![
+        ("InitializeEscrow", initialize_units),
+        ("ReleaseFunds", release_units),
+        ("CancelEscrow", cancel_units),
+    ]
+}
+
+// TEST 1: Compute Budget
+// Test that no instruction uses more compute units than the checked-in baseline allows
+#[test]
+fn test_compute_units_within_baseline() {
+    println!("\n========== TEST: Compute Units Within Baseline ==========\n");
+    let measured = measure_compute_units();
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        write_baseline(&measured);
+        println!("Baseline updated at {}", baseline_path().display());
+        return;
+    }
+    let baseline = read_baseline();
+
+    println!(
+        "{:<18} {:>10} {:>10} {:>8}",
+        "Instruction", "Baseline", "Measured", "Delta"
+    );
+    let mut over_budget = Vec::new();
+    for (name, units) in &measured {
+        let budget = *baseline
+            .get(*name)
+            .unwrap_or_else(|| panic!("{} is missing from the CU baseline", name));
+        println!(
+            "{:<18} {:>10} {:>10} {:>+8}",
+            name,
+            budget,
+            units,
+            *units as i64 - budget as i64
+        );
+        if *units > budget {
+            over_budget.push(*name);
+        }
+    }
+    assert!(
+        over_budget.is_empty(),
+        "{:?} used more compute units than the baseline; trim them or rerun with {}=1",
+        over_budget,
+        UPDATE_ENV
+    );
+    println!("\nCompute Budget Test PASSED!\n");
+}
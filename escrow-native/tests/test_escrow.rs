@@ -73,8 +73,9 @@ pub fn verify_escrow(
 fn test_initialize_escrow_success() {
     let params = EscrowParams {
         escrow_id: 10,
-        deposit_amount: 1_000_000_00,
+        deposit_amount: 100_000_000,
         receive_amount: 200_000_000,
+        ..Default::default()
     };
 
     let mut setup = setup_escrow_test(params.escrow_id).expect("Setup failed");
@@ -117,11 +118,12 @@ fn test_refund_escrow_success() {
         escrow_id: 10,
         deposit_amount: 900_000_000,
         receive_amount: 100_000_000,
+        ..Default::default()
     };
     let mut set_up = setup_escrow_test(params.escrow_id).expect("Setup failed");
     // Step 1: Initialize escrow
     println!("\nSTEP 1: Initialize Escrow");
-    let initialize_escrow_instruction = create_initialize_escrow_instruction(&mut set_up, &params);
+    let initialize_escrow_instruction = create_initialize_escrow_instruction(&set_up, &params);
     send_transaction(
         &mut set_up.svm,
         initialize_escrow_instruction,
@@ -220,6 +222,7 @@ fn test_release_funds_success() {
         escrow_id: 10,
         deposit_amount: 200_000_000,
         receive_amount: 100_000_000,
+        ..Default::default()
     };
 
     let mut set_up = setup_escrow_test(params.escrow_id).expect("failed to set escrow setup");
@@ -281,7 +284,8 @@ fn test_release_funds_success() {
     );
     // STEP 3: Execute release_funds (taker sends Token B, receives Token A)
     println!("\nSTEP 3: Execute Release Funds");
-    let release_instruction = create_release_funds_instruction(&mut set_up, &params);
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
     send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
         .expect("failed to send tx");
 
@@ -369,3 +373,150 @@ fn test_release_funds_success() {
     }
     println!("\nRelease Funds Test PASSED!\n");
 }
+// TEST CASE FOR PARTIAL FILLS (SEVERAL TAKERS)
+#[test]
+fn test_release_funds_partial_fill() {
+    println!("\n========== TEST: Release Funds Partial Fill ==========\n");
+    let params = EscrowParams {
+        escrow_id: 11,
+        deposit_amount: 400_000_000,
+        receive_amount: 200_000_000,
+        ..Default::default()
+    };
+
+    let mut set_up = setup_escrow_test(params.escrow_id).expect("failed to set escrow setup");
+    // STEP 1: Initialize escrow (maker deposits Token A)
+    let initialize_escrow_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(
+        &mut set_up.svm,
+        initialize_escrow_instruction,
+        &set_up.maker,
+    )
+    .expect("Initialize failed");
+
+    // STEP 2: Fill a quarter of the escrow
+    println!("\nSTEP 2: Partial Fill");
+    let first_fill = params.receive_amount / 4;
+    let release_instruction = create_release_funds_instruction(&mut set_up, &params, first_fill);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("partial fill failed");
+
+    // Token A is pro-rated at the maker's price and the rest stays in the vault
+    let first_received = params.deposit_amount / 4;
+    verify_vault(
+        &set_up.svm,
+        &set_up.vault_pda,
+        params.deposit_amount - first_received,
+        &set_up.mint_a_pubkey,
+    )
+    .expect("Vault verification failed after partial fill");
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("escrow account should remain open");
+    let escrow_data =
+        Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow");
+    assert_eq!(
+        escrow_data.offered,
+        params.deposit_amount - first_received,
+        "Remaining Token A mismatch"
+    );
+    assert_eq!(
+        escrow_data.receive,
+        params.receive_amount - first_fill,
+        "Remaining Token B mismatch"
+    );
+
+    // STEP 3: Filling more than remains must fail
+    println!("\nSTEP 3: Overfill");
+    let overfill_instruction =
+        create_release_funds_instruction(&mut set_up, &params, escrow_data.receive + 1);
+    let result = send_transaction(&mut set_up.svm, overfill_instruction, &set_up.taker);
+    assert!(result.is_err(), "Overfilling the escrow should fail");
+
+    // STEP 4: Fill the rest of the escrow
+    println!("\nSTEP 4: Final Fill");
+    let final_instruction =
+        create_release_funds_instruction(&mut set_up, &params, escrow_data.receive);
+    send_transaction(&mut set_up.svm, final_instruction, &set_up.taker).expect("final fill failed");
+
+    let taker_a_after = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("failed to get taker a account after");
+    let taker_a_data_after =
+        Account::unpack(&taker_a_after.data).expect("unable to unpack taker a account data after");
+    assert_eq!(
+        taker_a_data_after.amount, params.deposit_amount,
+        "Taker should have received the whole deposit across both fills"
+    );
+    let maker_b_after = set_up
+        .svm
+        .get_account(&set_up.maker_token_acc_b)
+        .expect("failed to get maker b account after");
+    let maker_b_data_after =
+        Account::unpack(&maker_b_after.data).expect("unable to unpack maker b account data after");
+    assert_eq!(
+        maker_b_data_after.amount, params.receive_amount,
+        "Maker should have received the full price across both fills"
+    );
+    // Verify vault is closed once empty
+    if let Some(vault_account) = set_up.svm.get_account(&set_up.vault_pda) {
+        assert!(vault_account.data.is_empty(), "Vault should be closed");
+        println!("Vault closed successfully");
+    }
+    println!("\nPartial Fill Test PASSED!\n");
+}
+// TEST CASE FOR CLOSING THE ESCROW STATE ACCOUNT ON TAKE
+#[test]
+fn test_release_funds_closes_escrow_account() {
+    println!("\n========== TEST: Release Funds Closes Escrow Account ==========\n");
+    let params = EscrowParams {
+        escrow_id: 12,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+
+    let mut set_up = setup_escrow_test(params.escrow_id).expect("failed to set escrow setup");
+    let initialize_escrow_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(
+        &mut set_up.svm,
+        initialize_escrow_instruction,
+        &set_up.maker,
+    )
+    .expect("Initialize failed");
+
+    let maker_lamports_before = set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap();
+    let escrow_rent = set_up.svm.get_balance(&set_up.escrow_pda).unwrap();
+    let vault_rent = set_up.svm.get_balance(&set_up.vault_pda).unwrap();
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    // The escrow state account must be gone and its rent returned to the maker
+    if let Some(escrow_account) = set_up.svm.get_account(&set_up.escrow_pda) {
+        assert_eq!(escrow_account.lamports, 0, "Escrow should hold no lamports");
+        assert!(escrow_account.data.is_empty(), "Escrow data should be gone");
+        assert_eq!(
+            escrow_account.owner,
+            solana_sdk::system_program::id(),
+            "Escrow should be handed back to the System Program"
+        );
+    }
+    let maker_lamports_after = set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap();
+    assert_eq!(
+        maker_lamports_after - maker_lamports_before,
+        escrow_rent + vault_rent,
+        "Maker should get back the rent of both escrow accounts"
+    );
+
+    // The same escrow_id can now be used for a new escrow
+    set_up.svm.expire_blockhash();
+    let reinitialize_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, reinitialize_instruction, &set_up.maker)
+        .expect("Re-initializing the same escrow_id failed");
+    println!("\nClose Escrow Account Test PASSED!\n");
+}
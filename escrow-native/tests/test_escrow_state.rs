@@ -0,0 +1,107 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_sdk::signer::Signer;
+
+/// Reads the escrow state account of the current test setup
+fn escrow_state(set_up: &EscrowTestSetup) -> Escrow {
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow")
+}
+
+// TEST 1: Initial State
+// Test that initialize records the offer and an Open status
+#[test]
+fn test_initialize_records_escrow_state() {
+    println!("\n========== TEST: Initialize Records Escrow State ==========\n");
+    let mut set_up = setup_escrow_test(60).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 60,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let escrow_data = escrow_state(&set_up);
+    assert_eq!(escrow_data.offered, params.deposit_amount);
+    assert_eq!(escrow_data.status, EscrowStatus::Open);
+    println!("\nEscrow State Test PASSED!\n");
+}
+
+// TEST 2: Vault Donation
+// Test that tokens sent straight to the vault do not change the pro-rated partial fill
+#[test]
+fn test_partial_fill_ignores_vault_donation() {
+    println!("\n========== TEST: Partial Fill Ignores Vault Donation ==========\n");
+    let mut set_up = setup_escrow_test(61).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 61,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let donation_instruction = spl_token::instruction::transfer(
+        &set_up.token_program_a,
+        &set_up.maker_token_acc_a,
+        &set_up.vault_pda,
+        &set_up.maker.pubkey(),
+        &[],
+        10_000_000,
+    )
+    .expect("failed to build donation");
+    send_transaction(&mut set_up.svm, donation_instruction, &set_up.maker)
+        .expect("Donation failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Partial fill failed");
+
+    let taker_a_after = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("failed to get taker a account after");
+    assert_eq!(
+        unpack_token_account(&taker_a_after.data).amount,
+        params.deposit_amount / 2,
+        "Partial fill should be pro-rated against the recorded offer"
+    );
+    let escrow_data = escrow_state(&set_up);
+    assert_eq!(escrow_data.offered, params.deposit_amount / 2);
+    assert_eq!(escrow_data.status, EscrowStatus::Open);
+    println!("\nVault Donation Test PASSED!\n");
+}
+
+// TEST 3: Filled Escrow
+// Test that a filled escrow is closed and cannot be cancelled afterwards
+#[test]
+fn test_release_funds_filled_escrow_not_cancellable() {
+    println!("\n========== TEST: Filled Escrow Not Cancellable ==========\n");
+    let mut set_up = setup_escrow_test(62).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 62,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    let refund_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    let result = send_transaction(&mut set_up.svm, refund_instruction, &set_up.maker);
+    assert!(result.is_err(), "A filled escrow should not be cancellable");
+    println!("\nFilled Escrow Test PASSED!\n");
+}
@@ -0,0 +1,98 @@
+mod common;
+use common::*;
+use escrow_native::events::{EscrowCancelled, EscrowCreated, EscrowEvent, EscrowFilled};
+use solana_sdk::signer::Signer;
+
+// TEST 1: Create And Fill Events
+// Test that creating and filling an escrow log typed events with the amounts moved
+#[test]
+fn test_create_and_fill_emit_events() {
+    println!("\n========== TEST: Create And Fill Emit Events ==========\n");
+    let mut set_up = setup_escrow_test(130).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 130,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let events = send_transaction_with_events(&mut set_up.svm, init_instruction, &set_up.maker)
+        .expect("Initialize failed");
+    assert_eq!(
+        events,
+        vec![EscrowEvent::Created(EscrowCreated {
+            escrow: set_up.escrow_pda,
+            maker: set_up.maker.pubkey(),
+            escrow_id: params.escrow_id,
+            token_mint_a: set_up.mint_a_pubkey,
+            token_mint_b: set_up.mint_b_pubkey,
+            offered: params.deposit_amount,
+            receive: params.receive_amount,
+            receive_asset: params.receive_asset,
+            expires_at: None,
+            allowed_taker: None,
+        })]
+    );
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    let events = send_transaction_with_events(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Partial fill failed");
+    assert_eq!(
+        events,
+        vec![EscrowEvent::Filled(EscrowFilled {
+            escrow: set_up.escrow_pda,
+            maker: set_up.maker.pubkey(),
+            taker: set_up.taker.pubkey(),
+            escrow_id: params.escrow_id,
+            token_a_released: params.deposit_amount / 2,
+            token_b_paid: params.receive_amount / 2,
+            fee_paid: 0,
+            remaining_offered: params.deposit_amount / 2,
+            remaining_receive: params.receive_amount / 2,
+        })]
+    );
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    let events = send_transaction_with_events(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Final fill failed");
+    match events.as_slice() {
+        [EscrowEvent::Filled(filled)] => {
+            assert_eq!(filled.remaining_offered, 0);
+            assert_eq!(filled.remaining_receive, 0);
+        }
+        other => panic!("Expected a single fill event, got {:?}", other),
+    }
+    println!("\nCreate And Fill Events Test PASSED!\n");
+}
+
+// TEST 2: Cancel Event
+// Test that cancelling an escrow logs the amount refunded to the maker
+#[test]
+fn test_cancel_emits_event() {
+    println!("\n========== TEST: Cancel Emits Event ==========\n");
+    let mut set_up = setup_escrow_test(131).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 131,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    let events = send_transaction_with_events(&mut set_up.svm, cancel_instruction, &set_up.maker)
+        .expect("Cancel failed");
+    assert_eq!(
+        events,
+        vec![EscrowEvent::Cancelled(EscrowCancelled {
+            escrow: set_up.escrow_pda,
+            maker: set_up.maker.pubkey(),
+            escrow_id: params.escrow_id,
+            refunded: params.deposit_amount,
+        })]
+    );
+    println!("\nCancel Event Test PASSED!\n");
+}
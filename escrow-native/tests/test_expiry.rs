@@ -0,0 +1,114 @@
+mod common;
+use common::*;
+use solana_sdk::{clock::Clock, program_pack::Pack};
+use spl_token::state::Account;
+
+/// Moves the test clock past the given unix timestamp
+fn warp_past(set_up: &mut EscrowTestSetup, expires_at: i64) {
+    let mut clock = set_up.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = expires_at + 1;
+    set_up.svm.set_sysvar(&clock);
+}
+
+// TEST 1: Take After Deadline
+// Test that a taker cannot fill an escrow once it has expired
+#[test]
+fn test_release_funds_after_expiry_fails() {
+    println!("\n========== TEST: Take After Expiry ==========\n");
+    let mut set_up = setup_escrow_test(20).expect("Setup failed");
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 20,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        expires_at: Some(now + 3_600),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    warp_past(&mut set_up, now + 3_600);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "Taking an expired escrow should fail");
+}
+
+// TEST 2: Refund Before Deadline
+// Test that an escrow cannot be refunded permissionlessly before it expires
+#[test]
+fn test_refund_expired_before_deadline_fails() {
+    println!("\n========== TEST: Refund Before Expiry ==========\n");
+    let mut set_up = setup_escrow_test(21).expect("Setup failed");
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 21,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        expires_at: Some(now + 3_600),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let refund_instruction = create_refund_expired_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, refund_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Refunding an escrow before its deadline should fail"
+    );
+}
+
+// TEST 3: Permissionless Refund After Deadline
+// Test that anyone can return an expired escrow to the maker
+#[test]
+fn test_refund_expired_by_anyone() {
+    println!("\n========== TEST: Refund Expired Escrow ==========\n");
+    let mut set_up = setup_escrow_test(22).expect("Setup failed");
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 22,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        expires_at: Some(now + 3_600),
+        ..Default::default()
+    };
+    let maker_a_before = set_up
+        .svm
+        .get_account(&set_up.maker_token_acc_a)
+        .expect("failed to get maker token A account");
+    let maker_a_before = Account::unpack(&maker_a_before.data)
+        .expect("failed to unpack maker token A account")
+        .amount;
+
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    warp_past(&mut set_up, now + 3_600);
+
+    // The taker, not the maker, sends the refund
+    let refund_instruction = create_refund_expired_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, refund_instruction, &set_up.taker)
+        .expect("Expired refund failed");
+
+    let maker_a_after = set_up
+        .svm
+        .get_account(&set_up.maker_token_acc_a)
+        .expect("failed to get maker token A account");
+    let maker_a_after = Account::unpack(&maker_a_after.data)
+        .expect("failed to unpack maker token A account")
+        .amount;
+    assert_eq!(
+        maker_a_after, maker_a_before,
+        "Maker should have the whole deposit back"
+    );
+    if let Some(vault_account) = set_up.svm.get_account(&set_up.vault_pda) {
+        assert!(vault_account.data.is_empty(), "Vault should be closed");
+    }
+    if let Some(escrow_account) = set_up.svm.get_account(&set_up.escrow_pda) {
+        let all_zeros = escrow_account.data.iter().all(|&b| b == 0);
+        assert!(all_zeros, "Escrow data should be zeroed");
+    }
+    println!("\nRefund Expired Test PASSED!\n");
+}
@@ -1,6 +1,8 @@
 mod common;
 use common::*;
+use escrow_native::instructions::instruction::{EscrowInstruction, InitializeEscrowArgs};
 use solana_sdk::{
+    clock::Clock,
     instruction::{AccountMeta, Instruction},
     program_pack::Pack,
     signature::Keypair,
@@ -17,6 +19,7 @@ fn test_initialize_escrow_insufficient_funds() {
         escrow_id: 1,
         deposit_amount: 2_000_000_000_000,
         receive_amount: 1_000_000,
+        ..Default::default()
     };
     let mut set_up = setup_escrow_test(escrow_params.escrow_id).expect("Setup failed");
     let maker_token_acc_a = set_up
@@ -31,7 +34,7 @@ fn test_initialize_escrow_insufficient_funds() {
     println!("Trying to deposit: {} tokens", escrow_params.deposit_amount);
     let instruction = create_initialize_escrow_instruction(&set_up, &escrow_params);
     let result = send_transaction(&mut set_up.svm, instruction, &set_up.maker);
-    println!("");
+    println!();
     assert!(
         result.is_err(),
         "Expected insufficient funds error, but transaction succeeded"
@@ -47,6 +50,7 @@ fn test_initialize_escrow_wrong_mint_a() {
         escrow_id: 102,
         deposit_amount: 100_000_000,
         receive_amount: 200_000_000,
+        ..Default::default()
     };
 
     let wrong_mint_a = Keypair::new();
@@ -58,10 +62,24 @@ fn test_initialize_escrow_wrong_mint_a() {
     println!("Expected mint: {}", setup.mint_a_pubkey);
 
     // Build instruction with wrong mint
-    let mut instruction_data = vec![0u8];
-    instruction_data.extend_from_slice(&params.escrow_id.to_le_bytes());
-    instruction_data.extend_from_slice(&params.deposit_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&params.receive_amount.to_le_bytes());
+    let instruction_data = EscrowInstruction::InitializeEscrow(InitializeEscrowArgs {
+        escrow_id: params.escrow_id,
+        deposit_amount: params.deposit_amount,
+        receive_amount: params.receive_amount,
+        expires_at: params.expires_at,
+        allowed_taker: params.allowed_taker,
+        receive_asset: params.receive_asset,
+        basket_offered: vec![],
+        basket_requested: vec![],
+        operator: params.operator,
+        offer_asset: params.offer_asset,
+        vesting: params.vesting,
+        arbiter: params.arbiter,
+        milestones: params.milestones.clone(),
+        approvers: params.approvers.clone(),
+        approval_threshold: params.approval_threshold,
+    })
+    .pack();
 
     let wrong_instruction = Instruction {
         program_id: setup.program_id,
@@ -76,6 +94,7 @@ fn test_initialize_escrow_wrong_mint_a() {
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(&setup.program_id), false),
         ],
         data: instruction_data,
     };
@@ -94,9 +113,10 @@ fn test_initialize_escrow_zero_deposit() {
         escrow_id: 103,
         deposit_amount: 0, // Invalid: zero deposit
         receive_amount: 200_000_000,
+        ..Default::default()
     };
     let mut set_up = setup_escrow_test(params.escrow_id).expect("failed to setup escrow");
-    let init_instruction = create_initialize_escrow_instruction(&mut set_up, &params);
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
     let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
     assert!(
         result.is_err(),
@@ -111,9 +131,10 @@ fn test_initialize_escrow_zero_receive() {
         escrow_id: 103,
         deposit_amount: 100_000_000, // Invalid: zero deposit
         receive_amount: 0,
+        ..Default::default()
     };
     let mut set_up = setup_escrow_test(params.escrow_id).expect("failed to setup escrow");
-    let init_instruction = create_initialize_escrow_instruction(&mut set_up, &params);
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
     let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
     assert!(
         result.is_err(),
@@ -131,6 +152,7 @@ fn test_initialize_escrow_not_signed_by_maker() {
         escrow_id: 106,
         deposit_amount: 100_000_000,
         receive_amount: 200_000_000,
+        ..Default::default()
     };
 
     let mut setup = setup_escrow_test(params.escrow_id).expect("Setup should succeed");
@@ -142,10 +164,24 @@ fn test_initialize_escrow_not_signed_by_maker() {
         .airdrop(&wrong_signer.pubkey(), 5_000_000_000)
         .expect("Airdrop should succeed");
 
-    let mut instruction_data = vec![0u8];
-    instruction_data.extend_from_slice(&params.escrow_id.to_le_bytes());
-    instruction_data.extend_from_slice(&params.deposit_amount.to_le_bytes());
-    instruction_data.extend_from_slice(&params.receive_amount.to_le_bytes());
+    let instruction_data = EscrowInstruction::InitializeEscrow(InitializeEscrowArgs {
+        escrow_id: params.escrow_id,
+        deposit_amount: params.deposit_amount,
+        receive_amount: params.receive_amount,
+        expires_at: params.expires_at,
+        allowed_taker: params.allowed_taker,
+        receive_asset: params.receive_asset,
+        basket_offered: vec![],
+        basket_requested: vec![],
+        operator: params.operator,
+        offer_asset: params.offer_asset,
+        vesting: params.vesting,
+        arbiter: params.arbiter,
+        milestones: params.milestones.clone(),
+        approvers: params.approvers.clone(),
+        approval_threshold: params.approval_threshold,
+    })
+    .pack();
 
     let instruction = Instruction {
         program_id: setup.program_id,
@@ -161,6 +197,7 @@ fn test_initialize_escrow_not_signed_by_maker() {
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(derive_config_pda(&setup.program_id), false),
         ],
         data: instruction_data,
     };
@@ -185,6 +222,7 @@ fn test_initialize_escrow_duplicate_id() {
         escrow_id: 108,
         deposit_amount: 100_000_000,
         receive_amount: 200_000_000,
+        ..Default::default()
     };
 
     let mut setup = setup_escrow_test(params.escrow_id).expect("Setup should succeed");
@@ -203,3 +241,23 @@ fn test_initialize_escrow_duplicate_id() {
         "Transaction should fail when escrow ID already exists"
     );
 }
+// TEST 9: Expiry In The Past
+// Test when the expiry timestamp is not in the future
+#[test]
+fn test_initialize_escrow_expiry_in_past() {
+    let mut setup = setup_escrow_test(109).expect("Setup should succeed");
+    let now = setup.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 109,
+        deposit_amount: 100_000_000,
+        receive_amount: 200_000_000,
+        expires_at: Some(now),
+        ..Default::default()
+    };
+    let instruction = create_initialize_escrow_instruction(&setup, &params);
+    let result = send_transaction(&mut setup.svm, instruction, &setup.maker);
+    assert!(
+        result.is_err(),
+        "Transaction should fail when expiry is not in the future"
+    );
+}
@@ -0,0 +1,114 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, EscrowStatus, EscrowV0, VersionedEscrow};
+use solana_sdk::{account::Account, signer::Signer};
+
+/// Rewrites the escrow state account in the version 0 layout, as the program used to create it
+fn downgrade_escrow_to_v0(set_up: &mut EscrowTestSetup, params: &EscrowParams) {
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow_data =
+        Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow");
+    let legacy = EscrowV0 {
+        escrow_id: params.escrow_id,
+        maker: escrow_data.maker,
+        token_mint_a: escrow_data.token_mint_a,
+        token_mint_b: escrow_data.token_mint_b,
+        receive: escrow_data.receive,
+        bump: escrow_data.bump,
+    };
+    let data = borsh::to_vec(&legacy).expect("failed to serialize legacy escrow");
+    assert_eq!(data.len(), EscrowV0::ACCOUNT_LEN);
+    let lamports = set_up.svm.minimum_balance_for_rent_exemption(data.len());
+    set_up
+        .svm
+        .set_account(
+            set_up.escrow_pda,
+            Account {
+                lamports,
+                data,
+                owner: set_up.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .expect("failed to write legacy escrow");
+}
+
+// TEST 1: Legacy Escrow Rejected
+// Test that the program refuses to act on an escrow that has not been migrated
+#[test]
+fn test_release_funds_rejects_unmigrated_escrow() {
+    println!("\n========== TEST: Unmigrated Escrow Rejected ==========\n");
+    let mut set_up = setup_escrow_test(70).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 70,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    downgrade_escrow_to_v0(&mut set_up, &params);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A version 0 escrow should be migrated before it can be taken"
+    );
+}
+
+// TEST 2: Migrate Legacy Escrow
+// Test that a version 0 escrow is upgraded in place and can then be taken
+#[test]
+fn test_migrate_escrow_from_v0() {
+    println!("\n========== TEST: Migrate Escrow From Version 0 ==========\n");
+    let mut set_up = setup_escrow_test(71).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 71,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    downgrade_escrow_to_v0(&mut set_up, &params);
+
+    let legacy_account = set_up.svm.get_account(&set_up.escrow_pda).unwrap();
+    let versioned = VersionedEscrow::unpack(&legacy_account.data).expect("failed to read v0");
+    assert_eq!(versioned.version(), 0);
+    let maker_lamports_before = set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap();
+
+    let migrate_instruction = create_migrate_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, migrate_instruction, &set_up.maker)
+        .expect("Migration failed");
+
+    let escrow_account = set_up.svm.get_account(&set_up.escrow_pda).unwrap();
+    assert_eq!(escrow_account.data.len(), Escrow::ACCOUNT_LEN);
+    assert_eq!(
+        escrow_account.lamports,
+        set_up
+            .svm
+            .minimum_balance_for_rent_exemption(Escrow::ACCOUNT_LEN),
+        "Escrow should stay rent exempt at the new size"
+    );
+    assert!(
+        set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap() < maker_lamports_before,
+        "Maker should pay for the extra rent"
+    );
+    let escrow_data =
+        Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow");
+    assert_eq!(escrow_data.offered, params.deposit_amount);
+    assert_eq!(escrow_data.receive, params.receive_amount);
+    assert_eq!(escrow_data.status, EscrowStatus::Open);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds after migration failed");
+    println!("\nMigration Test PASSED!\n");
+}
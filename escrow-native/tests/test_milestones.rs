@@ -0,0 +1,196 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, EscrowStatus};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signer::Signer};
+
+/// Reads the balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("token account not found");
+    unpack_token_account(&account.data).amount
+}
+
+/// Opens an escrow paying the taker 100 Token A in three milestones, with a deadline an hour
+/// from now
+fn open_milestone_escrow(set_up: &mut EscrowTestSetup, escrow_id: u64) -> EscrowParams {
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id,
+        deposit_amount: 100_000_000,
+        receive_amount: 0,
+        expires_at: Some(now + 3_600),
+        allowed_taker: Some(set_up.taker.pubkey()),
+        milestones: vec![20_000_000, 30_000_000, 50_000_000],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    params
+}
+
+// TEST 1: Approve One Milestone
+// Test that a milestone escrow cannot be taken, and that approving a milestone pays its tranche
+// to the recipient once
+#[test]
+fn test_milestone_approval_releases_tranche() {
+    println!("\n========== TEST: Milestone Approval Releases Tranche ==========\n");
+    let mut set_up = setup_escrow_test(230).expect("Setup failed");
+    let params = open_milestone_escrow(&mut set_up, 230);
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::InProgress);
+    assert_eq!(escrow.beneficiary, Some(set_up.taker.pubkey()));
+
+    let release_instruction = create_release_funds_instruction(&mut set_up, &params, 1);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "A milestone escrow should not be taken");
+
+    let approve_instruction = create_approve_milestone_instruction(&set_up, &params, 1);
+    send_transaction(&mut set_up.svm, approve_instruction, &set_up.maker)
+        .expect("Approve milestone failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        30_000_000
+    );
+    assert_eq!(token_balance(&set_up, &set_up.vault_pda), 70_000_000);
+
+    set_up.svm.expire_blockhash();
+    let approve_instruction = create_approve_milestone_instruction(&set_up, &params, 1);
+    let result = send_transaction(&mut set_up.svm, approve_instruction, &set_up.maker);
+    assert!(result.is_err(), "A milestone should only be approved once");
+    println!("\nMilestone Approval Test PASSED!\n");
+}
+
+// TEST 2: Approve Every Milestone
+// Test that approving the last milestone pays out the rest and closes the escrow
+#[test]
+fn test_milestone_last_approval_closes_escrow() {
+    println!("\n========== TEST: Milestone Last Approval Closes Escrow ==========\n");
+    let mut set_up = setup_escrow_test(231).expect("Setup failed");
+    let params = open_milestone_escrow(&mut set_up, 231);
+
+    for milestone in [2, 0, 1] {
+        let approve_instruction = create_approve_milestone_instruction(&set_up, &params, milestone);
+        send_transaction(&mut set_up.svm, approve_instruction, &set_up.maker)
+            .expect("Approve milestone failed");
+    }
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none(),
+        "Vault should be closed"
+    );
+    println!("\nMilestone Close Test PASSED!\n");
+}
+
+// TEST 3: Refund After Deadline
+// Test that the maker can only cancel once the deadline has passed, getting back the tranches
+// still unapproved
+#[test]
+fn test_milestone_refund_after_deadline() {
+    println!("\n========== TEST: Milestone Refund After Deadline ==========\n");
+    let mut set_up = setup_escrow_test(232).expect("Setup failed");
+    let maker_token_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+    let params = open_milestone_escrow(&mut set_up, 232);
+    let approve_instruction = create_approve_milestone_instruction(&set_up, &params, 0);
+    send_transaction(&mut set_up.svm, approve_instruction, &set_up.maker)
+        .expect("Approve milestone failed");
+
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    let result = send_transaction(&mut set_up.svm, cancel_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "Milestones should stay payable until the deadline"
+    );
+
+    let mut clock = set_up.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = params.expires_at.unwrap() + 1;
+    set_up.svm.set_sysvar(&clock);
+    set_up.svm.expire_blockhash();
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    send_transaction(&mut set_up.svm, cancel_instruction, &set_up.maker)
+        .expect("Cancel after deadline failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        maker_token_a_before - 20_000_000
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        20_000_000
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none(),
+        "Vault should be closed"
+    );
+    println!("\nMilestone Refund Test PASSED!\n");
+}
+
+// TEST 4: Milestones Must Cover The Deposit
+// Test that milestones that do not add up to the deposit are rejected
+#[test]
+fn test_milestones_must_add_up_to_deposit() {
+    println!("\n========== TEST: Milestones Must Add Up ==========\n");
+    let mut set_up = setup_escrow_test(233).expect("Setup failed");
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 233,
+        deposit_amount: 100_000_000,
+        receive_amount: 0,
+        expires_at: Some(now + 3_600),
+        allowed_taker: Some(set_up.taker.pubkey()),
+        milestones: vec![20_000_000, 30_000_000],
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(result.is_err(), "Milestones should add up to the deposit");
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nMilestone Total Test PASSED!\n");
+}
+
+// TEST 5: Re-initialize An Escrow In Progress
+// Test that the maker cannot turn a milestone escrow back into an open trade by initializing it
+// again
+#[test]
+fn test_milestone_reinitialize_fails() {
+    println!("\n========== TEST: Milestone Re-initialize Fails ==========\n");
+    let mut set_up = setup_escrow_test(234).expect("Setup failed");
+    let params = open_milestone_escrow(&mut set_up, 234);
+
+    set_up.svm.expire_blockhash();
+    let reinit_params = EscrowParams {
+        escrow_id: 234,
+        deposit_amount: params.deposit_amount,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &reinit_params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "An existing escrow should not be re-initialized"
+    );
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::InProgress);
+    assert_eq!(escrow.milestones.len(), params.milestones.len());
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    println!("\nMilestone Re-initialize Test PASSED!\n");
+}
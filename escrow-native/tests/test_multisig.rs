@@ -0,0 +1,235 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Reads the escrow state account of the current test setup
+fn escrow_state(set_up: &EscrowTestSetup) -> Escrow {
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow")
+}
+
+/// Reads the balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("token account not found");
+    unpack_token_account(&account.data).amount
+}
+
+/// Moves the maker's tokens into accounts owned by a 2-of-3 multisig of the maker and two
+/// other members, which are returned
+fn maker_multisig(set_up: &mut EscrowTestSetup) -> (Pubkey, Keypair, Keypair) {
+    let (member_2, member_3) = (Keypair::new(), Keypair::new());
+    let maker = set_up.maker.insecure_clone();
+    let multisig = create_multisig(
+        &mut set_up.svm,
+        &maker,
+        &[&maker.pubkey(), &member_2.pubkey(), &member_3.pubkey()],
+        2,
+    )
+    .expect("failed to create multisig");
+    set_up.maker_token_acc_a =
+        create_token_account(&mut set_up.svm, &maker, &set_up.mint_a_pubkey, &multisig)
+            .expect("failed to create multisig Token A account");
+    set_up.maker_token_acc_b =
+        create_token_account(&mut set_up.svm, &maker, &set_up.mint_b_pubkey, &multisig)
+            .expect("failed to create multisig Token B account");
+    mint_tokens_to(
+        &mut set_up.svm,
+        &maker,
+        &set_up.mint_a_pubkey,
+        1_000_000_000,
+        &set_up.maker_token_acc_a,
+    )
+    .expect("failed to fund multisig");
+    (multisig, member_2, member_3)
+}
+
+// TEST 1: Multisig Maker
+// Test that a multisig's members can open an escrow from its account, and that a cancel
+// returns Token A to the multisig
+#[test]
+fn test_multisig_maker_make_and_cancel() {
+    println!("\n========== TEST: Multisig Maker Make And Cancel ==========\n");
+    let mut set_up = setup_escrow_test(190).expect("Setup failed");
+    let (multisig, member_2, _member_3) = maker_multisig(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 190,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let mut init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    init_instruction.accounts.extend(multisig_accounts(
+        &multisig,
+        &[&set_up.maker.pubkey(), &member_2.pubkey()],
+    ));
+    send_transaction_with_signers(
+        &mut set_up.svm,
+        init_instruction,
+        &set_up.maker,
+        &[&member_2],
+    )
+    .expect("Initialize failed");
+    assert_eq!(escrow_state(&set_up).maker_multisig, Some(multisig));
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        900_000_000
+    );
+
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    send_transaction(&mut set_up.svm, cancel_instruction, &set_up.maker).expect("Cancel failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        1_000_000_000,
+        "The multisig should get all of Token A back"
+    );
+    println!("\nMultisig Maker Test PASSED!\n");
+}
+
+// TEST 2: Multisig Taker
+// Test that a multisig's members can take an escrow, paying Token B from the multisig's
+// account and receiving Token A into it
+#[test]
+fn test_multisig_taker_take() {
+    println!("\n========== TEST: Multisig Taker Take ==========\n");
+    let mut set_up = setup_escrow_test(191).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 191,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let member_2 = Keypair::new();
+    let taker = set_up.taker.insecure_clone();
+    let multisig = create_multisig(
+        &mut set_up.svm,
+        &taker,
+        &[&taker.pubkey(), &member_2.pubkey()],
+        2,
+    )
+    .expect("failed to create multisig");
+    set_up.taker_token_acc_a =
+        create_token_account(&mut set_up.svm, &taker, &set_up.mint_a_pubkey, &multisig)
+            .expect("failed to create multisig Token A account");
+    set_up.taker_token_acc_b =
+        create_token_account(&mut set_up.svm, &taker, &set_up.mint_b_pubkey, &multisig)
+            .expect("failed to create multisig Token B account");
+    let maker = set_up.maker.insecure_clone();
+    mint_tokens_to(
+        &mut set_up.svm,
+        &maker,
+        &set_up.mint_b_pubkey,
+        params.receive_amount,
+        &set_up.taker_token_acc_b,
+    )
+    .expect("failed to fund multisig");
+
+    let mut release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    release_instruction.accounts.extend(multisig_accounts(
+        &multisig,
+        &[&taker.pubkey(), &member_2.pubkey()],
+    ));
+    send_transaction_with_signers(&mut set_up.svm, release_instruction, &taker, &[&member_2])
+        .expect("Release funds failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_b), 0);
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount
+    );
+    println!("\nMultisig Taker Test PASSED!\n");
+}
+
+// TEST 3: Too Few Signers
+// Test that a 2-of-3 multisig cannot open an escrow with a single member's signature
+#[test]
+fn test_multisig_below_threshold_fails() {
+    println!("\n========== TEST: Multisig Below Threshold Fails ==========\n");
+    let mut set_up = setup_escrow_test(192).expect("Setup failed");
+    let (multisig, _member_2, _member_3) = maker_multisig(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 192,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let mut init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    init_instruction
+        .accounts
+        .extend(multisig_accounts(&multisig, &[&set_up.maker.pubkey()]));
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(result.is_err(), "One of three members should not be enough");
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should not be created"
+    );
+    println!("\nMultisig Threshold Test PASSED!\n");
+}
+
+// TEST 4: Outsider Wallet
+// Test that a wallet outside the multisig cannot trade its tokens, even with enough
+// members signing
+#[test]
+fn test_multisig_non_member_wallet_fails() {
+    println!("\n========== TEST: Multisig Non-Member Wallet Fails ==========\n");
+    let mut set_up = setup_escrow_test(193).expect("Setup failed");
+    let (member_1, member_2) = (Keypair::new(), Keypair::new());
+    let maker = set_up.maker.insecure_clone();
+    let multisig = create_multisig(
+        &mut set_up.svm,
+        &maker,
+        &[&member_1.pubkey(), &member_2.pubkey()],
+        2,
+    )
+    .expect("failed to create multisig");
+    set_up.maker_token_acc_a =
+        create_token_account(&mut set_up.svm, &maker, &set_up.mint_a_pubkey, &multisig)
+            .expect("failed to create multisig Token A account");
+    mint_tokens_to(
+        &mut set_up.svm,
+        &maker,
+        &set_up.mint_a_pubkey,
+        1_000_000_000,
+        &set_up.maker_token_acc_a,
+    )
+    .expect("failed to fund multisig");
+    let params = EscrowParams {
+        escrow_id: 193,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let mut init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    init_instruction.accounts.extend(multisig_accounts(
+        &multisig,
+        &[&member_1.pubkey(), &member_2.pubkey()],
+    ));
+    let result = send_transaction_with_signers(
+        &mut set_up.svm,
+        init_instruction,
+        &set_up.maker,
+        &[&member_1, &member_2],
+    );
+    assert!(
+        result.is_err(),
+        "A non-member wallet should not make for the multisig"
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a),
+        1_000_000_000
+    );
+    println!("\nMultisig Outsider Test PASSED!\n");
+}
@@ -0,0 +1,125 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, ReceiveAsset};
+use solana_sdk::{program_pack::Pack, signer::Signer};
+use spl_token::state::Account;
+
+// TEST 1: Sell Tokens For SOL
+// Test that a native SOL escrow pays the maker in lamports and releases Token A to the taker
+#[test]
+fn test_release_funds_native_sol() {
+    println!("\n========== TEST: Release Funds For Native SOL ==========\n");
+    let mut set_up = setup_escrow_test(50).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 50,
+        deposit_amount: 100_000_000,
+        receive_amount: 1_000_000_000,
+        receive_asset: ReceiveAsset::NativeSol,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow_data =
+        Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow");
+    assert_eq!(escrow_data.receive_asset, ReceiveAsset::NativeSol);
+
+    let maker_lamports_before = set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap();
+    let vault_rent = set_up.svm.get_balance(&set_up.vault_pda).unwrap();
+
+    // The maker is not a signer here, so their lamports only change by the payment and vault rent.
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    let maker_lamports_after = set_up.svm.get_balance(&set_up.maker.pubkey()).unwrap();
+    assert_eq!(
+        maker_lamports_after - maker_lamports_before - vault_rent,
+        params.receive_amount,
+        "Maker should receive the requested lamports"
+    );
+    let taker_a_after = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("failed to get taker a account after");
+    let taker_a_data_after =
+        Account::unpack(&taker_a_after.data).expect("unable to unpack taker a account data after");
+    assert_eq!(
+        taker_a_data_after.amount, params.deposit_amount,
+        "Taker should have received the deposit"
+    );
+    println!("\nNative SOL Exchange Test PASSED!\n");
+}
+
+// TEST 2: Token Payment For SOL Escrow
+// Test that a native SOL escrow cannot be paid with Token B
+#[test]
+fn test_release_funds_native_sol_rejects_token_payment() {
+    println!("\n========== TEST: Native SOL Escrow Rejects Token B ==========\n");
+    let mut set_up = setup_escrow_test(51).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 51,
+        deposit_amount: 100_000_000,
+        receive_amount: 1_000_000_000,
+        receive_asset: ReceiveAsset::NativeSol,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    // Build the take as if the escrow wanted Token B.
+    let token_params = EscrowParams {
+        receive_asset: ReceiveAsset::Token,
+        ..params
+    };
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &token_params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A native SOL escrow should not accept Token B"
+    );
+}
+
+// TEST 3: Taker Must Stay Rent Exempt
+// Test that a taker cannot pay with the lamports that keep their wallet rent exempt
+#[test]
+fn test_release_funds_native_sol_keeps_taker_rent_exempt() {
+    println!("\n========== TEST: Native SOL Taker Stays Rent Exempt ==========\n");
+    let mut set_up = setup_escrow_test(52).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 52,
+        deposit_amount: 100_000_000,
+        receive_amount: 1_000_000_000,
+        receive_asset: ReceiveAsset::NativeSol,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    // Enough for the payment and the transaction fee, but not for the wallet's rent as well.
+    let taker = set_up.taker.pubkey();
+    let mut taker_account = set_up.svm.get_account(&taker).unwrap();
+    taker_account.lamports = params.receive_amount + 10_000;
+    set_up
+        .svm
+        .set_account(taker, taker_account)
+        .expect("failed to set taker lamports");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "The taker should keep enough lamports to stay rent exempt"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_some(),
+        "The escrow should stay open"
+    );
+}
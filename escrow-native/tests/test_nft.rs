@@ -0,0 +1,136 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, OfferAsset};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+/// Replaces Token A with a new mint of 0 decimals, minting `supply` tokens to the maker
+fn setup_nft_mint(set_up: &mut EscrowTestSetup, supply: u64) {
+    let mint = Keypair::new();
+    let maker = set_up.maker.insecure_clone();
+    let taker = set_up.taker.insecure_clone();
+    create_token_mint(&mut set_up.svm, &mint, 0, &maker).expect("failed to create NFT mint");
+    set_up.mint_a_pubkey = mint.pubkey();
+    set_up.maker_token_acc_a =
+        create_token_account(&mut set_up.svm, &maker, &mint.pubkey(), &maker.pubkey())
+            .expect("failed to create maker NFT account");
+    set_up.taker_token_acc_a =
+        create_token_account(&mut set_up.svm, &taker, &mint.pubkey(), &taker.pubkey())
+            .expect("failed to create taker NFT account");
+    mint_tokens_to(
+        &mut set_up.svm,
+        &maker,
+        &mint.pubkey(),
+        supply,
+        &set_up.maker_token_acc_a,
+    )
+    .expect("failed to mint NFT");
+}
+
+// TEST 1: NFT Sale
+// Test that an NFT escrow records its mode and sells the NFT to the taker in one fill
+#[test]
+fn test_nft_sale() {
+    println!("\n========== TEST: NFT Sale ==========\n");
+    let mut set_up = setup_escrow_test(200).expect("Setup failed");
+    setup_nft_mint(&mut set_up, 1);
+    let params = EscrowParams {
+        escrow_id: 200,
+        deposit_amount: 1,
+        receive_amount: 50_000_000,
+        offer_asset: OfferAsset::Nft,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert!(escrow.is_nft());
+    assert_eq!(escrow.offered, 1);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+    let taker_token_a = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("taker NFT account not found");
+    assert_eq!(unpack_token_account(&taker_token_a.data).amount, 1);
+    println!("\nNFT Sale Test PASSED!\n");
+}
+
+// TEST 2: Fungible Mint
+// Test that a mint with decimals cannot be sold as an NFT
+#[test]
+fn test_nft_rejects_mint_with_decimals() {
+    println!("\n========== TEST: NFT Rejects Mint With Decimals ==========\n");
+    let mut set_up = setup_escrow_test(201).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 201,
+        deposit_amount: 1,
+        receive_amount: 50_000_000,
+        offer_asset: OfferAsset::Nft,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(result.is_err(), "A 9-decimal mint should not be an NFT");
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nNFT Decimals Test PASSED!\n");
+}
+
+// TEST 3: Supply Above One
+// Test that a 0-decimal mint with more than one token cannot be sold as an NFT
+#[test]
+fn test_nft_rejects_supply_above_one() {
+    println!("\n========== TEST: NFT Rejects Supply Above One ==========\n");
+    let mut set_up = setup_escrow_test(202).expect("Setup failed");
+    setup_nft_mint(&mut set_up, 2);
+    let params = EscrowParams {
+        escrow_id: 202,
+        deposit_amount: 1,
+        receive_amount: 50_000_000,
+        offer_asset: OfferAsset::Nft,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "A mint with a supply of 2 should not be an NFT"
+    );
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nNFT Supply Test PASSED!\n");
+}
+
+// TEST 4: Partial Fill
+// Test that an NFT escrow cannot be partially filled
+#[test]
+fn test_nft_partial_fill_fails() {
+    println!("\n========== TEST: NFT Partial Fill Fails ==========\n");
+    let mut set_up = setup_escrow_test(203).expect("Setup failed");
+    setup_nft_mint(&mut set_up, 1);
+    let params = EscrowParams {
+        escrow_id: 203,
+        deposit_amount: 1,
+        receive_amount: 50_000_000,
+        offer_asset: OfferAsset::Nft,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "Half the price should not buy the NFT");
+    let vault = set_up
+        .svm
+        .get_account(&set_up.vault_pda)
+        .expect("vault not found");
+    assert_eq!(unpack_token_account(&vault.data).amount, 1);
+    println!("\nNFT Partial Fill Test PASSED!\n");
+}
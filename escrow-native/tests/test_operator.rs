@@ -0,0 +1,185 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+/// Reads the escrow state account of the current test setup
+fn escrow_state(set_up: &EscrowTestSetup) -> Escrow {
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow")
+}
+
+/// Creates a funded keypair to act as the escrow's operator
+fn funded_operator(set_up: &mut EscrowTestSetup) -> Keypair {
+    let operator = Keypair::new();
+    set_up
+        .svm
+        .airdrop(&operator.pubkey(), 1_000_000_000)
+        .expect("failed to airdrop to operator");
+    operator
+}
+
+// TEST 1: Operator Cancel
+// Test that the operator can cancel the escrow and the refund still goes to the maker
+#[test]
+fn test_operator_cancel_refunds_maker() {
+    println!("\n========== TEST: Operator Cancel Refunds Maker ==========\n");
+    let mut set_up = setup_escrow_test(180).expect("Setup failed");
+    let operator = funded_operator(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 180,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        operator: Some(operator.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    assert_eq!(escrow_state(&set_up).operator, Some(operator.pubkey()));
+    let maker_lamports_before = set_up
+        .svm
+        .get_account(&set_up.maker.pubkey())
+        .expect("maker account not found")
+        .lamports;
+
+    let cancel_instruction =
+        create_operator_cancel_instruction(&mut set_up, &params, &operator.pubkey());
+    send_transaction(&mut set_up.svm, cancel_instruction, &operator).expect("Cancel failed");
+
+    let maker_token_a = set_up
+        .svm
+        .get_account(&set_up.maker_token_acc_a)
+        .expect("maker token account not found");
+    assert_eq!(
+        unpack_token_account(&maker_token_a.data).amount,
+        1_000_000_000,
+        "Maker should get all of Token A back"
+    );
+    let maker_lamports_after = set_up
+        .svm
+        .get_account(&set_up.maker.pubkey())
+        .expect("maker account not found")
+        .lamports;
+    assert!(
+        maker_lamports_after > maker_lamports_before,
+        "The maker, not the operator, should reclaim the rent"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    println!("\nOperator Cancel Test PASSED!\n");
+}
+
+// TEST 2: Operator Reprice
+// Test that the operator can reprice the escrow but cannot move Token A
+#[test]
+fn test_operator_reprice_but_not_withdraw() {
+    println!("\n========== TEST: Operator Reprice But Not Withdraw ==========\n");
+    let mut set_up = setup_escrow_test(181).expect("Setup failed");
+    let operator = funded_operator(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 181,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        operator: Some(operator.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let update_instruction = create_operator_update_instruction(
+        &set_up,
+        &params,
+        &operator.pubkey(),
+        Some(80_000_000),
+        0,
+        0,
+    );
+    send_transaction(&mut set_up.svm, update_instruction, &operator).expect("Reprice failed");
+    assert_eq!(escrow_state(&set_up).receive, 80_000_000);
+
+    let withdraw_instruction = create_operator_update_instruction(
+        &set_up,
+        &params,
+        &operator.pubkey(),
+        None,
+        0,
+        10_000_000,
+    );
+    let result = send_transaction(&mut set_up.svm, withdraw_instruction, &operator);
+    assert!(result.is_err(), "The operator should not withdraw Token A");
+    assert_eq!(escrow_state(&set_up).offered, params.deposit_amount);
+    println!("\nOperator Reprice Test PASSED!\n");
+}
+
+// TEST 3: Stranger Cancel
+// Test that a key other than the maker's operator cannot cancel the escrow
+#[test]
+fn test_non_operator_cancel_fails() {
+    println!("\n========== TEST: Non-Operator Cancel Fails ==========\n");
+    let mut set_up = setup_escrow_test(182).expect("Setup failed");
+    let operator = funded_operator(&mut set_up);
+    let stranger = funded_operator(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 182,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        operator: Some(operator.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let cancel_instruction =
+        create_operator_cancel_instruction(&mut set_up, &params, &stranger.pubkey());
+    let result = send_transaction(&mut set_up.svm, cancel_instruction, &stranger);
+    assert!(result.is_err(), "A stranger should not cancel the escrow");
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_some(),
+        "Escrow account should stay open"
+    );
+    println!("\nNon-Operator Cancel Test PASSED!\n");
+}
+
+// TEST 4: Set Operator
+// Test that the maker can revoke the operator, and the operator cannot change it
+#[test]
+fn test_set_operator_is_maker_only() {
+    println!("\n========== TEST: Set Operator Is Maker Only ==========\n");
+    let mut set_up = setup_escrow_test(183).expect("Setup failed");
+    let operator = funded_operator(&mut set_up);
+    let params = EscrowParams {
+        escrow_id: 183,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        operator: Some(operator.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let hijack_instruction = create_set_operator_instruction(
+        &set_up,
+        &params,
+        &operator.pubkey(),
+        Some(Keypair::new().pubkey()),
+    );
+    let result = send_transaction(&mut set_up.svm, hijack_instruction, &operator);
+    assert!(result.is_err(), "The operator should not replace itself");
+    assert_eq!(escrow_state(&set_up).operator, Some(operator.pubkey()));
+
+    let revoke_instruction =
+        create_set_operator_instruction(&set_up, &params, &set_up.maker.pubkey(), None);
+    send_transaction(&mut set_up.svm, revoke_instruction, &set_up.maker).expect("Revoke failed");
+    assert_eq!(escrow_state(&set_up).operator, None);
+
+    let cancel_instruction =
+        create_operator_cancel_instruction(&mut set_up, &params, &operator.pubkey());
+    let result = send_transaction(&mut set_up.svm, cancel_instruction, &operator);
+    assert!(result.is_err(), "A revoked operator should not cancel");
+    println!("\nSet Operator Test PASSED!\n");
+}
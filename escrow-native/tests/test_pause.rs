@@ -0,0 +1,101 @@
+mod common;
+use common::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+/// Creates a fee-free config with the maker as admin and sets its pause flag
+fn set_paused(set_up: &mut EscrowTestSetup, paused: bool) {
+    if set_up
+        .svm
+        .get_account(&derive_config_pda(&set_up.program_id))
+        .is_none()
+    {
+        let maker = set_up.maker.pubkey();
+        set_upgrade_authority(set_up, &maker);
+        let config_instruction = create_initialize_config_instruction(
+            set_up,
+            &set_up.maker.pubkey(),
+            0,
+            &Keypair::new().pubkey(),
+        );
+        send_transaction(&mut set_up.svm, config_instruction, &set_up.maker)
+            .expect("Initialize config failed");
+    }
+    let pause_instruction =
+        create_update_config_instruction(set_up, &set_up.maker.pubkey(), None, None, Some(paused));
+    send_transaction(&mut set_up.svm, pause_instruction, &set_up.maker)
+        .expect("Update pause flag failed");
+}
+
+// TEST 1: Pause Blocks Trading
+// Test that a paused program rejects new escrows and takes but still lets the maker cancel
+#[test]
+fn test_pause_blocks_initialize_and_take() {
+    println!("\n========== TEST: Pause Blocks Initialize And Take ==========\n");
+    let mut set_up = setup_escrow_test(100).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 100,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    set_paused(&mut set_up, true);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "A take should fail while paused");
+
+    let maker_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+    let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    send_transaction(&mut set_up.svm, cancel_instruction, &set_up.maker)
+        .expect("Cancel should work while paused");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a) - maker_a_before,
+        params.deposit_amount
+    );
+
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(result.is_err(), "A new escrow should fail while paused");
+    println!("\nPause Test PASSED!\n");
+}
+
+// TEST 2: Unpause
+// Test that unpausing the program lets escrows be created and taken again
+#[test]
+fn test_unpause_resumes_trading() {
+    println!("\n========== TEST: Unpause Resumes Trading ==========\n");
+    let mut set_up = setup_escrow_test(101).expect("Setup failed");
+    set_paused(&mut set_up, true);
+    set_paused(&mut set_up, false);
+
+    let params = EscrowParams {
+        escrow_id: 101,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker)
+        .expect("Initialize after unpause failed");
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds after unpause failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount
+    );
+    println!("\nUnpause Test PASSED!\n");
+}
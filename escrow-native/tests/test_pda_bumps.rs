@@ -0,0 +1,116 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+/// What one extra bump tried by `find_program_address` costs on chain
+const CREATE_PROGRAM_ADDRESS_UNITS: u64 = 1_500;
+
+/// How many bumps `find_program_address` has to try past the first for an escrow's two PDAs
+fn bump_search_depth(set_up: &EscrowTestSetup, escrow_id: u64) -> u64 {
+    let maker = set_up.maker.pubkey();
+    let escrow_seed = escrow_id.to_le_bytes();
+    [Escrow::VAULT_SEED, Escrow::SEED]
+        .iter()
+        .map(|seed| {
+            let (_, bump) = Pubkey::find_program_address(
+                &[seed, maker.as_ref(), escrow_seed.as_ref()],
+                &set_up.program_id,
+            );
+            255 - bump as u64
+        })
+        .sum()
+}
+
+// TEST 1: Stored Bumps
+// Test that initializing an escrow stores the bumps of its PDAs and of the config PDA
+#[test]
+fn test_initialize_stores_pda_bumps() {
+    println!("\n========== TEST: Initialize Stores PDA Bumps ==========\n");
+    let mut set_up = setup_escrow_test(140).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 140,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let escrow_account = set_up.svm.get_account(&set_up.escrow_pda).unwrap();
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.bump, set_up.escrow_bump);
+    assert_eq!(escrow.vault_bump, set_up.vault_bump);
+    assert_eq!(
+        escrow.address(&set_up.program_id).unwrap(),
+        set_up.escrow_pda
+    );
+    assert_eq!(
+        escrow.vault_address(&set_up.program_id).unwrap(),
+        set_up.vault_pda
+    );
+    assert_eq!(
+        escrow.config_address(&set_up.program_id).unwrap(),
+        derive_config_pda(&set_up.program_id)
+    );
+    println!("\nStored Bumps Test PASSED!\n");
+}
+
+// TEST 2: Cancel Cost Does Not Depend On The Bumps
+// Test that cancelling an escrow whose PDAs need a long bump search costs no more than one
+// whose PDAs are found on the first try, as it would with `find_program_address`
+#[test]
+fn test_cancel_compute_units_do_not_depend_on_bumps() {
+    println!("\n========== TEST: Cancel Compute Units Do Not Depend On Bumps ==========\n");
+    let mut set_up = setup_escrow_test(141).expect("Setup failed");
+    let shallow_id = (141..1_000)
+        .find(|&escrow_id| bump_search_depth(&set_up, escrow_id) == 0)
+        .expect("no escrow ID with both bumps at 255");
+    let deep_id = (141..1_000)
+        .find(|&escrow_id| bump_search_depth(&set_up, escrow_id) >= 3)
+        .expect("no escrow ID with a long bump search");
+
+    let mut cancel_units = Vec::new();
+    for escrow_id in [shallow_id, deep_id] {
+        let depth = bump_search_depth(&set_up, escrow_id);
+        let (vault_pda, vault_bump, escrow_pda, escrow_bump) =
+            derive_pdas(&set_up.maker.pubkey(), escrow_id, &set_up.program_id);
+        set_up.vault_pda = vault_pda;
+        set_up.vault_bump = vault_bump;
+        set_up.escrow_pda = escrow_pda;
+        set_up.escrow_bump = escrow_bump;
+        let params = EscrowParams {
+            escrow_id,
+            deposit_amount: 100_000_000,
+            receive_amount: 50_000_000,
+            ..Default::default()
+        };
+        let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+        send_transaction(&mut set_up.svm, init_instruction, &set_up.maker)
+            .expect("Initialize failed");
+        let cancel_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+        let units =
+            send_transaction_with_compute_units(&mut set_up.svm, cancel_instruction, &set_up.maker)
+                .expect("Cancel failed");
+        println!(
+            "Escrow {}: {} extra bumps to search, cancel used {} CU",
+            escrow_id, depth, units
+        );
+        cancel_units.push((depth, units));
+    }
+
+    let (_, shallow_units) = cancel_units[0];
+    let (deep_depth, deep_units) = cancel_units[1];
+    println!(
+        "Deriving the PDAs with find_program_address would have cost escrow {} about {} more CU",
+        deep_id,
+        deep_depth * CREATE_PROGRAM_ADDRESS_UNITS
+    );
+    assert!(
+        deep_units.abs_diff(shallow_units) < CREATE_PROGRAM_ADDRESS_UNITS,
+        "Cancel should not search for bumps: {} CU vs {} CU",
+        deep_units,
+        shallow_units
+    );
+    println!("\nCancel Compute Units Test PASSED!\n");
+}
@@ -0,0 +1,63 @@
+mod common;
+use common::*;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+use spl_token::state::Account;
+
+// TEST 1: Wrong Taker
+// Test that a private escrow rejects any taker other than the designated one
+#[test]
+fn test_release_funds_private_escrow_wrong_taker() {
+    println!("\n========== TEST: Private Escrow Wrong Taker ==========\n");
+    let mut set_up = setup_escrow_test(30).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 30,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        allowed_taker: Some(Keypair::new().pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Only the designated taker should be able to take a private escrow"
+    );
+}
+
+// TEST 2: Designated Taker
+// Test that the designated taker can take a private escrow
+#[test]
+fn test_release_funds_private_escrow_designated_taker() {
+    println!("\n========== TEST: Private Escrow Designated Taker ==========\n");
+    let mut set_up = setup_escrow_test(31).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 31,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        allowed_taker: Some(set_up.taker.pubkey()),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Designated taker should be able to take the escrow");
+
+    let taker_a_after = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("failed to get taker a account after");
+    let taker_a_data_after =
+        Account::unpack(&taker_a_after.data).expect("unable to unpack taker a account data after");
+    assert_eq!(
+        taker_a_data_after.amount, params.deposit_amount,
+        "Designated taker should have received the deposit"
+    );
+    println!("\nPrivate Escrow Test PASSED!\n");
+}
@@ -0,0 +1,255 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Protocol fee charged in these tests, in basis points (1%)
+const FEE_BPS: u16 = 100;
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+/// Creates the config with the maker, as the upgrade authority, as admin and returns the fee recipient's Token B account
+fn setup_protocol_fee(set_up: &mut EscrowTestSetup, fee_recipient: &Pubkey) -> Pubkey {
+    let maker = set_up.maker.pubkey();
+    set_upgrade_authority(set_up, &maker);
+    let config_instruction = create_initialize_config_instruction(
+        set_up,
+        &set_up.maker.pubkey(),
+        FEE_BPS,
+        fee_recipient,
+    );
+    send_transaction(&mut set_up.svm, config_instruction, &set_up.maker)
+        .expect("Initialize config failed");
+    let token_program_b = set_up.token_program_b;
+    create_token_account_with_program(
+        &mut set_up.svm,
+        &set_up.maker,
+        &set_up.mint_b_pubkey,
+        fee_recipient,
+        &token_program_b,
+    )
+    .expect("failed to create fee recipient account")
+}
+
+// TEST 1: Fee On Fill
+// Test that a fill pays the protocol fee to the fee recipient and the rest to the maker
+#[test]
+fn test_release_funds_pays_protocol_fee() {
+    println!("\n========== TEST: Release Funds Pays Protocol Fee ==========\n");
+    let mut set_up = setup_escrow_test(90).expect("Setup failed");
+    let fee_recipient = Keypair::new().pubkey();
+    let fee_account = setup_protocol_fee(&mut set_up, &fee_recipient);
+    let params = EscrowParams {
+        escrow_id: 90,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let mut release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    release_instruction
+        .accounts
+        .push(AccountMeta::new(fee_account, false));
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    let fee = params.receive_amount * FEE_BPS as u64 / 10_000;
+    assert_eq!(token_balance(&set_up, &fee_account), fee);
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount - fee,
+        "Maker should receive the fill minus the protocol fee"
+    );
+    println!("\nProtocol Fee Test PASSED!\n");
+}
+
+// TEST 2: Missing Fee Account
+// Test that a fill cannot skip the protocol fee by leaving out the fee account
+#[test]
+fn test_release_funds_requires_fee_account() {
+    println!("\n========== TEST: Release Funds Requires Fee Account ==========\n");
+    let mut set_up = setup_escrow_test(91).expect("Setup failed");
+    setup_protocol_fee(&mut set_up, &Keypair::new().pubkey());
+    let params = EscrowParams {
+        escrow_id: 91,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A fill without the fee account should fail"
+    );
+}
+
+// TEST 3: Update Config
+// Test that only the admin can update the config
+#[test]
+fn test_update_config_admin_only() {
+    println!("\n========== TEST: Update Config Admin Only ==========\n");
+    let mut set_up = setup_escrow_test(92).expect("Setup failed");
+    setup_protocol_fee(&mut set_up, &Keypair::new().pubkey());
+
+    let update_instruction =
+        create_update_config_instruction(&set_up, &set_up.taker.pubkey(), Some(0), None, None);
+    let result = send_transaction(&mut set_up.svm, update_instruction, &set_up.taker);
+    assert!(result.is_err(), "Only the admin should update the config");
+
+    let too_high_instruction =
+        create_update_config_instruction(&set_up, &set_up.maker.pubkey(), Some(1_001), None, None);
+    let result = send_transaction(&mut set_up.svm, too_high_instruction, &set_up.maker);
+    assert!(result.is_err(), "A fee above 10% should be rejected");
+
+    let update_instruction =
+        create_update_config_instruction(&set_up, &set_up.maker.pubkey(), Some(0), None, None);
+    send_transaction(&mut set_up.svm, update_instruction, &set_up.maker)
+        .expect("Admin update failed");
+
+    // With no fee, a fill no longer needs the fee account
+    let params = EscrowParams {
+        escrow_id: 92,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds without a fee failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount
+    );
+    println!("\nUpdate Config Test PASSED!\n");
+}
+
+// TEST 4: Initialize Config
+// Test that only the program's upgrade authority can create the config
+#[test]
+fn test_initialize_config_upgrade_authority_only() {
+    println!("\n========== TEST: Initialize Config Upgrade Authority Only ==========\n");
+    let mut set_up = setup_escrow_test(93).expect("Setup failed");
+    let maker = set_up.maker.pubkey();
+    set_upgrade_authority(&mut set_up, &maker);
+
+    let config_instruction = create_initialize_config_instruction(
+        &set_up,
+        &set_up.taker.pubkey(),
+        FEE_BPS,
+        &set_up.taker.pubkey(),
+    );
+    let result = send_transaction(&mut set_up.svm, config_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Only the upgrade authority should create the config"
+    );
+    assert!(set_up
+        .svm
+        .get_account(&derive_config_pda(&set_up.program_id))
+        .is_none());
+
+    let config_instruction =
+        create_initialize_config_instruction(&set_up, &maker, FEE_BPS, &set_up.taker.pubkey());
+    send_transaction(&mut set_up.svm, config_instruction, &set_up.maker)
+        .expect("Initialize config failed");
+    println!("\nInitialize Config Test PASSED!\n");
+}
+
+// TEST 5: Fee Fixed At Creation
+// Test that raising the fee does not change the fee of an escrow that is already open
+#[test]
+fn test_open_escrow_keeps_its_fee() {
+    println!("\n========== TEST: Open Escrow Keeps Its Fee ==========\n");
+    let mut set_up = setup_escrow_test(94).expect("Setup failed");
+    let fee_account = setup_protocol_fee(&mut set_up, &Keypair::new().pubkey());
+    let params = EscrowParams {
+        escrow_id: 94,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let update_instruction =
+        create_update_config_instruction(&set_up, &set_up.maker.pubkey(), Some(1_000), None, None);
+    send_transaction(&mut set_up.svm, update_instruction, &set_up.maker)
+        .expect("Admin update failed");
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.fee_bps, FEE_BPS);
+
+    let mut release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    release_instruction
+        .accounts
+        .push(AccountMeta::new(fee_account, false));
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    let fee = params.receive_amount * FEE_BPS as u64 / 10_000;
+    assert_eq!(token_balance(&set_up, &fee_account), fee);
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount - fee,
+        "The escrow should pay the fee configured when it was created"
+    );
+    println!("\nFee Fixed At Creation Test PASSED!\n");
+}
+
+// TEST 6: Pre-funded Config
+// Test that sending lamports to the config PDA before it exists does not block its creation
+#[test]
+fn test_initialize_config_with_prefunded_pda() {
+    println!("\n========== TEST: Initialize Config With Pre-funded PDA ==========\n");
+    let mut set_up = setup_escrow_test(95).expect("Setup failed");
+    let maker = set_up.maker.pubkey();
+    set_upgrade_authority(&mut set_up, &maker);
+    let config_pda = derive_config_pda(&set_up.program_id);
+    set_up
+        .svm
+        .airdrop(&config_pda, 1_000_000)
+        .expect("failed to fund config PDA");
+
+    let config_instruction =
+        create_initialize_config_instruction(&set_up, &maker, FEE_BPS, &set_up.taker.pubkey());
+    send_transaction(&mut set_up.svm, config_instruction, &set_up.maker)
+        .expect("Initialize config failed");
+    let config_account = set_up.svm.get_account(&config_pda).unwrap();
+    assert_eq!(config_account.owner, set_up.program_id);
+    assert!(
+        config_account.lamports
+            >= set_up
+                .svm
+                .minimum_balance_for_rent_exemption(config_account.data.len()),
+        "Config should be topped up to rent exemption"
+    );
+
+    let config_instruction =
+        create_initialize_config_instruction(&set_up, &maker, FEE_BPS, &set_up.taker.pubkey());
+    set_up.svm.expire_blockhash();
+    let result = send_transaction(&mut set_up.svm, config_instruction, &set_up.maker);
+    assert!(result.is_err(), "The config should only be created once");
+    println!("\nPre-funded Config Test PASSED!\n");
+}
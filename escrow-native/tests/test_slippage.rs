@@ -0,0 +1,162 @@
+mod common;
+use common::*;
+
+/// Transfer fee charged by both Token-2022 test mints, in basis points (1%)
+const TRANSFER_FEE_BPS: u16 = 100;
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &solana_sdk::pubkey::Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+// TEST 1: Take Within Limits
+// Test that a take succeeds when it receives exactly the minimum and pays exactly the maximum
+#[test]
+fn test_release_funds_within_slippage_limits() {
+    println!("\n========== TEST: Release Funds Within Slippage Limits ==========\n");
+    let mut set_up = setup_escrow_test(160).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 160,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction = create_release_funds_instruction_with_limits(
+        &mut set_up,
+        &params,
+        params.receive_amount,
+        params.deposit_amount,
+        params.receive_amount,
+        params.deposit_amount,
+        params.receive_amount,
+    );
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    println!("\nSlippage Limits Test PASSED!\n");
+}
+
+// TEST 2: Too Little Token A
+// Test that a take fails if it would release less Token A than the taker's minimum
+#[test]
+fn test_release_funds_below_min_token_a_fails() {
+    println!("\n========== TEST: Release Funds Below Minimum Token A ==========\n");
+    let mut set_up = setup_escrow_test(161).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 161,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction = create_release_funds_instruction_with_limits(
+        &mut set_up,
+        &params,
+        params.receive_amount,
+        params.deposit_amount,
+        params.receive_amount,
+        params.deposit_amount + 1,
+        params.receive_amount,
+    );
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Take below the minimum Token A should fail"
+    );
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_a), 0);
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_some(),
+        "Escrow should stay open"
+    );
+    println!("\nMinimum Token A Test PASSED!\n");
+}
+
+// TEST 3: Transfer Fee Over The Maximum
+// Test that a take fails if Token B's transfer fee pushes the payment over the taker's maximum
+#[test]
+fn test_release_funds_transfer_fee_exceeds_max_token_b() {
+    println!("\n========== TEST: Transfer Fee Exceeds Maximum Token B ==========\n");
+    let mut set_up =
+        setup_escrow_test_with_token_program(162, &spl_token_2022::ID, Some(TRANSFER_FEE_BPS))
+            .expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 162,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let taker_b_before = token_balance(&set_up, &set_up.taker_token_acc_b);
+
+    // The vault holds the deposit net of the 1% fee, and the taker nets 1% less again.
+    let release_instruction = create_release_funds_instruction_with_limits(
+        &mut set_up,
+        &params,
+        params.receive_amount,
+        99_000_000,
+        params.receive_amount,
+        98_010_000,
+        params.receive_amount,
+    );
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Take should fail when the transfer fee exceeds the maximum Token B"
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_b),
+        taker_b_before
+    );
+    println!("\nMaximum Token B Test PASSED!\n");
+}
+
+// TEST 4: Quote Outlives Another Fill
+// Test that a partial fill by another taker does not invalidate a quote at the same price
+#[test]
+fn test_release_funds_quote_survives_partial_fill() {
+    println!("\n========== TEST: Quote Survives Partial Fill ==========\n");
+    let mut set_up = setup_escrow_test(163).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 163,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let release_instruction = create_release_funds_instruction(&mut set_up, &params, 20_000_000);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("First fill failed");
+
+    // Quoted before the first fill landed, at the same price
+    set_up.svm.expire_blockhash();
+    let release_instruction = create_release_funds_instruction_with_terms(
+        &mut set_up,
+        &params,
+        10_000_000,
+        params.deposit_amount,
+        params.receive_amount,
+    );
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Fill quoted before the first fill failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        60_000_000
+    );
+    assert_eq!(token_balance(&set_up, &set_up.vault_pda), 40_000_000);
+    println!("\nQuote Survives Partial Fill Test PASSED!\n");
+}
@@ -0,0 +1,84 @@
+mod common;
+use common::*;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+// TEST 1: Take Creates Missing Accounts
+// Test that a take creates the taker's Token A and the maker's Token B associated token
+// accounts when they do not exist yet
+#[test]
+fn test_release_funds_creates_missing_token_accounts() {
+    println!("\n========== TEST: Release Funds Creates Missing Token Accounts ==========\n");
+    let mut set_up = setup_escrow_test(170).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 170,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    // Both accounts are empty, so removing them only drops their rent.
+    for account in [set_up.taker_token_acc_a, set_up.maker_token_acc_b] {
+        set_up
+            .svm
+            .set_account(account, Account::default())
+            .expect("failed to remove token account");
+        assert!(set_up.svm.get_account(&account).is_none());
+    }
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    let taker_token_a = set_up
+        .svm
+        .get_account(&set_up.taker_token_acc_a)
+        .expect("Taker's Token A account should be created");
+    assert_eq!(
+        unpack_token_account(&taker_token_a.data).amount,
+        params.deposit_amount
+    );
+    let maker_token_b = set_up
+        .svm
+        .get_account(&set_up.maker_token_acc_b)
+        .expect("Maker's Token B account should be created");
+    assert_eq!(
+        unpack_token_account(&maker_token_b.data).amount,
+        params.receive_amount
+    );
+    println!("\nAccount Creation Test PASSED!\n");
+}
+
+// TEST 2: Missing Account At Another Address
+// Test that a missing Token A account is only created at the taker's associated token address
+#[test]
+fn test_release_funds_rejects_missing_non_associated_account() {
+    println!(
+        "\n========== TEST: Release Funds Rejects Missing Non-Associated Account ==========\n"
+    );
+    let mut set_up = setup_escrow_test(171).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 171,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    set_up.taker_token_acc_a = Pubkey::new_unique();
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A missing account that is not the taker's associated token account should not be created"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_some(),
+        "Escrow should stay open"
+    );
+    println!("\nNon-Associated Account Test PASSED!\n");
+}
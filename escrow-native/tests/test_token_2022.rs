@@ -0,0 +1,172 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+use solana_sdk::{instruction::AccountMeta, signature::Keypair, signer::Signer};
+
+/// Transfer fee charged by both Token-2022 test mints, in basis points (1%)
+const TRANSFER_FEE_BPS: u16 = 100;
+
+/// Protocol fee charged on the accepted bid, in basis points (1%)
+const PROTOCOL_FEE_BPS: u16 = 100;
+
+/// Reads the token balance of an account owned by either token program
+fn token_balance(set_up: &EscrowTestSetup, account: &solana_sdk::pubkey::Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+// TEST 1: Token-2022 Exchange With Transfer Fees
+// Test that a full exchange of Token-2022 mints nets the maker exactly the requested amount
+#[test]
+fn test_release_funds_token_2022_transfer_fee() {
+    println!("\n========== TEST: Token-2022 Exchange With Transfer Fee ==========\n");
+    let mut set_up =
+        setup_escrow_test_with_token_program(40, &spl_token_2022::ID, Some(TRANSFER_FEE_BPS))
+            .expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 40,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    // The vault only receives the deposit net of the 1% fee, and only that is on offer.
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow_data =
+        Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow");
+    assert_eq!(
+        escrow_data.offered, 99_000_000,
+        "Offered should be net of fees"
+    );
+    assert_eq!(token_balance(&set_up, &set_up.vault_pda), 99_000_000);
+
+    let maker_b_before = token_balance(&set_up, &set_up.maker_token_acc_b);
+    let taker_b_before = token_balance(&set_up, &set_up.taker_token_acc_b);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b) - maker_b_before,
+        params.receive_amount,
+        "Maker should net exactly the requested Token B"
+    );
+    assert!(
+        taker_b_before - token_balance(&set_up, &set_up.taker_token_acc_b) > params.receive_amount,
+        "Taker should pay the Token B transfer fee on top"
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        98_010_000,
+        "Taker should receive the vault balance net of the Token A fee"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none()
+            || set_up.svm.get_account(&set_up.vault_pda).unwrap().lamports == 0,
+        "Vault should be closed after its withheld fees are harvested"
+    );
+    println!("\nToken-2022 Exchange Test PASSED!\n");
+}
+
+// TEST 2: Token-2022 Cancel With Transfer Fees
+// Test that a Token-2022 escrow with withheld fees in the vault can be cancelled and closed
+#[test]
+fn test_cancel_escrow_token_2022_transfer_fee() {
+    println!("\n========== TEST: Token-2022 Cancel With Transfer Fee ==========\n");
+    let mut set_up =
+        setup_escrow_test_with_token_program(41, &spl_token_2022::ID, Some(TRANSFER_FEE_BPS))
+            .expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 41,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let maker_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+
+    let refund_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    send_transaction(&mut set_up.svm, refund_instruction, &set_up.maker).expect("Cancel failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a) - maker_a_before,
+        98_010_000,
+        "Maker should get the vault balance back net of the Token A fee"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none()
+            || set_up.svm.get_account(&set_up.vault_pda).unwrap().lamports == 0,
+        "Vault should be closed after its withheld fees are harvested"
+    );
+    println!("\nToken-2022 Cancel Test PASSED!\n");
+}
+
+// TEST 3: Token-2022 Accepted Bid With Protocol Fee
+// Test that the fee recipient of an accepted bid nets the whole protocol fee after transfer fees
+#[test]
+fn test_accept_bid_token_2022_protocol_fee() {
+    println!("\n========== TEST: Token-2022 Accept Bid With Protocol Fee ==========\n");
+    let mut set_up =
+        setup_escrow_test_with_token_program(42, &spl_token_2022::ID, Some(TRANSFER_FEE_BPS))
+            .expect("Setup failed");
+    let maker = set_up.maker.pubkey();
+    set_upgrade_authority(&mut set_up, &maker);
+    let fee_recipient = Keypair::new().pubkey();
+    let config_instruction =
+        create_initialize_config_instruction(&set_up, &maker, PROTOCOL_FEE_BPS, &fee_recipient);
+    send_transaction(&mut set_up.svm, config_instruction, &set_up.maker)
+        .expect("Initialize config failed");
+    let token_program_b = set_up.token_program_b;
+    let fee_account = create_token_account_with_program(
+        &mut set_up.svm,
+        &set_up.maker,
+        &set_up.mint_b_pubkey,
+        &fee_recipient,
+        &token_program_b,
+    )
+    .expect("failed to create fee recipient account");
+    let params = EscrowParams {
+        escrow_id: 42,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let bid_instruction = create_place_bid_instruction(&set_up, &params, 40_000_000, 99_000_000);
+    send_transaction(&mut set_up.svm, bid_instruction, &set_up.taker).expect("Place bid failed");
+    let (_, bid_vault_pda) = derive_bid_pdas(&set_up);
+    assert_eq!(token_balance(&set_up, &bid_vault_pda), 39_600_000);
+
+    let mut accept_instruction = create_accept_bid_instruction(&set_up, &params);
+    accept_instruction
+        .accounts
+        .push(AccountMeta::new(fee_account, false));
+    send_transaction(&mut set_up.svm, accept_instruction, &set_up.maker)
+        .expect("Accept bid failed");
+
+    // 1% of the 39.6 Token B in the bid vault, grossed up to 0.4 so the recipient nets 0.396.
+    assert_eq!(
+        token_balance(&set_up, &fee_account),
+        396_000,
+        "Fee recipient should net the whole protocol fee"
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        38_808_000,
+        "Maker should receive the rest of the bid vault net of the Token B fee"
+    );
+    println!("\nToken-2022 Accept Bid Test PASSED!\n");
+}
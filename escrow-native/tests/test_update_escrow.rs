@@ -0,0 +1,178 @@
+mod common;
+use common::*;
+use escrow_native::state::Escrow;
+
+/// Reads the token balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &solana_sdk::pubkey::Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("failed to get token account");
+    unpack_token_account(&account.data).amount
+}
+
+/// Reads the escrow state account of the current test setup
+fn escrow_state(set_up: &EscrowTestSetup) -> Escrow {
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack escrow")
+}
+
+// TEST 1: Reprice
+// Test that a repriced escrow rejects takes quoted at the old price and honours the new one
+#[test]
+fn test_update_escrow_reprice() {
+    println!("\n========== TEST: Update Escrow Reprice ==========\n");
+    let mut set_up = setup_escrow_test(80).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 80,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    let new_receive = 80_000_000;
+    let update_instruction =
+        create_update_escrow_instruction(&set_up, &params, Some(new_receive), 0, 0);
+    send_transaction(&mut set_up.svm, update_instruction, &set_up.maker).expect("Update failed");
+    assert_eq!(escrow_state(&set_up).receive, new_receive);
+
+    // A taker that quoted the old price must not be charged the new one
+    let stale_instruction = create_release_funds_instruction_with_terms(
+        &mut set_up,
+        &params,
+        params.receive_amount,
+        params.deposit_amount,
+        params.receive_amount,
+    );
+    let result = send_transaction(&mut set_up.svm, stale_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "A take quoted before repricing should fail"
+    );
+
+    let release_instruction = create_release_funds_instruction(&mut set_up, &params, new_receive);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds at the new price failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        new_receive,
+        "Maker should receive the new price"
+    );
+    println!("\nReprice Test PASSED!\n");
+}
+
+// TEST 2: Top Up
+// Test that the maker can add Token A to an open escrow
+#[test]
+fn test_update_escrow_top_up() {
+    println!("\n========== TEST: Update Escrow Top Up ==========\n");
+    let mut set_up = setup_escrow_test(81).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 81,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let maker_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+
+    let top_up = 50_000_000;
+    let update_instruction = create_update_escrow_instruction(&set_up, &params, None, top_up, 0);
+    send_transaction(&mut set_up.svm, update_instruction, &set_up.maker).expect("Top up failed");
+
+    assert_eq!(
+        maker_a_before - token_balance(&set_up, &set_up.maker_token_acc_a),
+        top_up
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount + top_up
+    );
+    let escrow_data = escrow_state(&set_up);
+    assert_eq!(escrow_data.offered, params.deposit_amount + top_up);
+    assert_eq!(escrow_data.receive, params.receive_amount);
+    println!("\nTop Up Test PASSED!\n");
+}
+
+// TEST 3: Withdraw
+// Test that the maker can withdraw part of the offer but not all of it
+#[test]
+fn test_update_escrow_withdraw() {
+    println!("\n========== TEST: Update Escrow Withdraw ==========\n");
+    let mut set_up = setup_escrow_test(82).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 82,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    let maker_a_before = token_balance(&set_up, &set_up.maker_token_acc_a);
+
+    let withdraw = 40_000_000;
+    let update_instruction =
+        create_update_escrow_instruction(&set_up, &params, Some(30_000_000), 0, withdraw);
+    send_transaction(&mut set_up.svm, update_instruction, &set_up.maker).expect("Withdraw failed");
+
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_a) - maker_a_before,
+        withdraw
+    );
+    let escrow_data = escrow_state(&set_up);
+    assert_eq!(escrow_data.offered, params.deposit_amount - withdraw);
+    assert_eq!(escrow_data.receive, 30_000_000);
+
+    // Withdrawing everything that is left must go through CancelEscrow instead
+    let drain_instruction =
+        create_update_escrow_instruction(&set_up, &params, None, 0, escrow_data.offered);
+    let result = send_transaction(&mut set_up.svm, drain_instruction, &set_up.maker);
+    assert!(result.is_err(), "Withdrawing the whole offer should fail");
+    println!("\nWithdraw Test PASSED!\n");
+}
+
+// TEST 4: Rounded Partial Fill
+// Test that a partial fill rounding the released Token A down keeps a quote at the original price valid
+#[test]
+fn test_quote_survives_rounded_partial_fill() {
+    println!("\n========== TEST: Quote Survives Rounded Partial Fill ==========\n");
+    let mut set_up = setup_escrow_test(83).expect("Setup failed");
+    let params = EscrowParams {
+        escrow_id: 83,
+        deposit_amount: 100,
+        receive_amount: 3,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+
+    // 1 of 3 Token B releases 33 of 100 Token A, leaving 67 for 2
+    let first_fill = create_release_funds_instruction(&mut set_up, &params, 1);
+    send_transaction(&mut set_up.svm, first_fill, &set_up.taker).expect("Partial fill failed");
+    let escrow_data = escrow_state(&set_up);
+    assert_eq!(escrow_data.offered, 67);
+    assert_eq!(escrow_data.receive, 2);
+
+    // A taker still quoting the advertised 100 for 3 can take the rest
+    let final_fill = create_release_funds_instruction_with_terms(
+        &mut set_up,
+        &params,
+        escrow_data.receive,
+        params.deposit_amount,
+        params.receive_amount,
+    );
+    send_transaction(&mut set_up.svm, final_fill, &set_up.taker)
+        .expect("A take quoted at the advertised price should succeed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount,
+        "Taker should have received the whole deposit across both fills"
+    );
+    println!("\nRounded Partial Fill Test PASSED!\n");
+}
@@ -0,0 +1,211 @@
+mod common;
+use common::*;
+use escrow_native::state::{Escrow, EscrowStatus, VestingSchedule};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signer::Signer};
+
+/// Reads the balance of a token account
+fn token_balance(set_up: &EscrowTestSetup, account: &Pubkey) -> u64 {
+    let account = set_up
+        .svm
+        .get_account(account)
+        .expect("token account not found");
+    unpack_token_account(&account.data).amount
+}
+
+/// Moves the test clock to the given unix timestamp
+fn warp_to(set_up: &mut EscrowTestSetup, unix_timestamp: i64) {
+    let mut clock = set_up.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = unix_timestamp;
+    set_up.svm.set_sysvar(&clock);
+    set_up.svm.expire_blockhash();
+}
+
+/// Opens an escrow vesting its Token A over 1000 seconds from an hour from now
+fn open_vesting_escrow(set_up: &mut EscrowTestSetup, escrow_id: u64) -> EscrowParams {
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        vesting: Some(VestingSchedule {
+            start: now + 3_600,
+            end: now + 4_600,
+        }),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(set_up, &params);
+    send_transaction(&mut set_up.svm, init_instruction, &set_up.maker).expect("Initialize failed");
+    params
+}
+
+// TEST 1: Take Keeps Token A Locked
+// Test that the taker pays up front, becomes the beneficiary, and cannot claim before the start
+#[test]
+fn test_vesting_take_locks_token_a() {
+    println!("\n========== TEST: Vesting Take Locks Token A ==========\n");
+    let mut set_up = setup_escrow_test(210).expect("Setup failed");
+    let params = open_vesting_escrow(&mut set_up, 210);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.maker_token_acc_b),
+        params.receive_amount
+    );
+    assert_eq!(token_balance(&set_up, &set_up.taker_token_acc_a), 0);
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::Vesting);
+    assert_eq!(escrow.beneficiary, Some(set_up.taker.pubkey()));
+
+    let claim_instruction = create_claim_vested_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, claim_instruction, &set_up.taker);
+    assert!(
+        result.is_err(),
+        "Nothing should be claimable before the start"
+    );
+    println!("\nVesting Take Test PASSED!\n");
+}
+
+// TEST 2: Linear Claims
+// Test that half of Token A is claimable halfway through, and the rest at the end, which
+// closes the escrow
+#[test]
+fn test_vesting_claims_unlock_linearly() {
+    println!("\n========== TEST: Vesting Claims Unlock Linearly ==========\n");
+    let mut set_up = setup_escrow_test(211).expect("Setup failed");
+    let params = open_vesting_escrow(&mut set_up, 211);
+    let vesting = params.vesting.expect("vesting schedule");
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    warp_to(&mut set_up, vesting.start + 500);
+    let claim_instruction = create_claim_vested_instruction(&set_up, &params);
+    // Anyone may claim; the tokens still go to the taker.
+    send_transaction(&mut set_up.svm, claim_instruction, &set_up.maker).expect("Claim failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount / 2
+    );
+
+    warp_to(&mut set_up, vesting.end);
+    let claim_instruction = create_claim_vested_instruction(&set_up, &params);
+    send_transaction(&mut set_up.svm, claim_instruction, &set_up.taker)
+        .expect("Final claim failed");
+    assert_eq!(
+        token_balance(&set_up, &set_up.taker_token_acc_a),
+        params.deposit_amount
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.escrow_pda).is_none(),
+        "Escrow account should be closed"
+    );
+    assert!(
+        set_up.svm.get_account(&set_up.vault_pda).is_none(),
+        "Vault account should be closed"
+    );
+    println!("\nVesting Claims Test PASSED!\n");
+}
+
+// TEST 3: Partial Fill
+// Test that a vesting escrow cannot be partially filled
+#[test]
+fn test_vesting_partial_fill_fails() {
+    println!("\n========== TEST: Vesting Partial Fill Fails ==========\n");
+    let mut set_up = setup_escrow_test(212).expect("Setup failed");
+    let params = open_vesting_escrow(&mut set_up, 212);
+
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount / 2);
+    let result = send_transaction(&mut set_up.svm, release_instruction, &set_up.taker);
+    assert!(result.is_err(), "A vesting escrow should only sell in full");
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    println!("\nVesting Partial Fill Test PASSED!\n");
+}
+
+// TEST 4: Backwards Schedule
+// Test that a vesting schedule must end after it starts
+#[test]
+fn test_vesting_rejects_end_before_start() {
+    println!("\n========== TEST: Vesting Rejects End Before Start ==========\n");
+    let mut set_up = setup_escrow_test(213).expect("Setup failed");
+    let now = set_up.svm.get_sysvar::<Clock>().unix_timestamp;
+    let params = EscrowParams {
+        escrow_id: 213,
+        deposit_amount: 100_000_000,
+        receive_amount: 50_000_000,
+        vesting: Some(VestingSchedule {
+            start: now + 4_600,
+            end: now + 3_600,
+        }),
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "A schedule ending before it starts should fail"
+    );
+    assert!(set_up.svm.get_account(&set_up.escrow_pda).is_none());
+    println!("\nVesting Schedule Test PASSED!\n");
+}
+
+// TEST 5: Re-initialize A Taken Escrow
+// Test that the maker cannot initialize a taken vesting escrow again to cancel it afterwards
+#[test]
+fn test_vesting_reinitialize_fails() {
+    println!("\n========== TEST: Vesting Re-initialize Fails ==========\n");
+    let mut set_up = setup_escrow_test(214).expect("Setup failed");
+    let params = open_vesting_escrow(&mut set_up, 214);
+    let release_instruction =
+        create_release_funds_instruction(&mut set_up, &params, params.receive_amount);
+    send_transaction(&mut set_up.svm, release_instruction, &set_up.taker)
+        .expect("Release funds failed");
+
+    set_up.svm.expire_blockhash();
+    let reinit_params = EscrowParams {
+        escrow_id: 214,
+        deposit_amount: params.deposit_amount,
+        receive_amount: params.receive_amount,
+        ..Default::default()
+    };
+    let init_instruction = create_initialize_escrow_instruction(&set_up, &reinit_params);
+    let result = send_transaction(&mut set_up.svm, init_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "An existing escrow should not be re-initialized"
+    );
+    let escrow_account = set_up
+        .svm
+        .get_account(&set_up.escrow_pda)
+        .expect("failed to get escrow account");
+    let escrow = Escrow::unpack_the_slice_data(&escrow_account.data).expect("failed to unpack");
+    assert_eq!(escrow.status, EscrowStatus::Vesting);
+    assert_eq!(escrow.beneficiary, Some(set_up.taker.pubkey()));
+
+    let refund_instruction = create_refund_escrow_instruction(&mut set_up, &params);
+    let result = send_transaction(&mut set_up.svm, refund_instruction, &set_up.maker);
+    assert!(
+        result.is_err(),
+        "A taken vesting escrow should not be cancelled"
+    );
+    assert_eq!(
+        token_balance(&set_up, &set_up.vault_pda),
+        params.deposit_amount
+    );
+    println!("\nVesting Re-initialize Test PASSED!\n");
+}